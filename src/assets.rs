@@ -1,6 +1,9 @@
 use crate::platform::Color;
+use crate::renderer::TextureFilter;
+use fontdue::Font;
+use image::imageops::FilterType;
 use image::{Rgba, RgbaImage};
-use mlua::{Lua, Table, UserData, UserDataMethods, Value, Variadic};
+use mlua::{AnyUserData, Lua, Table, UserData, UserDataMethods, Value, Variadic};
 use std::collections::HashMap;
 use std::io::Cursor;
 use std::path::{Component, Path, PathBuf};
@@ -12,6 +15,13 @@ struct ImageAsset {
     unloaded: bool,
     revision: u64,
     export_root: Option<PathBuf>,
+    // set while a background decode (loadImageAsync) is still filling `image`
+    loading: bool,
+    load_error: Option<String>,
+    // per-image override of app.nearestNeighborScaling
+    filter: Option<TextureFilter>,
+    // rgb already multiplied by alpha (premultiplyAlpha); renderers blend it accordingly
+    premultiplied: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -30,11 +40,38 @@ struct SoundAsset {
 #[derive(Clone, Debug)]
 pub(crate) struct SoundHandle(Arc<Mutex<SoundAsset>>);
 
+struct FontAsset {
+    // key shared with the renderer's font cache
+    path: String,
+    font: Arc<Font>,
+}
+
+#[derive(Clone)]
+pub(crate) struct FontAssetHandle(Arc<FontAsset>);
+
 #[derive(Debug)]
 pub(crate) struct AssetManager {
     env_root: PathBuf,
     images: HashMap<PathBuf, Weak<Mutex<ImageAsset>>>,
     sounds: HashMap<PathBuf, Weak<Mutex<SoundAsset>>>,
+    fonts: HashMap<PathBuf, Weak<FontAsset>>,
+    // images from newImage, newRenderTarget and newImageFromData; not cached, only tracked
+    // so stats() can count them
+    created_images: Vec<Weak<Mutex<ImageAsset>>>,
+    // assets.setAutoGc: seconds between automatic gc() runs, and time since the last one
+    auto_gc_interval: Option<f32>,
+    auto_gc_elapsed: f32,
+}
+
+pub(crate) type SharedAssetManager = Arc<Mutex<AssetManager>>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct AssetStats {
+    pub(crate) images: usize,
+    pub(crate) image_bytes: usize,
+    pub(crate) sounds: usize,
+    pub(crate) sound_bytes: usize,
+    pub(crate) fonts: usize,
 }
 
 fn lua_color4(lua: &Lua, color: Color) -> mlua::Result<Table> {
@@ -46,7 +83,7 @@ fn lua_color4(lua: &Lua, color: Color) -> mlua::Result<Table> {
     Ok(table)
 }
 
-fn color4_table_to_color(table: Table) -> mlua::Result<Color> {
+pub(crate) fn color4_table_to_color(table: Table) -> mlua::Result<Color> {
     let r: f32 = table.get("r")?;
     let g: f32 = table.get("g")?;
     let b: f32 = table.get("b")?;
@@ -133,6 +170,193 @@ fn encode_wav_bytes(sample_rate: u32, channels: u16, samples: &[f32]) -> mlua::R
     Ok(bytes)
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SoundFormat {
+    Wav,
+    Ogg,
+    Mp3,
+}
+
+fn detect_sound_format(path: &Path, bytes: &[u8]) -> Option<SoundFormat> {
+    if bytes.starts_with(b"RIFF") {
+        return Some(SoundFormat::Wav);
+    }
+    if bytes.starts_with(b"OggS") {
+        return Some(SoundFormat::Ogg);
+    }
+    // an ID3v2 tag, or a bare MPEG frame sync (11 set bits) with the Layer III bits; AAC ADTS
+    // headers share the sync but leave the layer bits at 0
+    if bytes.starts_with(b"ID3")
+        || (bytes.len() >= 2
+            && bytes[0] == 0xFF
+            && bytes[1] & 0xE0 == 0xE0
+            && bytes[1] & 0x06 == 0x02)
+    {
+        return Some(SoundFormat::Mp3);
+    }
+    match path
+        .extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("wav") | Some("wave") => Some(SoundFormat::Wav),
+        Some("ogg") | Some("oga") => Some(SoundFormat::Ogg),
+        Some("mp3") => Some(SoundFormat::Mp3),
+        _ => None,
+    }
+}
+
+fn decode_wav_samples(path: &Path, bytes: &[u8]) -> mlua::Result<(u32, u16, Vec<f32>)> {
+    let mut reader = hound::WavReader::new(Cursor::new(bytes))
+        .map_err(|error| asset_decode_error("wav file", path, error))?;
+    let spec = reader.spec();
+    let mut samples = Vec::new();
+    match spec.sample_format {
+        hound::SampleFormat::Float => {
+            for sample in reader.samples::<f32>() {
+                samples.push(
+                    sample
+                        .map_err(|error| asset_decode_error("wav sample", path, error))?
+                        .clamp(-1.0, 1.0),
+                );
+            }
+        }
+        hound::SampleFormat::Int => {
+            let max = ((1u64 << spec.bits_per_sample.saturating_sub(1)) as f32) - 1.0;
+            if spec.bits_per_sample <= 16 {
+                for sample in reader.samples::<i16>() {
+                    samples.push(
+                        (sample.map_err(|error| asset_decode_error("wav sample", path, error))?
+                            as f32
+                            / max)
+                            .clamp(-1.0, 1.0),
+                    );
+                }
+            } else {
+                for sample in reader.samples::<i32>() {
+                    samples.push(
+                        (sample.map_err(|error| asset_decode_error("wav sample", path, error))?
+                            as f32
+                            / max)
+                            .clamp(-1.0, 1.0),
+                    );
+                }
+            }
+        }
+    }
+    Ok((spec.sample_rate, spec.channels, samples))
+}
+
+fn decode_ogg_samples(path: &Path, bytes: &[u8]) -> mlua::Result<(u32, u16, Vec<f32>)> {
+    let mut reader = lewton::inside_ogg::OggStreamReader::new(Cursor::new(bytes))
+        .map_err(|error| asset_decode_error("ogg file", path, error))?;
+    let sample_rate = reader.ident_hdr.audio_sample_rate;
+    let channels = reader.ident_hdr.audio_channels as u16;
+    let mut samples = Vec::new();
+    while let Some(packet) = reader
+        .read_dec_packet_itl()
+        .map_err(|error| asset_decode_error("ogg packet", path, error))?
+    {
+        samples.extend(
+            packet
+                .into_iter()
+                .map(|sample: i16| (sample as f32 / i16::MAX as f32).clamp(-1.0, 1.0)),
+        );
+    }
+    Ok((sample_rate, channels, samples))
+}
+
+fn decode_mp3_samples(path: &Path, bytes: &[u8]) -> mlua::Result<(u32, u16, Vec<f32>)> {
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
+    use symphonia::core::errors::Error as SymphoniaError;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    let source = MediaSourceStream::new(Box::new(Cursor::new(bytes.to_vec())), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension("mp3");
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|error| asset_decode_error("mp3 file", path, error))?
+        .format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| asset_decode_error("mp3 file", path, "no audio track"))?;
+    let track_id = track.id;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|error| asset_decode_error("mp3 file", path, error))?;
+
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+    let mut channels = track
+        .codec_params
+        .channels
+        .map(|channels| channels.count() as u16)
+        .unwrap_or(0);
+    let mut samples = Vec::new();
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(error))
+                if error.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                break;
+            }
+            Err(error) => return Err(asset_decode_error("mp3 packet", path, error)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // a corrupt frame is skipped rather than failing the whole file
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(error) => return Err(asset_decode_error("mp3 packet", path, error)),
+        };
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        channels = spec.channels.count() as u16;
+        let needed = decoded.capacity() as u64;
+        let buffer = match &mut buffer {
+            Some(buffer) if buffer.capacity() as u64 >= needed * channels as u64 => buffer,
+            slot => slot.insert(SampleBuffer::new(needed, spec)),
+        };
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend(
+            buffer
+                .samples()
+                .iter()
+                .map(|sample| sample.clamp(-1.0, 1.0)),
+        );
+    }
+    if sample_rate == 0 || channels == 0 {
+        return Err(asset_decode_error("mp3 file", path, "no decodable frames"));
+    }
+    Ok((sample_rate, channels, samples))
+}
+
+fn parse_resize_filter(mode: Option<&str>) -> mlua::Result<FilterType> {
+    match mode.map(|mode| mode.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("nearest") => Ok(FilterType::Nearest),
+        Some("linear") | Some("bilinear") => Ok(FilterType::Triangle),
+        Some(other) => Err(mlua::Error::external(format!(
+            "unknown resize mode '{other}' (expected \"nearest\" or \"linear\")"
+        ))),
+    }
+}
+
 fn normalize_path(path: &Path) -> PathBuf {
     let mut normalized = PathBuf::new();
     for component in path.components() {
@@ -175,6 +399,39 @@ fn resolve_export_path(root: &Path, input: &str, extension: &str) -> mlua::Resul
     Ok(resolved)
 }
 
+// screenshots land in the project like exported images do
+pub(crate) fn resolve_png_export_path(root: &Path, input: &str) -> mlua::Result<PathBuf> {
+    resolve_export_path(root, input, "png")
+}
+
+pub(crate) fn write_png(path: &Path, image: RgbaImage) -> mlua::Result<()> {
+    ensure_parent_dir(path)
+        .map_err(|error| asset_io_error("create export directory for image", path, error))?;
+    image::DynamicImage::ImageRgba8(image)
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|error| asset_io_error("write png image", path, error))
+}
+
+// "png", "jpg", ... as accepted by assets.loadImage; case and a leading dot are ignored
+fn parse_image_format(name: &str) -> mlua::Result<image::ImageFormat> {
+    image::ImageFormat::from_extension(name.trim_start_matches('.').to_ascii_lowercase())
+        .ok_or_else(|| mlua::Error::external(format!("unknown image format '{name}'")))
+}
+
+// without a format the decoder sniffs the file contents, so the extension doesn't matter
+fn decode_image_file(path: &Path, format: Option<image::ImageFormat>) -> mlua::Result<RgbaImage> {
+    let bytes = std::fs::read(path).map_err(|error| asset_io_error("read image", path, error))?;
+    let decoded = match format {
+        Some(format) => image::load_from_memory_with_format(&bytes, format).map_err(|error| {
+            let kind = format!("{} image", format.extensions_str()[0]);
+            asset_decode_error(&kind, path, error)
+        })?,
+        None => image::load_from_memory(&bytes)
+            .map_err(|error| asset_decode_error("image", path, error))?,
+    };
+    Ok(decoded.to_rgba8())
+}
+
 fn ensure_parent_dir(path: &Path) -> mlua::Result<()> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(mlua::Error::external)?;
@@ -201,6 +458,10 @@ impl ImageHandle {
             unloaded: false,
             revision: 0,
             export_root: None,
+            loading: false,
+            load_error: None,
+            filter: None,
+            premultiplied: false,
         })))
     }
 
@@ -246,6 +507,138 @@ impl ImageHandle {
         .ok_or_else(|| mlua::Error::external("pixel out of bounds"))
     }
 
+    // copies (src_x, src_y, w, h) of `source` to (dst_x, dst_y), clipped to both images
+    pub(crate) fn blit_from(
+        &self,
+        source: &ImageHandle,
+        dst_x: i64,
+        dst_y: i64,
+        region: Option<(i64, i64, i64, i64)>,
+    ) -> mlua::Result<()> {
+        // copy the region out first so blitting an image onto itself can't deadlock
+        let patch = source.with_image(|image| {
+            let (src_w, src_h) = (image.width() as i64, image.height() as i64);
+            let (src_x, src_y, w, h) = region.unwrap_or((0, 0, src_w, src_h));
+            let x0 = src_x.max(0);
+            let y0 = src_y.max(0);
+            let x1 = (src_x + w).min(src_w);
+            let y1 = (src_y + h).min(src_h);
+            if x1 <= x0 || y1 <= y0 {
+                return None;
+            }
+            let patch = image::imageops::crop_imm(
+                image,
+                x0 as u32,
+                y0 as u32,
+                (x1 - x0) as u32,
+                (y1 - y0) as u32,
+            )
+            .to_image();
+            Some((patch, dst_x + (x0 - src_x), dst_y + (y0 - src_y)))
+        })?;
+        let Some((patch, offset_x, offset_y)) = patch else {
+            return Ok(());
+        };
+
+        self.with_image_mut(|image| {
+            let (dst_w, dst_h) = (image.width() as i64, image.height() as i64);
+            for (x, y, pixel) in patch.enumerate_pixels() {
+                let tx = offset_x + x as i64;
+                let ty = offset_y + y as i64;
+                if tx >= 0 && ty >= 0 && tx < dst_w && ty < dst_h {
+                    image.put_pixel(tx as u32, ty as u32, *pixel);
+                }
+            }
+        })?;
+        if let Ok(mut image) = self.0.lock() {
+            image.revision = image.revision.wrapping_add(1);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn resize(&self, width: u32, height: u32, filter: FilterType) -> mlua::Result<()> {
+        if width == 0 || height == 0 || width > u16::MAX as u32 || height > u16::MAX as u32 {
+            return Err(mlua::Error::external(format!(
+                "resize expects dimensions between 1 and {}, got {width}x{height}",
+                u16::MAX
+            )));
+        }
+        self.with_image_mut(|image| {
+            *image = image::imageops::resize(image, width, height, filter);
+        })?;
+        if let Ok(mut image) = self.0.lock() {
+            image.revision = image.revision.wrapping_add(1);
+        }
+        Ok(())
+    }
+
+    // mirrors or rotates the pixels in place; rotating swaps width and height
+    fn transform(&self, f: impl FnOnce(&mut RgbaImage)) -> mlua::Result<()> {
+        self.with_image_mut(f)?;
+        if let Ok(mut image) = self.0.lock() {
+            image.revision = image.revision.wrapping_add(1);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn flip_horizontal(&self) -> mlua::Result<()> {
+        self.transform(|image| image::imageops::flip_horizontal_in_place(image))
+    }
+
+    pub(crate) fn flip_vertical(&self) -> mlua::Result<()> {
+        self.transform(|image| image::imageops::flip_vertical_in_place(image))
+    }
+
+    pub(crate) fn rotate90(&self, clockwise: bool) -> mlua::Result<()> {
+        self.transform(|image| {
+            *image = if clockwise {
+                image::imageops::rotate90(image)
+            } else {
+                image::imageops::rotate270(image)
+            };
+        })
+    }
+
+    // swaps in new pixels of the same size, e.g. after a render target was drawn into
+    pub(crate) fn replace_pixels(&self, pixels: RgbaImage) -> mlua::Result<()> {
+        let mut image = self
+            .0
+            .lock()
+            .map_err(|_| mlua::Error::external("image lock poisoned"))?;
+        if image.unloaded {
+            return Err(mlua::Error::external("image is unloaded"));
+        }
+        image.image = pixels;
+        image.revision = image.revision.wrapping_add(1);
+        Ok(())
+    }
+
+    // raw RGBA8 rows, top to bottom, the layout setPixels expects back
+    pub(crate) fn pixel_bytes(&self) -> mlua::Result<Vec<u8>> {
+        self.with_image(|image| image.as_raw().clone())
+    }
+
+    pub(crate) fn set_pixel_bytes(&self, bytes: &[u8]) -> mlua::Result<()> {
+        let mut image = self
+            .0
+            .lock()
+            .map_err(|_| mlua::Error::external("image lock poisoned"))?;
+        if image.unloaded {
+            return Err(mlua::Error::external("image is unloaded"));
+        }
+        let (width, height) = image.image.dimensions();
+        let expected = width as usize * height as usize * 4;
+        if bytes.len() != expected {
+            return Err(mlua::Error::external(format!(
+                "setPixels expects {expected} bytes for a {width}x{height} image, got {}",
+                bytes.len()
+            )));
+        }
+        image.image.copy_from_slice(bytes);
+        image.revision = image.revision.wrapping_add(1);
+        Ok(())
+    }
+
     pub(crate) fn unload(&self) {
         if let Ok(mut image) = self.0.lock() {
             image.image = RgbaImage::new(0, 0);
@@ -258,6 +651,72 @@ impl ImageHandle {
         self.with_image(|_| ())
     }
 
+    // false while loadImageAsync is decoding or after it failed
+    pub(crate) fn is_ready(&self) -> mlua::Result<bool> {
+        let image = self
+            .0
+            .lock()
+            .map_err(|_| mlua::Error::external("image lock poisoned"))?;
+        Ok(!image.loading && image.load_error.is_none())
+    }
+
+    pub(crate) fn load_error(&self) -> mlua::Result<Option<String>> {
+        let image = self
+            .0
+            .lock()
+            .map_err(|_| mlua::Error::external("image lock poisoned"))?;
+        Ok(image.load_error.clone())
+    }
+
+    pub(crate) fn is_premultiplied(&self) -> bool {
+        self.0.lock().is_ok_and(|image| image.premultiplied)
+    }
+
+    // once per image: a second pass would darken the edges again
+    pub(crate) fn premultiply_alpha(&self) -> mlua::Result<()> {
+        let mut image = self
+            .0
+            .lock()
+            .map_err(|_| mlua::Error::external("image lock poisoned"))?;
+        if image.unloaded {
+            return Err(mlua::Error::external("image is unloaded"));
+        }
+        if image.premultiplied {
+            return Ok(());
+        }
+        for pixel in image.image.pixels_mut() {
+            let alpha = pixel[3] as u16;
+            for channel in &mut pixel.0[..3] {
+                *channel = ((*channel as u16 * alpha + 127) / 255) as u8;
+            }
+        }
+        image.premultiplied = true;
+        image.revision = image.revision.wrapping_add(1);
+        Ok(())
+    }
+
+    pub(crate) fn filter_override(&self) -> Option<TextureFilter> {
+        self.0.lock().ok().and_then(|image| image.filter)
+    }
+
+    pub(crate) fn set_filter(&self, mode: Option<&str>) -> mlua::Result<()> {
+        let filter = match mode.map(|mode| mode.trim().to_ascii_lowercase()).as_deref() {
+            None => None,
+            Some("nearest") => Some(TextureFilter::Nearest),
+            Some("linear") => Some(TextureFilter::Linear),
+            Some(other) => {
+                return Err(mlua::Error::external(format!(
+                    "unknown filter '{other}' (expected \"nearest\" or \"linear\")"
+                )));
+            }
+        };
+        self.0
+            .lock()
+            .map_err(|_| mlua::Error::external("image lock poisoned"))?
+            .filter = filter;
+        Ok(())
+    }
+
     #[cfg(not(target_os = "emscripten"))]
     pub(crate) fn revision(&self) -> mlua::Result<u64> {
         let image = self
@@ -288,12 +747,8 @@ impl ImageHandle {
         };
         let export_root = export_root
             .ok_or_else(|| mlua::Error::external("image export is unavailable for this handle"))?;
-        let path = resolve_export_path(&export_root, user_path, "png")?;
-        ensure_parent_dir(&path)
-            .map_err(|error| asset_io_error("create export directory for image", &path, error))?;
-        image::DynamicImage::ImageRgba8(image)
-            .save_with_format(&path, image::ImageFormat::Png)
-            .map_err(|error| asset_io_error("write png image", &path, error))
+        let path = resolve_png_export_path(&export_root, user_path)?;
+        write_png(&path, image)
     }
 }
 
@@ -336,6 +791,136 @@ impl SoundHandle {
         Ok(sound.bytes.clone())
     }
 
+    // interleaved stereo copy with a left/right balance; pan 0 leaves the sound unchanged
+    pub(crate) fn panned_samples(&self, pan: f32) -> mlua::Result<(u32, Vec<f32>)> {
+        let pan = if pan.is_finite() {
+            pan.clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        let left_gain = (1.0 - pan).min(1.0);
+        let right_gain = (1.0 + pan).min(1.0);
+        self.with_samples(|sample_rate, channels, samples| {
+            let stereo = match channels {
+                1 => samples
+                    .iter()
+                    .flat_map(|sample| [sample * left_gain, sample * right_gain])
+                    .collect(),
+                2 => samples
+                    .chunks_exact(2)
+                    .flat_map(|frame| [frame[0] * left_gain, frame[1] * right_gain])
+                    .collect(),
+                _ => {
+                    return Err(mlua::Error::external(
+                        "panning supports mono and stereo sounds only",
+                    ));
+                }
+            };
+            Ok((sample_rate, stereo))
+        })
+    }
+
+    // linear interpolation per channel; the duration stays the same, so the frame count
+    // scales with the rate
+    pub(crate) fn resample(&self, new_rate: u32) -> mlua::Result<()> {
+        // far above any audio device, and keeps a typo from allocating gigabytes of samples
+        const MAX_SAMPLE_RATE: u32 = 384_000;
+        if new_rate == 0 || new_rate > MAX_SAMPLE_RATE {
+            return Err(mlua::Error::external(format!(
+                "sample rate must be between 1 and {MAX_SAMPLE_RATE}"
+            )));
+        }
+        let mut sound = self
+            .0
+            .lock()
+            .map_err(|_| mlua::Error::external("sound lock poisoned"))?;
+        if sound.unloaded {
+            return Err(mlua::Error::external("sound is unloaded"));
+        }
+        if sound.sample_rate == new_rate {
+            return Ok(());
+        }
+        let channels = sound.channels.max(1) as usize;
+        let frames = sound.samples.len() / channels;
+        let step = sound.sample_rate as f64 / new_rate as f64;
+        let new_frames = (frames as f64 / step).round() as usize;
+        let mut resampled = Vec::with_capacity(new_frames * channels);
+        for frame in 0..new_frames {
+            let position = frame as f64 * step;
+            let index = (position as usize).min(frames.saturating_sub(1));
+            let next = (index + 1).min(frames.saturating_sub(1));
+            let t = (position - index as f64).clamp(0.0, 1.0) as f32;
+            for channel in 0..channels {
+                let a = sound.samples[index * channels + channel];
+                let b = sound.samples[next * channels + channel];
+                resampled.push(a + (b - a) * t);
+            }
+        }
+        sound.bytes = encode_wav_bytes(new_rate, sound.channels, &resampled)?;
+        sound.samples = resampled;
+        sound.sample_rate = new_rate;
+        Ok(())
+    }
+
+    // copies the interleaved samples [start, end) into a new sound; both ends must fall on
+    // frame boundaries so channels stay paired
+    pub(crate) fn slice(&self, start: i64, end: i64) -> mlua::Result<SoundHandle> {
+        let sound = self
+            .0
+            .lock()
+            .map_err(|_| mlua::Error::external("sound lock poisoned"))?;
+        if sound.unloaded {
+            return Err(mlua::Error::external("sound is unloaded"));
+        }
+        let len = sound.samples.len() as i64;
+        if start < 0 || end > len || start >= end {
+            return Err(mlua::Error::external(format!(
+                "slice range {start}..{end} is out of bounds (sound has {len} samples)"
+            )));
+        }
+        let channels = sound.channels.max(1) as i64;
+        if start % channels != 0 || end % channels != 0 {
+            return Err(mlua::Error::external(format!(
+                "slice bounds must be multiples of the channel count ({channels})"
+            )));
+        }
+        let samples = sound.samples[start as usize..end as usize].to_vec();
+        let bytes = encode_wav_bytes(sound.sample_rate, sound.channels, &samples)?;
+        Ok(SoundHandle(Arc::new(Mutex::new(SoundAsset {
+            sample_rate: sound.sample_rate,
+            channels: sound.channels,
+            samples,
+            bytes,
+            unloaded: false,
+            export_root: sound.export_root.clone(),
+        }))))
+    }
+
+    // interleaved sample range [start, end) for a loop body given in seconds;
+    // `end` defaults to the end of the sound
+    pub(crate) fn loop_region(&self, start: f32, end: Option<f32>) -> mlua::Result<(usize, usize)> {
+        self.with_samples(|sample_rate, channels, samples| {
+            let frames = samples.len() / channels.max(1) as usize;
+            let duration = frames as f32 / sample_rate.max(1) as f32;
+            let end = end.unwrap_or(duration);
+            if !start.is_finite() || !end.is_finite() || start < 0.0 || end > duration + 1e-4 {
+                return Err(mlua::Error::external(format!(
+                    "loop region must lie within the sound (0 to {duration} seconds)"
+                )));
+            }
+            let to_frame =
+                |seconds: f32| ((seconds * sample_rate as f32).round() as usize).min(frames);
+            let (start_frame, end_frame) = (to_frame(start), to_frame(end));
+            if start_frame >= end_frame {
+                return Err(mlua::Error::external(
+                    "loop region end must be after its start",
+                ));
+            }
+            let channels = channels as usize;
+            Ok((start_frame * channels, end_frame * channels))
+        })
+    }
+
     #[allow(dead_code)]
     pub(crate) fn with_samples<R>(
         &self,
@@ -390,6 +975,57 @@ impl SoundHandle {
     }
 }
 
+impl FontAssetHandle {
+    pub(crate) fn path(&self) -> &str {
+        &self.0.path
+    }
+}
+
+impl UserData for FontAssetHandle {
+    fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("path", |_lua, this, ()| Ok(this.path().to_string()));
+        methods.add_method("lineHeight", |_lua, this, size: f32| {
+            let size = size.max(1.0);
+            Ok(this
+                .0
+                .font
+                .horizontal_line_metrics(size)
+                .map(|metrics| metrics.new_line_size)
+                .unwrap_or(size))
+        });
+    }
+}
+
+// RGBA bytes given either as a binary string or as an array of numbers 0-255
+fn rgba_bytes_from_lua(data: Value, function: &str) -> mlua::Result<Vec<u8>> {
+    match data {
+        Value::String(data) => Ok(data.as_bytes().to_vec()),
+        Value::Table(data) => data
+            .sequence_values::<Value>()
+            .enumerate()
+            .map(|(index, value)| {
+                // a fractional byte is almost always a 0..1 color by mistake, so it isn't rounded
+                let byte = match value? {
+                    Value::Integer(byte) => u8::try_from(byte).ok(),
+                    Value::Number(byte) if byte.fract() == 0.0 && (0.0..=255.0).contains(&byte) => {
+                        Some(byte as u8)
+                    }
+                    _ => None,
+                };
+                byte.ok_or_else(|| {
+                    mlua::Error::external(format!(
+                        "{function} entry {} must be an integer from 0 to 255",
+                        index + 1
+                    ))
+                })
+            })
+            .collect(),
+        _ => Err(mlua::Error::external(format!(
+            "{function} expects a string or table of RGBA bytes"
+        ))),
+    }
+}
+
 impl UserData for ImageHandle {
     fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
         methods.add_method("width", |_lua, this, ()| Ok(this.dimensions()?.0));
@@ -428,6 +1064,12 @@ impl UserData for ImageHandle {
             }
             Ok(())
         });
+        methods.add_method("getPixels", |lua, this, ()| {
+            lua.create_string(this.pixel_bytes()?)
+        });
+        methods.add_method("setPixels", |_lua, this, pixels: Value| {
+            this.set_pixel_bytes(&rgba_bytes_from_lua(pixels, "setPixels")?)
+        });
         methods.add_method("fill", |_lua, this, args: Variadic<Value>| {
             let color = parse_color_args(&args)?;
             this.with_image_mut(|image| {
@@ -440,6 +1082,38 @@ impl UserData for ImageHandle {
             }
             Ok(())
         });
+        methods.add_method(
+            "blit",
+            |_lua, this, (source, dst_x, dst_y, region): (AnyUserData, f64, f64, Variadic<f64>)| {
+                let source = source.borrow::<ImageHandle>()?.clone();
+                let region = match region.len() {
+                    0 => None,
+                    4 => Some((
+                        region[0].floor() as i64,
+                        region[1].floor() as i64,
+                        region[2].floor() as i64,
+                        region[3].floor() as i64,
+                    )),
+                    _ => {
+                        return Err(mlua::Error::external(
+                            "blit expects (src, dstX, dstY) or (src, dstX, dstY, srcX, srcY, w, h)",
+                        ));
+                    }
+                };
+                this.blit_from(&source, dst_x.floor() as i64, dst_y.floor() as i64, region)
+            },
+        );
+        methods.add_method(
+            "resize",
+            |_lua, this, (width, height, mode): (u32, u32, Option<String>)| {
+                this.resize(width, height, parse_resize_filter(mode.as_deref())?)
+            },
+        );
+        methods.add_method("flipHorizontal", |_lua, this, ()| this.flip_horizontal());
+        methods.add_method("flipVertical", |_lua, this, ()| this.flip_vertical());
+        methods.add_method("rotate90", |_lua, this, clockwise: Option<bool>| {
+            this.rotate90(clockwise.unwrap_or(true))
+        });
         methods.add_method("upload", |_lua, this, ()| this.ensure_uploaded());
         methods.add_method("export", |_lua, this, path: String| this.export_png(&path));
         methods.add_method("save", |_lua, this, path: String| this.export_png(&path));
@@ -454,6 +1128,23 @@ impl UserData for ImageHandle {
                 .map_err(|_| mlua::Error::external("image lock poisoned"))?;
             Ok(image.unloaded)
         });
+        methods.add_method("isReady", |_lua, this, ()| this.is_ready());
+        methods.add_method("getLoadError", |_lua, this, ()| this.load_error());
+        methods.add_method("setFilter", |_lua, this, mode: Option<String>| {
+            this.set_filter(mode.as_deref())
+        });
+        methods.add_method("premultiplyAlpha", |_lua, this, ()| {
+            this.premultiply_alpha()
+        });
+        methods.add_method("isPremultiplied", |_lua, this, ()| {
+            Ok(this.is_premultiplied())
+        });
+        methods.add_method("getFilter", |_lua, this, ()| {
+            Ok(this.filter_override().map(|filter| match filter {
+                TextureFilter::Nearest => "nearest",
+                TextureFilter::Linear => "linear",
+            }))
+        });
     }
 }
 
@@ -507,6 +1198,12 @@ impl UserData for SoundHandle {
             sound.bytes = encode_wav_bytes(sound.sample_rate, sound.channels, &sound.samples)?;
             Ok(())
         });
+        methods.add_method("slice", |_lua, this, (start, end): (i64, i64)| {
+            this.slice(start, end)
+        });
+        methods.add_method("resample", |_lua, this, new_rate: u32| {
+            this.resample(new_rate)
+        });
         methods.add_method("upload", |_lua, this, ()| this.ensure_uploaded());
         methods.add_method("export", |_lua, this, path: String| this.export_wav(&path));
         methods.add_method("save", |_lua, this, path: String| this.export_wav(&path));
@@ -530,6 +1227,10 @@ impl AssetManager {
             env_root,
             images: HashMap::new(),
             sounds: HashMap::new(),
+            fonts: HashMap::new(),
+            created_images: Vec::new(),
+            auto_gc_interval: None,
+            auto_gc_elapsed: 0.0,
         }
     }
 
@@ -548,50 +1249,202 @@ impl AssetManager {
         self.env_root.join("assets").join(path)
     }
 
+    // resolve_path, but refusing anything outside the project (used for enumeration)
+    fn resolve_project_path(&self, user_path: &str) -> mlua::Result<PathBuf> {
+        let resolved = normalize_path(&self.resolve_path(user_path));
+        if !resolved.starts_with(normalize_path(&self.env_root)) {
+            return Err(mlua::Error::external(format!(
+                "path escapes project root: {user_path}"
+            )));
+        }
+        Ok(resolved)
+    }
+
+    pub(crate) fn exists(&self, user_path: &str) -> mlua::Result<bool> {
+        Ok(self.resolve_project_path(user_path)?.exists())
+    }
+
+    // entry names (files and directories) sorted so level order doesn't depend on the OS
+    pub(crate) fn list(&self, user_dir: &str) -> mlua::Result<Vec<String>> {
+        let dir = self.resolve_project_path(user_dir)?;
+        let entries = std::fs::read_dir(&dir).map_err(|error| {
+            mlua::Error::external(format!("failed to list {}: {error}", dir.display()))
+        })?;
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(mlua::Error::external)?;
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        names.sort();
+        Ok(names)
+    }
+
     fn canonical_for_cache(path: &Path) -> PathBuf {
         std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
     }
 
-    pub(crate) fn load_image(&mut self, user_path: &str) -> mlua::Result<ImageHandle> {
+    pub(crate) fn load_image(
+        &mut self,
+        user_path: &str,
+        format: Option<image::ImageFormat>,
+    ) -> mlua::Result<ImageHandle> {
         let resolved = self.resolve_path(user_path);
         let cache_key = Self::canonical_for_cache(&resolved);
         if let Some(existing) = self.images.get(&cache_key).and_then(Weak::upgrade) {
-            let unloaded = existing
-                .lock()
-                .map_err(|_| mlua::Error::external("image lock poisoned"))?
-                .unloaded;
-            if !unloaded {
+            let usable = {
+                let image = existing
+                    .lock()
+                    .map_err(|_| mlua::Error::external("image lock poisoned"))?;
+                // a pending async load would hand back the placeholder, so decode it here instead
+                !image.unloaded && !image.loading && image.load_error.is_none()
+            };
+            if usable {
                 return Ok(ImageHandle(existing));
             }
         }
 
-        let bytes = std::fs::read(&resolved)
-            .map_err(|error| asset_io_error("read image", &resolved, error))?;
-        let image = image::load_from_memory(&bytes)
-            .map_err(|error| asset_decode_error("image", &resolved, error))?
-            .to_rgba8();
+        let image = decode_image_file(&resolved, format)?;
         let handle = Arc::new(Mutex::new(ImageAsset {
             image,
             unloaded: false,
             revision: 0,
             export_root: Some(self.env_root.clone()),
+            loading: false,
+            load_error: None,
+            filter: None,
+            premultiplied: false,
         }));
         self.images.insert(cache_key, Arc::downgrade(&handle));
         Ok(ImageHandle(handle))
     }
 
-    pub(crate) fn new_image(&mut self, width: u16, height: u16, color: Color) -> ImageHandle {
-        let pixel = Rgba([color.r, color.g, color.b, color.a]);
-        let image = RgbaImage::from_pixel(width as u32, height as u32, pixel);
-        ImageHandle(Arc::new(Mutex::new(ImageAsset {
-            image,
-            unloaded: false,
-            revision: 0,
-            export_root: Some(self.env_root.clone()),
-        })))
+    // returns right away with a transparent 1x1 placeholder; the file is decoded on a
+    // background thread and swapped in once ready (the renderer re-uploads on the revision bump)
+    #[cfg(not(target_os = "emscripten"))]
+    pub(crate) fn load_image_async(&mut self, user_path: &str) -> mlua::Result<ImageHandle> {
+        let resolved = self.resolve_path(user_path);
+        let cache_key = Self::canonical_for_cache(&resolved);
+        if let Some(existing) = self.images.get(&cache_key).and_then(Weak::upgrade) {
+            let usable = {
+                let image = existing
+                    .lock()
+                    .map_err(|_| mlua::Error::external("image lock poisoned"))?;
+                !image.unloaded && image.load_error.is_none()
+            };
+            if usable {
+                return Ok(ImageHandle(existing));
+            }
+        }
+
+        let handle = Arc::new(Mutex::new(ImageAsset {
+            image: RgbaImage::from_pixel(1, 1, Rgba([0, 0, 0, 0])),
+            unloaded: false,
+            revision: 0,
+            export_root: Some(self.env_root.clone()),
+            loading: true,
+            load_error: None,
+            filter: None,
+            premultiplied: false,
+        }));
+        self.images.insert(cache_key, Arc::downgrade(&handle));
+
+        let target = Arc::downgrade(&handle);
+        std::thread::Builder::new()
+            .name("neolove-image-loader".to_string())
+            .spawn(move || {
+                let decoded = decode_image_file(&resolved, None);
+                // nobody is waiting for it any more
+                let Some(target) = target.upgrade() else {
+                    return;
+                };
+                let Ok(mut asset) = target.lock() else {
+                    return;
+                };
+                asset.loading = false;
+                if asset.unloaded {
+                    return;
+                }
+                match decoded {
+                    Ok(image) => {
+                        asset.image = image;
+                        asset.revision = asset.revision.wrapping_add(1);
+                    }
+                    Err(error) => asset.load_error = Some(error.to_string()),
+                }
+            })
+            .map_err(|error| asset_io_error("start loading image", Path::new(user_path), error))?;
+
+        Ok(ImageHandle(handle))
+    }
+
+    // browser builds have no worker threads here, so the image is simply ready on return
+    #[cfg(target_os = "emscripten")]
+    pub(crate) fn load_image_async(&mut self, user_path: &str) -> mlua::Result<ImageHandle> {
+        self.load_image(user_path, None)
+    }
+
+    pub(crate) fn load_font(&mut self, user_path: &str) -> mlua::Result<FontAssetHandle> {
+        let resolved = self.resolve_path(user_path);
+        let cache_key = Self::canonical_for_cache(&resolved);
+        if let Some(existing) = self.fonts.get(&cache_key).and_then(Weak::upgrade) {
+            return Ok(FontAssetHandle(existing));
+        }
+
+        let bytes = std::fs::read(&resolved)
+            .map_err(|error| asset_io_error("read font", &resolved, error))?;
+        let font = Font::from_bytes(bytes, fontdue::FontSettings::default())
+            .map_err(|error| asset_decode_error("font", &resolved, error))?;
+        let asset = Arc::new(FontAsset {
+            path: normalize_path(&resolved).to_string_lossy().into_owned(),
+            font: Arc::new(font),
+        });
+        crate::renderer::register_font(asset.path.clone(), &asset.font);
+        self.fonts.insert(cache_key, Arc::downgrade(&asset));
+        Ok(FontAssetHandle(asset))
+    }
+
+    pub(crate) fn new_image(&mut self, width: u16, height: u16, color: Color) -> ImageHandle {
+        let pixel = Rgba([color.r, color.g, color.b, color.a]);
+        let image = RgbaImage::from_pixel(width as u32, height as u32, pixel);
+        self.track_created_image(image)
+    }
+
+    pub(crate) fn new_image_from_rgba(
+        &mut self,
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    ) -> mlua::Result<ImageHandle> {
+        let expected = width as usize * height as usize * 4;
+        if data.len() != expected {
+            return Err(mlua::Error::external(format!(
+                "image data for {width}x{height} must be {expected} bytes (width * height * 4), got {}",
+                data.len()
+            )));
+        }
+        let image = RgbaImage::from_raw(width, height, data)
+            .ok_or_else(|| mlua::Error::external("image data does not match its dimensions"))?;
+        Ok(self.track_created_image(image))
+    }
+
+    fn track_created_image(&mut self, image: RgbaImage) -> ImageHandle {
+        let handle = Arc::new(Mutex::new(ImageAsset {
+            image,
+            unloaded: false,
+            revision: 0,
+            export_root: Some(self.env_root.clone()),
+            loading: false,
+            load_error: None,
+            filter: None,
+            premultiplied: false,
+        }));
+        // scripts that create images every frame would otherwise grow this list forever
+        self.created_images.retain(|image| image.strong_count() > 0);
+        self.created_images.push(Arc::downgrade(&handle));
+        ImageHandle(handle)
     }
 
-    pub(crate) fn load_sound_wav(&mut self, user_path: &str) -> mlua::Result<SoundHandle> {
+    pub(crate) fn load_sound(&mut self, user_path: &str) -> mlua::Result<SoundHandle> {
         let resolved = self.resolve_path(user_path);
         let cache_key = Self::canonical_for_cache(&resolved);
         if let Some(existing) = self.sounds.get(&cache_key).and_then(Weak::upgrade) {
@@ -606,50 +1459,36 @@ impl AssetManager {
 
         let file_bytes = std::fs::read(&resolved)
             .map_err(|error| asset_io_error("read sound", &resolved, error))?;
-        let mut reader = hound::WavReader::new(Cursor::new(file_bytes.as_slice()))
-            .map_err(|error| asset_decode_error("wav file", &resolved, error))?;
-        let spec = reader.spec();
-        let mut samples = Vec::new();
-        match spec.sample_format {
-            hound::SampleFormat::Float => {
-                for sample in reader.samples::<f32>() {
-                    samples.push(
-                        sample
-                            .map_err(|error| asset_decode_error("wav sample", &resolved, error))?
-                            .clamp(-1.0, 1.0),
-                    );
+        let (sample_rate, channels, samples, bytes) =
+            match detect_sound_format(&resolved, &file_bytes) {
+                Some(SoundFormat::Wav) => {
+                    let (sample_rate, channels, samples) =
+                        decode_wav_samples(&resolved, &file_bytes)?;
+                    (sample_rate, channels, samples, file_bytes)
                 }
-            }
-            hound::SampleFormat::Int => {
-                let max = ((1u64 << spec.bits_per_sample.saturating_sub(1)) as f32) - 1.0;
-                if spec.bits_per_sample <= 16 {
-                    for sample in reader.samples::<i16>() {
-                        samples.push(
-                            (sample.map_err(|error| {
-                                asset_decode_error("wav sample", &resolved, error)
-                            })? as f32
-                                / max)
-                                .clamp(-1.0, 1.0),
-                        );
-                    }
-                } else {
-                    for sample in reader.samples::<i32>() {
-                        samples.push(
-                            (sample.map_err(|error| {
-                                asset_decode_error("wav sample", &resolved, error)
-                            })? as f32
-                                / max)
-                                .clamp(-1.0, 1.0),
-                        );
-                    }
+                Some(format @ (SoundFormat::Ogg | SoundFormat::Mp3)) => {
+                    let (sample_rate, channels, samples) = if format == SoundFormat::Ogg {
+                        decode_ogg_samples(&resolved, &file_bytes)?
+                    } else {
+                        decode_mp3_samples(&resolved, &file_bytes)?
+                    };
+                    // the playback and export paths work on wav bytes, so re-encode once up front
+                    let bytes = encode_wav_bytes(sample_rate, channels, &samples)?;
+                    (sample_rate, channels, samples, bytes)
                 }
-            }
-        }
+                None => {
+                    return Err(asset_decode_error(
+                        "sound",
+                        &resolved,
+                        "unsupported format (supported: .wav, .ogg, .mp3)",
+                    ));
+                }
+            };
         let handle = Arc::new(Mutex::new(SoundAsset {
-            sample_rate: spec.sample_rate,
-            channels: spec.channels,
+            sample_rate,
+            channels,
             samples,
-            bytes: file_bytes,
+            bytes,
             unloaded: false,
             export_root: Some(self.env_root.clone()),
         }));
@@ -674,6 +1513,64 @@ impl AssetManager {
         }))))
     }
 
+    // interleaved samples straight from Lua; encoded once instead of once per setSample
+    pub(crate) fn new_sound_from_samples(
+        &mut self,
+        sample_rate: u32,
+        channels: u16,
+        mut samples: Vec<f32>,
+    ) -> mlua::Result<SoundHandle> {
+        if channels == 0 {
+            return Err(mlua::Error::external(
+                "newSoundFromSamples expects at least one channel",
+            ));
+        }
+        if samples.len() % channels as usize != 0 {
+            return Err(mlua::Error::external(format!(
+                "newSoundFromSamples got {} samples, which is not a multiple of {channels} channels",
+                samples.len()
+            )));
+        }
+        if let Some(index) = samples.iter().position(|sample| !sample.is_finite()) {
+            return Err(mlua::Error::external(format!(
+                "newSoundFromSamples sample {} is not a finite number",
+                index + 1
+            )));
+        }
+        for sample in &mut samples {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+        self.new_sound(sample_rate, channels, samples)
+    }
+
+    pub(crate) fn mix_sounds(
+        &mut self,
+        a: &SoundHandle,
+        b: &SoundHandle,
+        gain_a: f32,
+        gain_b: f32,
+    ) -> mlua::Result<SoundHandle> {
+        let (sample_rate, channels, first) =
+            a.with_samples(|rate, channels, samples| Ok((rate, channels, samples.to_vec())))?;
+        // the shorter sound is padded with silence
+        let mixed = b.with_samples(|rate, other_channels, second| {
+            if rate != sample_rate || other_channels != channels {
+                return Err(mlua::Error::external(format!(
+                    "mixSounds expects matching formats, got {sample_rate} Hz/{channels} ch and {rate} Hz/{other_channels} ch"
+                )));
+            }
+            let len = first.len().max(second.len());
+            Ok((0..len)
+                .map(|index| {
+                    let a = first.get(index).copied().unwrap_or(0.0) * gain_a;
+                    let b = second.get(index).copied().unwrap_or(0.0) * gain_b;
+                    (a + b).clamp(-1.0, 1.0)
+                })
+                .collect::<Vec<f32>>())
+        })?;
+        self.new_sound(sample_rate, channels, mixed)
+    }
+
     pub(crate) fn unload_image_path(&mut self, user_path: &str) -> bool {
         let resolved = self.resolve_path(user_path);
         let Some(handle) = self
@@ -700,11 +1597,59 @@ impl AssetManager {
         true
     }
 
+    // live, loaded assets in the cache plus live created images; sizes are estimates of the
+    // decoded data
+    pub(crate) fn stats(&self) -> AssetStats {
+        let mut stats = AssetStats::default();
+        let images = self.images.values().chain(&self.created_images);
+        for image in images.filter_map(Weak::upgrade) {
+            if let Ok(image) = image.lock() {
+                if !image.unloaded {
+                    stats.images += 1;
+                    stats.image_bytes += image.image.as_raw().len();
+                }
+            }
+        }
+        for sound in self.sounds.values().filter_map(Weak::upgrade) {
+            if let Ok(sound) = sound.lock() {
+                if !sound.unloaded {
+                    stats.sounds += 1;
+                    // decoded samples plus the wav encoding kept for playback
+                    stats.sound_bytes += sound.samples.len() * 4 + sound.bytes.len();
+                }
+            }
+        }
+        stats.fonts = self
+            .fonts
+            .values()
+            .filter(|font| font.strong_count() > 0)
+            .count();
+        stats
+    }
+
+    pub(crate) fn set_auto_gc(&mut self, interval: Option<f32>) {
+        self.auto_gc_interval = interval;
+        self.auto_gc_elapsed = 0.0;
+    }
+
+    pub(crate) fn tick_auto_gc(&mut self, dt: f32) {
+        let Some(interval) = self.auto_gc_interval else {
+            return;
+        };
+        self.auto_gc_elapsed += dt.max(0.0);
+        if self.auto_gc_elapsed >= interval {
+            self.auto_gc_elapsed = 0.0;
+            self.gc();
+        }
+    }
+
     pub(crate) fn gc(&mut self) -> (usize, usize) {
         let before_images = self.images.len();
         let before_sounds = self.sounds.len();
         self.images.retain(|_, weak| weak.strong_count() > 0);
         self.sounds.retain(|_, weak| weak.strong_count() > 0);
+        self.fonts.retain(|_, weak| weak.strong_count() > 0);
+        self.created_images.retain(|weak| weak.strong_count() > 0);
         (
             before_images - self.images.len(),
             before_sounds - self.sounds.len(),
@@ -712,7 +1657,7 @@ impl AssetManager {
     }
 }
 
-pub(crate) fn add_assets_module(lua: &Lua, env_root: PathBuf) -> mlua::Result<()> {
+pub(crate) fn add_assets_module(lua: &Lua, env_root: PathBuf) -> mlua::Result<SharedAssetManager> {
     let manager = Arc::new(Mutex::new(AssetManager::new(env_root)));
     let assets = lua.create_table()?;
 
@@ -720,11 +1665,31 @@ pub(crate) fn add_assets_module(lua: &Lua, env_root: PathBuf) -> mlua::Result<()
         let manager = manager.clone();
         assets.set(
             "loadImage",
+            lua.create_function(
+                move |lua, (path, format, premultiply): (String, Option<String>, Option<bool>)| {
+                    let format = format.as_deref().map(parse_image_format).transpose()?;
+                    let handle = manager
+                        .lock()
+                        .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                        .load_image(&path, format)?;
+                    if premultiply.unwrap_or(false) {
+                        handle.premultiply_alpha()?;
+                    }
+                    lua.create_userdata(handle)
+                },
+            )?,
+        )?;
+    }
+
+    {
+        let manager = manager.clone();
+        assets.set(
+            "loadImageAsync",
             lua.create_function(move |lua, path: String| {
                 let handle = manager
                     .lock()
                     .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
-                    .load_image(&path)?;
+                    .load_image_async(&path)?;
                 lua.create_userdata(handle)
             })?,
         )?;
@@ -752,6 +1717,47 @@ pub(crate) fn add_assets_module(lua: &Lua, env_root: PathBuf) -> mlua::Result<()
         )?;
     }
 
+    {
+        let manager = manager.clone();
+        assets.set(
+            "newRenderTarget",
+            lua.create_function(move |lua, (w, h): (u32, u32)| {
+                if w == 0 || h == 0 || w > u16::MAX as u32 || h > u16::MAX as u32 {
+                    return Err(mlua::Error::external(format!(
+                        "newRenderTarget expects dimensions between 1 and {}",
+                        u16::MAX
+                    )));
+                }
+                let handle = manager
+                    .lock()
+                    .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                    .new_image(w as u16, h as u16, Color::rgba(0, 0, 0, 0));
+                lua.create_userdata(handle)
+            })?,
+        )?;
+    }
+
+    {
+        let manager = manager.clone();
+        assets.set(
+            "newImageFromData",
+            lua.create_function(move |lua, (w, h, data): (u32, u32, Value)| {
+                if w == 0 || h == 0 || w > u16::MAX as u32 || h > u16::MAX as u32 {
+                    return Err(mlua::Error::external(format!(
+                        "newImageFromData expects dimensions between 1 and {}",
+                        u16::MAX
+                    )));
+                }
+                let bytes = rgba_bytes_from_lua(data, "newImageFromData")?;
+                let handle = manager
+                    .lock()
+                    .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                    .new_image_from_rgba(w, h, bytes)?;
+                lua.create_userdata(handle)
+            })?,
+        )?;
+    }
+
     {
         let manager = manager.clone();
         assets.set(
@@ -760,7 +1766,21 @@ pub(crate) fn add_assets_module(lua: &Lua, env_root: PathBuf) -> mlua::Result<()
                 let handle = manager
                     .lock()
                     .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
-                    .load_sound_wav(&path)?;
+                    .load_sound(&path)?;
+                lua.create_userdata(handle)
+            })?,
+        )?;
+    }
+
+    {
+        let manager = manager.clone();
+        assets.set(
+            "loadFont",
+            lua.create_function(move |lua, path: String| {
+                let handle = manager
+                    .lock()
+                    .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                    .load_font(&path)?;
                 lua.create_userdata(handle)
             })?,
         )?;
@@ -788,6 +1808,46 @@ pub(crate) fn add_assets_module(lua: &Lua, env_root: PathBuf) -> mlua::Result<()
         )?;
     }
 
+    {
+        let manager = manager.clone();
+        assets.set(
+            "newSoundFromSamples",
+            lua.create_function(
+                move |lua, (sample_rate, channels, samples): (u32, u16, Vec<f32>)| {
+                    let handle = manager
+                        .lock()
+                        .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                        .new_sound_from_samples(sample_rate, channels, samples)?;
+                    lua.create_userdata(handle)
+                },
+            )?,
+        )?;
+    }
+
+    {
+        let manager = manager.clone();
+        assets.set(
+            "mixSounds",
+            lua.create_function(
+                move |lua,
+                      (a, b, gain_a, gain_b): (
+                    AnyUserData,
+                    AnyUserData,
+                    Option<f32>,
+                    Option<f32>,
+                )| {
+                    let a = a.borrow::<SoundHandle>()?.clone();
+                    let b = b.borrow::<SoundHandle>()?.clone();
+                    let handle = manager
+                        .lock()
+                        .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                        .mix_sounds(&a, &b, gain_a.unwrap_or(1.0), gain_b.unwrap_or(1.0))?;
+                    lua.create_userdata(handle)
+                },
+            )?,
+        )?;
+    }
+
     {
         let manager = manager.clone();
         assets.set(
@@ -838,6 +1898,24 @@ pub(crate) fn add_assets_module(lua: &Lua, env_root: PathBuf) -> mlua::Result<()
         )?;
     }
 
+    assets.set(
+        "resizeImage",
+        lua.create_function(
+            move |_lua, (image_ud, width, height, mode): (AnyUserData, u32, u32, Option<String>)| {
+                let image = image_ud.borrow::<ImageHandle>()?;
+                image.resize(width, height, parse_resize_filter(mode.as_deref())?)
+            },
+        )?,
+    )?;
+
+    assets.set(
+        "saveImage",
+        lua.create_function(move |_lua, (image_ud, path): (AnyUserData, String)| {
+            let image = image_ud.borrow::<ImageHandle>()?;
+            image.export_png(&path)
+        })?,
+    )?;
+
     {
         let manager = manager.clone();
         assets.set(
@@ -852,8 +1930,79 @@ pub(crate) fn add_assets_module(lua: &Lua, env_root: PathBuf) -> mlua::Result<()
         )?;
     }
 
+    {
+        let manager = manager.clone();
+        assets.set(
+            "exists",
+            lua.create_function(move |_lua, path: String| {
+                manager
+                    .lock()
+                    .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                    .exists(&path)
+            })?,
+        )?;
+    }
+
+    {
+        let manager = manager.clone();
+        assets.set(
+            "list",
+            lua.create_function(move |_lua, dir: Option<String>| {
+                manager
+                    .lock()
+                    .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                    .list(dir.as_deref().unwrap_or(""))
+            })?,
+        )?;
+    }
+
+    {
+        let manager = manager.clone();
+        assets.set(
+            "stats",
+            lua.create_function(move |lua, ()| {
+                let stats = manager
+                    .lock()
+                    .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                    .stats();
+                let table = lua.create_table()?;
+                table.set("images", stats.images)?;
+                table.set("imageBytes", stats.image_bytes)?;
+                table.set("sounds", stats.sounds)?;
+                table.set("soundBytes", stats.sound_bytes)?;
+                table.set("fonts", stats.fonts)?;
+                table.set("totalBytes", stats.image_bytes + stats.sound_bytes)?;
+                Ok(table)
+            })?,
+        )?;
+    }
+
+    {
+        let manager = manager.clone();
+        assets.set(
+            "setAutoGc",
+            lua.create_function(move |_lua, interval: Option<f32>| {
+                let interval = match interval {
+                    None => None,
+                    Some(interval) if interval.is_finite() && interval > 0.0 => Some(interval),
+                    Some(interval) if interval == 0.0 => None,
+                    Some(_) => {
+                        return Err(mlua::Error::external(
+                            "assets.setAutoGc expects a positive interval in seconds, or 0/nil to disable",
+                        ));
+                    }
+                };
+                manager
+                    .lock()
+                    .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                    .set_auto_gc(interval);
+                Ok(())
+            })?,
+        )?;
+    }
+
     lua.globals().set("assets", assets)?;
-    Ok(())
+    Ok(manager)
 }
 
 #[cfg(test)]
@@ -897,6 +2046,75 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn exists_and_list_stay_inside_the_project() -> mlua::Result<()> {
+        let root = temp_root("asset_list");
+        fs::create_dir_all(root.join("assets/levels")).map_err(mlua::Error::external)?;
+        fs::write(root.join("assets/levels/02.json"), "{}").map_err(mlua::Error::external)?;
+        fs::write(root.join("assets/levels/01.json"), "{}").map_err(mlua::Error::external)?;
+        fs::write(root.join("main.luau"), "").map_err(mlua::Error::external)?;
+
+        let manager = AssetManager::new(root.clone());
+        assert!(manager.exists("levels/01.json")?);
+        assert!(manager.exists("./main.luau")?);
+        assert!(!manager.exists("levels/03.json")?);
+        assert_eq!(manager.list("levels")?, vec!["01.json", "02.json"]);
+        assert_eq!(manager.list("")?, vec!["levels"]);
+        assert!(manager.list("../..").is_err());
+        assert!(manager.exists("../../etc").is_err());
+        assert!(manager.list("missing").is_err());
+
+        fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn image_filter_override_round_trips() -> mlua::Result<()> {
+        let image = ImageHandle::from_rgba_image(RgbaImage::new(2, 2));
+        assert_eq!(image.filter_override(), None);
+        image.set_filter(Some("Nearest"))?;
+        assert_eq!(image.filter_override(), Some(TextureFilter::Nearest));
+        image.set_filter(Some("linear"))?;
+        assert_eq!(image.filter_override(), Some(TextureFilter::Linear));
+        assert!(image.set_filter(Some("cubic")).is_err());
+        image.set_filter(None)?;
+        assert_eq!(image.filter_override(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn async_image_load_swaps_in_decoded_pixels() -> mlua::Result<()> {
+        let root = temp_root("asset_async_image");
+        fs::create_dir_all(root.join("assets")).map_err(mlua::Error::external)?;
+        RgbaImage::from_pixel(3, 2, Rgba([10, 20, 30, 255]))
+            .save(root.join("assets").join("art.png"))
+            .map_err(mlua::Error::external)?;
+
+        let mut manager = AssetManager::new(root.clone());
+        let handle = manager.load_image_async("art.png")?;
+        let missing = manager.load_image_async("missing.png")?;
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while (!handle.is_ready()? || missing.load_error()?.is_none())
+            && std::time::Instant::now() < deadline
+        {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        assert!(handle.is_ready()?);
+        assert_eq!(handle.dimensions()?, (3, 2));
+        assert_eq!(handle.sample_rgba(2, 1)?, [10, 20, 30, 255]);
+        assert!(!missing.is_ready()?);
+        assert!(
+            missing
+                .load_error()?
+                .is_some_and(|error| error.contains("failed to read image"))
+        );
+
+        fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
     #[test]
     fn sound_export_writes_wav_and_appends_extension() -> mlua::Result<()> {
         let root = temp_root("asset_sound_export");
@@ -938,6 +2156,334 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn load_font_shares_cached_handles() -> mlua::Result<()> {
+        let root = temp_root("asset_font_cache");
+        fs::create_dir_all(root.join("assets")).map_err(mlua::Error::external)?;
+        fs::write(
+            root.join("assets").join("proggy.ttf"),
+            include_bytes!("../samples/new_features_test/assets/fonts/ProggyClean.ttf"),
+        )
+        .map_err(mlua::Error::external)?;
+        fs::write(root.join("assets").join("broken.ttf"), b"not a font")
+            .map_err(mlua::Error::external)?;
+
+        let mut manager = AssetManager::new(root.clone());
+        let first = manager.load_font("proggy.ttf")?;
+        let second = manager.load_font("assets/proggy.ttf")?;
+        assert!(Arc::ptr_eq(&first.0, &second.0));
+        assert!(first.path().ends_with("proggy.ttf"));
+
+        let error = manager.load_font("broken.ttf").err().map(|e| e.to_string());
+        assert!(error.is_some_and(|error| error.contains("failed to decode font")));
+
+        fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn new_image_from_rgba_validates_length() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_image_data"));
+        let image = manager.new_image_from_rgba(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 128])?;
+        assert_eq!(image.dimensions()?, (2, 1));
+        assert_eq!(image.sample_rgba(1, 0)?, [0, 0, 255, 128]);
+
+        let error = manager
+            .new_image_from_rgba(2, 2, vec![0; 4])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("must be 16 bytes"));
+        Ok(())
+    }
+
+    #[test]
+    fn rgba_byte_tables_reject_fractions_and_name_the_entry() -> mlua::Result<()> {
+        let lua = Lua::new();
+        let bytes: Table = lua.load("return { 0, 255, 128.0, 1 }").eval()?;
+        assert_eq!(
+            rgba_bytes_from_lua(Value::Table(bytes), "newImageFromData")?,
+            vec![0, 255, 128, 1]
+        );
+
+        for (data, index) in [
+            ("{ 0, 0.5, 0, 0 }", 2),
+            ("{ 0, 0, 0, 256 }", 4),
+            ("{ -1 }", 1),
+        ] {
+            let bytes: Table = lua.load(format!("return {data}")).eval()?;
+            let error = rgba_bytes_from_lua(Value::Table(bytes), "newImageFromData")
+                .unwrap_err()
+                .to_string();
+            assert!(
+                error.contains(&format!(
+                    "newImageFromData entry {index} must be an integer"
+                )),
+                "{error}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn pixel_bytes_round_trip_and_check_length() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_pixel_bytes"));
+        let image = manager.new_image(2, 1, Color::WHITE);
+        let before = image.revision()?;
+
+        image.set_pixel_bytes(&[255, 0, 0, 255, 0, 0, 255, 128])?;
+        assert_eq!(image.sample_rgba(1, 0)?, [0, 0, 255, 128]);
+        assert_eq!(image.pixel_bytes()?, vec![255, 0, 0, 255, 0, 0, 255, 128]);
+        assert_ne!(image.revision()?, before);
+
+        let error = image.set_pixel_bytes(&[0; 4]).unwrap_err().to_string();
+        assert!(error.contains("expects 8 bytes"), "{error}");
+        Ok(())
+    }
+
+    #[test]
+    fn blit_copies_clipped_regions() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_blit"));
+        let red = Color::rgba(255, 0, 0, 255);
+        let target = manager.new_image(4, 4, Color::WHITE);
+        let source = manager.new_image(3, 3, red);
+
+        // partly off the right/bottom edges
+        target.blit_from(&source, 2, 2, None)?;
+        assert_eq!(target.sample_rgba(3, 3)?, [255, 0, 0, 255]);
+        assert_eq!(target.sample_rgba(1, 1)?, [255, 255, 255, 255]);
+
+        // region starting left of the source shifts the copy to keep pixels aligned
+        target.blit_from(&source, 0, 0, Some((-1, 0, 2, 1)))?;
+        assert_eq!(target.sample_rgba(0, 0)?, [255, 255, 255, 255]);
+        assert_eq!(target.sample_rgba(1, 0)?, [255, 0, 0, 255]);
+
+        // blitting onto itself must not deadlock
+        target.blit_from(&target, 0, 2, Some((0, 0, 4, 1)))?;
+        assert_eq!(target.sample_rgba(1, 2)?, [255, 0, 0, 255]);
+        Ok(())
+    }
+
+    #[test]
+    fn resize_replaces_image_and_rejects_bad_sizes() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_resize"));
+        let image = manager.new_image_from_rgba(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 255])?;
+
+        image.resize(4, 2, parse_resize_filter(None)?)?;
+        assert_eq!(image.dimensions()?, (4, 2));
+        assert_eq!(image.sample_rgba(1, 1)?, [255, 0, 0, 255]);
+        assert_eq!(image.sample_rgba(2, 0)?, [0, 0, 255, 255]);
+
+        image.resize(1, 1, parse_resize_filter(Some("linear"))?)?;
+        assert_eq!(image.dimensions()?, (1, 1));
+
+        assert!(image.resize(0, 4, FilterType::Nearest).is_err());
+        assert!(parse_resize_filter(Some("cubic")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn flips_and_rotations_rearrange_pixels() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_flip"));
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        // 2x1: red on the left, blue on the right
+        let image = manager.new_image_from_rgba(2, 1, [red, blue].concat())?;
+
+        image.flip_horizontal()?;
+        assert_eq!(image.sample_rgba(0, 0)?, blue);
+
+        image.rotate90(true)?;
+        assert_eq!(image.dimensions()?, (1, 2));
+        assert_eq!(image.sample_rgba(0, 0)?, blue);
+        assert_eq!(image.sample_rgba(0, 1)?, red);
+
+        image.flip_vertical()?;
+        assert_eq!(image.sample_rgba(0, 0)?, red);
+
+        image.rotate90(false)?;
+        assert_eq!(image.dimensions()?, (2, 1));
+        assert_eq!(image.sample_rgba(0, 0)?, red);
+        assert_eq!(image.sample_rgba(1, 0)?, blue);
+        Ok(())
+    }
+
+    #[test]
+    fn stats_and_auto_gc_track_the_cache() -> mlua::Result<()> {
+        let root = temp_root("asset_stats");
+        fs::create_dir_all(root.join("assets")).map_err(mlua::Error::external)?;
+        RgbaImage::new(4, 2)
+            .save(root.join("assets").join("tile.png"))
+            .map_err(mlua::Error::external)?;
+        let mut manager = AssetManager::new(root.clone());
+
+        let image = manager.load_image("tile.png", None)?;
+        let stats = manager.stats();
+        assert_eq!((stats.images, stats.image_bytes), (1, 4 * 2 * 4));
+        assert_eq!(stats.sounds, 0);
+
+        drop(image);
+        manager.set_auto_gc(Some(1.0));
+        manager.tick_auto_gc(0.5);
+        assert_eq!(manager.images.len(), 1);
+        manager.tick_auto_gc(0.5);
+        assert!(manager.images.is_empty());
+        assert_eq!(manager.stats(), AssetStats::default());
+
+        fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn stats_count_new_images_and_render_targets() -> mlua::Result<()> {
+        let lua = Lua::new();
+        let manager = add_assets_module(&lua, temp_root("asset_stats_created"))?;
+        let stats = || -> mlua::Result<AssetStats> {
+            Ok(manager
+                .lock()
+                .map_err(|_| mlua::Error::external("asset manager lock poisoned"))?
+                .stats())
+        };
+
+        lua.load("image = assets.newImage(2, 2); target = assets.newRenderTarget(3, 1)")
+            .exec()?;
+        let created = stats()?;
+        assert_eq!((created.images, created.image_bytes), (2, (4 + 3) * 4));
+
+        lua.load("target:unload()").exec()?;
+        assert_eq!(stats()?.images, 1);
+        lua.load("image = nil; target = nil").exec()?;
+        lua.gc_collect()?;
+        assert_eq!(stats()?, AssetStats::default());
+        Ok(())
+    }
+
+    #[test]
+    fn dropped_new_images_are_pruned_from_the_created_list() {
+        let mut manager = AssetManager::new(temp_root("asset_created_prune"));
+        let kept = manager.new_image(1, 1, Color::WHITE);
+        for _ in 0..100 {
+            drop(manager.new_image(4, 4, Color::WHITE));
+        }
+        assert_eq!(manager.created_images.len(), 2);
+        drop(kept);
+        drop(manager.new_image(1, 1, Color::WHITE));
+        assert_eq!(manager.created_images.len(), 1);
+    }
+
+    #[test]
+    fn slice_copies_frame_aligned_ranges() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_slice"));
+        let stereo = manager.new_sound(8_000, 2, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6])?;
+        let middle = stereo.slice(2, 6)?;
+        assert_eq!(middle.sample_rate()?, 8_000);
+        assert_eq!(middle.channels()?, 2);
+        middle.with_samples(|_, _, samples| {
+            assert_eq!(samples, &[0.3, 0.4, 0.5, 0.6]);
+            Ok(())
+        })?;
+        assert!(stereo.slice(1, 5).is_err());
+        assert!(stereo.slice(2, 2).is_err());
+        assert!(stereo.slice(0, 8).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn resample_interpolates_each_channel() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_resample"));
+        let stereo = manager.new_sound(4, 2, vec![0.0, 1.0, 1.0, 0.0])?;
+        stereo.resample(8)?;
+        assert_eq!(stereo.sample_rate()?, 8);
+        stereo.with_samples(|_, channels, samples| {
+            assert_eq!(channels, 2);
+            assert_eq!(samples, &[0.0, 1.0, 0.5, 0.5, 1.0, 0.0, 1.0, 0.0]);
+            Ok(())
+        })?;
+        assert!(stereo.resample(0).is_err());
+        assert!(stereo.resample(384_001).is_err());
+        assert_eq!(stereo.sample_rate()?, 8);
+        Ok(())
+    }
+
+    #[test]
+    fn loop_region_is_frame_aligned_and_validated() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_loop_region"));
+        let stereo = manager.new_sound(10, 2, vec![0.0; 40])?;
+        assert_eq!(stereo.loop_region(0.5, None)?, (10, 40));
+        assert_eq!(stereo.loop_region(0.5, Some(1.5))?, (10, 30));
+        assert!(stereo.loop_region(1.5, Some(1.0)).is_err());
+        assert!(stereo.loop_region(-1.0, None).is_err());
+        assert!(stereo.loop_region(0.0, Some(3.0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn panned_samples_balance_channels() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_pan"));
+        let mono = manager.new_sound(8_000, 1, vec![0.5, -1.0])?;
+        let (rate, left) = mono.panned_samples(-1.0)?;
+        assert_eq!(rate, 8_000);
+        assert_eq!(left, vec![0.5, 0.0, -1.0, 0.0]);
+        assert_eq!(mono.panned_samples(0.0)?.1, vec![0.5, 0.5, -1.0, -1.0]);
+
+        let stereo = manager.new_sound(8_000, 2, vec![1.0, 1.0])?;
+        assert_eq!(stereo.panned_samples(5.0)?.1, vec![0.0, 1.0]);
+        assert_eq!(stereo.panned_samples(0.5)?.1, vec![0.5, 1.0]);
+
+        let surround = manager.new_sound(8_000, 3, vec![0.0; 3])?;
+        assert!(surround.panned_samples(0.0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn new_sound_from_samples_validates_and_clamps() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_sound_samples"));
+        let stereo = manager.new_sound_from_samples(8_000, 2, vec![0.25, -0.5, 2.0, -3.0])?;
+        stereo.with_samples(|rate, channels, samples| {
+            assert_eq!((rate, channels), (8_000, 2));
+            assert_eq!(samples, &[0.25, -0.5, 1.0, -1.0]);
+            Ok(())
+        })?;
+
+        assert!(
+            manager
+                .new_sound_from_samples(8_000, 2, vec![0.0; 3])
+                .is_err()
+        );
+        assert!(manager.new_sound_from_samples(8_000, 0, vec![0.0]).is_err());
+        assert!(
+            manager
+                .new_sound_from_samples(8_000, 1, vec![0.0, f32::NAN])
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mix_sounds_sums_clamps_and_checks_formats() -> mlua::Result<()> {
+        let mut manager = AssetManager::new(temp_root("asset_mix"));
+        let a = manager.new_sound(8_000, 1, vec![0.5, 0.75, -0.5])?;
+        let b = manager.new_sound(8_000, 1, vec![0.25, 0.5])?;
+
+        let mixed = manager.mix_sounds(&a, &b, 1.0, 1.0)?;
+        mixed.with_samples(|rate, channels, samples| {
+            assert_eq!((rate, channels), (8_000, 1));
+            assert_eq!(samples, &[0.75, 1.0, -0.5]);
+            Ok(())
+        })?;
+
+        let quiet = manager.mix_sounds(&a, &b, 0.5, 0.0)?;
+        quiet.with_samples(|_, _, samples| {
+            assert_eq!(samples, &[0.25, 0.375, -0.25]);
+            Ok(())
+        })?;
+
+        let stereo = manager.new_sound(8_000, 2, vec![0.0; 2])?;
+        let other_rate = manager.new_sound(44_100, 1, vec![0.0])?;
+        assert!(manager.mix_sounds(&a, &stereo, 1.0, 1.0).is_err());
+        assert!(manager.mix_sounds(&a, &other_rate, 1.0, 1.0).is_err());
+        Ok(())
+    }
+
     #[test]
     fn load_image_error_mentions_resolved_path() -> mlua::Result<()> {
         let root = temp_root("asset_missing_image");
@@ -945,7 +2491,10 @@ mod tests {
 
         let mut manager = AssetManager::new(root.clone());
         let missing_path = root.join("assets").join("missing.png");
-        let error = manager.load_image("missing.png").unwrap_err().to_string();
+        let error = manager
+            .load_image("missing.png", None)
+            .unwrap_err()
+            .to_string();
 
         assert!(error.contains("failed to read image"));
         assert!(error.contains(missing_path.to_string_lossy().as_ref()));
@@ -954,6 +2503,62 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn load_image_format_hint_overrides_detection() -> mlua::Result<()> {
+        let root = temp_root("asset_image_format");
+        let assets_dir = root.join("assets");
+        write_png(
+            &assets_dir.join("misnamed.jpg"),
+            RgbaImage::from_pixel(2, 1, Rgba([1, 2, 3, 255])),
+        )?;
+
+        let mut manager = AssetManager::new(root.clone());
+        let png = parse_image_format("PNG")?;
+        assert_eq!(
+            manager
+                .load_image("misnamed.jpg", Some(png))?
+                .dimensions()?,
+            (2, 1)
+        );
+
+        let mut manager = AssetManager::new(root.clone());
+        let error = manager
+            .load_image("misnamed.jpg", Some(parse_image_format(".jpg")?))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("failed to decode jpg image"), "{error}");
+        assert!(error.contains("misnamed.jpg"), "{error}");
+        assert!(parse_image_format("psd2").is_err());
+
+        fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn load_sound_decodes_mp3_frames() -> mlua::Result<()> {
+        let root = temp_root("asset_mp3");
+        fs::create_dir_all(root.join("assets")).map_err(mlua::Error::external)?;
+        // eight silent MPEG-1 Layer III mono frames at 128 kbps and 44.1 kHz: a header and
+        // all-zero side info and main data, 417 bytes each
+        let mut frame = vec![0u8; 417];
+        frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0xC0]);
+        fs::write(root.join("assets").join("blip.bin"), frame.repeat(8))
+            .map_err(mlua::Error::external)?;
+
+        let mut manager = AssetManager::new(root.clone());
+        let sound = manager.load_sound("blip.bin")?;
+        assert_eq!(sound.sample_rate()?, 44_100);
+        assert_eq!(sound.channels()?, 1);
+        sound.with_samples(|_, _, samples| {
+            assert_eq!(samples.len(), 8 * 1152);
+            assert!(samples.iter().all(|sample| *sample == 0.0));
+            Ok(())
+        })?;
+
+        fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
     #[test]
     fn load_sound_error_mentions_resolved_path() -> mlua::Result<()> {
         let root = temp_root("asset_invalid_sound");
@@ -964,10 +2569,7 @@ mod tests {
         fs::write(&invalid_path, b"not a wav").map_err(mlua::Error::external)?;
 
         let mut manager = AssetManager::new(root.clone());
-        let error = manager
-            .load_sound_wav("broken.wav")
-            .unwrap_err()
-            .to_string();
+        let error = manager.load_sound("broken.wav").unwrap_err().to_string();
 
         assert!(error.contains("failed to decode wav file"));
         assert!(error.contains(invalid_path.to_string_lossy().as_ref()));
@@ -975,4 +2577,38 @@ mod tests {
         fs::remove_dir_all(root).map_err(mlua::Error::external)?;
         Ok(())
     }
+
+    #[test]
+    fn sound_format_detection_prefers_magic_bytes() {
+        assert_eq!(
+            detect_sound_format(Path::new("music.wav"), b"OggS\0\x02"),
+            Some(SoundFormat::Ogg)
+        );
+        assert_eq!(
+            detect_sound_format(Path::new("blip.ogg"), b"RIFF\0\0\0\0WAVE"),
+            Some(SoundFormat::Wav)
+        );
+        assert_eq!(
+            detect_sound_format(Path::new("music.OGG"), b""),
+            Some(SoundFormat::Ogg)
+        );
+        assert_eq!(
+            detect_sound_format(Path::new("track.bin"), b"ID3\x04\0"),
+            Some(SoundFormat::Mp3)
+        );
+        assert_eq!(
+            detect_sound_format(Path::new("track.bin"), &[0xFF, 0xFB, 0x90, 0x00]),
+            Some(SoundFormat::Mp3)
+        );
+        // AAC ADTS, MPEG-4 and MPEG-2 flavours
+        assert_eq!(
+            detect_sound_format(Path::new("track.bin"), &[0xFF, 0xF1, 0x50, 0x80]),
+            None
+        );
+        assert_eq!(
+            detect_sound_format(Path::new("track.aac"), &[0xFF, 0xF9, 0x50, 0x80]),
+            None
+        );
+        assert_eq!(detect_sound_format(Path::new("notes.txt"), b"hello"), None);
+    }
 }
@@ -1,7 +1,42 @@
+use mlua::{Lua, Table};
+use std::cell::Cell;
+
+thread_local! {
+    // multiplier applied to every volume passed to play/playOnce/playPanned/setVolume
+    static MASTER_VOLUME: Cell<f32> = const { Cell::new(1.0) };
+}
+
+fn effective_volume(volume: f32) -> f32 {
+    volume.clamp(0.0, 1.0) * MASTER_VOLUME.with(Cell::get)
+}
+
+fn add_master_volume_functions(lua: &Lua, audio: &Table) -> mlua::Result<()> {
+    audio.set(
+        "setMasterVolume",
+        lua.create_function(move |_lua, volume: f32| {
+            let volume = if volume.is_finite() {
+                volume.clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            MASTER_VOLUME.with(|master| master.set(volume));
+            Ok(())
+        })?,
+    )?;
+
+    audio.set(
+        "getMasterVolume",
+        lua.create_function(move |_lua, ()| Ok(MASTER_VOLUME.with(Cell::get)))?,
+    )?;
+
+    Ok(())
+}
+
 #[cfg(not(target_os = "emscripten"))]
 mod native {
     use crate::assets::SoundHandle;
     use mlua::{AnyUserData, Lua};
+    use rodio::buffer::SamplesBuffer;
     use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
     use std::cell::RefCell;
     use std::collections::HashMap;
@@ -26,20 +61,63 @@ mod native {
             })
         }
 
+        fn new_sink(&self, volume: f32) -> mlua::Result<Arc<Sink>> {
+            let sink = Arc::new(Sink::try_new(&self.handle).map_err(|error| {
+                mlua::Error::external(format!("failed to create audio sink: {error}"))
+            })?);
+            sink.set_volume(super::effective_volume(volume));
+            Ok(sink)
+        }
+
         fn play(&self, sound: &SoundHandle, looped: bool, volume: f32) -> mlua::Result<()> {
             let bytes = sound.bytes()?;
             let decoder = Decoder::new(Cursor::new(bytes)).map_err(|error| {
                 mlua::Error::external(format!("failed to decode audio data: {error}"))
             })?;
-            let sink = Arc::new(Sink::try_new(&self.handle).map_err(|error| {
-                mlua::Error::external(format!("failed to create audio sink: {error}"))
-            })?);
-            sink.set_volume(volume.clamp(0.0, 1.0));
+            let sink = self.new_sink(volume)?;
             if looped {
                 sink.append(decoder.repeat_infinite());
             } else {
                 sink.append(decoder);
             }
+            self.start(sound, sink)
+        }
+
+        // plays the whole clip up to `end`, then repeats only [start, end)
+        fn play_looped(
+            &self,
+            sound: &SoundHandle,
+            start: f32,
+            end: Option<f32>,
+            volume: f32,
+        ) -> mlua::Result<()> {
+            let (start, end) = sound.loop_region(start, end)?;
+            let (sample_rate, channels, intro, body) =
+                sound.with_samples(|sample_rate, channels, samples| {
+                    Ok((
+                        sample_rate,
+                        channels,
+                        samples[..start].to_vec(),
+                        samples[start..end].to_vec(),
+                    ))
+                })?;
+            let sink = self.new_sink(volume)?;
+            if !intro.is_empty() {
+                sink.append(SamplesBuffer::new(channels, sample_rate, intro));
+            }
+            sink.append(SamplesBuffer::new(channels, sample_rate, body).repeat_infinite());
+            self.start(sound, sink)
+        }
+
+        fn play_panned(&self, sound: &SoundHandle, pan: f32, volume: f32) -> mlua::Result<()> {
+            let (sample_rate, samples) = sound.panned_samples(pan)?;
+            let sink = self.new_sink(volume)?;
+            sink.append(SamplesBuffer::new(2, sample_rate, samples));
+            self.start(sound, sink)
+        }
+
+        // one sink per sound: starting a sound again replaces its previous playback
+        fn start(&self, sound: &SoundHandle, sink: Arc<Sink>) -> mlua::Result<()> {
             let mut sinks = self
                 .sinks
                 .lock()
@@ -62,13 +140,35 @@ mod native {
             Ok(())
         }
 
+        fn stop_all(&self) -> mlua::Result<()> {
+            let mut sinks = self
+                .sinks
+                .lock()
+                .map_err(|_| mlua::Error::external("audio sink lock poisoned"))?;
+            for (_, sink) in sinks.drain() {
+                sink.stop();
+            }
+            Ok(())
+        }
+
+        // a sink runs dry once a non-looped sound finishes, so this stays accurate
+        fn is_playing(&self, sound: &SoundHandle) -> mlua::Result<bool> {
+            let sinks = self
+                .sinks
+                .lock()
+                .map_err(|_| mlua::Error::external("audio sink lock poisoned"))?;
+            Ok(sinks
+                .get(&sound.id())
+                .is_some_and(|sink| !sink.empty() && !sink.is_paused()))
+        }
+
         fn set_volume(&self, sound: &SoundHandle, volume: f32) -> mlua::Result<()> {
             let sinks = self
                 .sinks
                 .lock()
                 .map_err(|_| mlua::Error::external("audio sink lock poisoned"))?;
             if let Some(existing) = sinks.get(&sound.id()) {
-                existing.set_volume(volume.clamp(0.0, 1.0));
+                existing.set_volume(super::effective_volume(volume));
             }
             Ok(())
         }
@@ -116,6 +216,38 @@ mod native {
             })?,
         )?;
 
+        audio.set(
+            "playLooped",
+            lua.create_function(
+                move |_lua,
+                      (sound_ud, start, end, volume): (
+                    AnyUserData,
+                    f32,
+                    Option<f32>,
+                    Option<f32>,
+                )| {
+                    let sound = sound_ud.borrow::<SoundHandle>()?;
+                    sound.ensure_uploaded()?;
+                    with_audio_backend(|audio| {
+                        audio.play_looped(&sound, start, end, volume.unwrap_or(1.0))
+                    })
+                },
+            )?,
+        )?;
+
+        audio.set(
+            "playPanned",
+            lua.create_function(
+                move |_lua, (sound_ud, pan, volume): (AnyUserData, f32, Option<f32>)| {
+                    let sound = sound_ud.borrow::<SoundHandle>()?;
+                    sound.ensure_uploaded()?;
+                    with_audio_backend(|audio| {
+                        audio.play_panned(&sound, pan, volume.unwrap_or(1.0))
+                    })
+                },
+            )?,
+        )?;
+
         audio.set(
             "stop",
             lua.create_function(move |_lua, sound_ud: AnyUserData| {
@@ -125,6 +257,25 @@ mod native {
             })?,
         )?;
 
+        audio.set(
+            "stopAll",
+            lua.create_function(move |_lua, ()| {
+                // nothing can be playing before the backend exists, so don't open a device
+                AUDIO.with(|cell| match cell.borrow().as_ref() {
+                    Some(audio) => audio.stop_all(),
+                    None => Ok(()),
+                })
+            })?,
+        )?;
+
+        audio.set(
+            "isPlaying",
+            lua.create_function(move |_lua, sound_ud: AnyUserData| {
+                let sound = sound_ud.borrow::<SoundHandle>()?;
+                with_audio_backend(|audio| audio.is_playing(&sound))
+            })?,
+        )?;
+
         audio.set(
             "setVolume",
             lua.create_function(move |_lua, (sound_ud, volume): (AnyUserData, f32)| {
@@ -134,6 +285,8 @@ mod native {
             })?,
         )?;
 
+        super::add_master_volume_functions(lua, &audio)?;
+
         lua.globals().set("audio", audio)?;
         Ok(())
     }
@@ -153,10 +306,14 @@ mod native {
             sample_rate: i32,
             channels: i32,
             looped: i32,
+            loop_start: f32,
+            loop_end: f32,
             volume: f32,
         ) -> i32;
         fn neolove_web_audio_stop(sound_id: i32) -> i32;
         fn neolove_web_audio_set_volume(sound_id: i32, volume: f32) -> i32;
+        fn neolove_web_audio_is_playing(sound_id: i32) -> i32;
+        fn neolove_web_audio_stop_all() -> i32;
         fn neolove_web_take_audio_error(buffer: *mut c_char, capacity: i32) -> i32;
     }
 
@@ -180,9 +337,16 @@ mod native {
         )))
     }
 
-    fn play_sound(sound: &SoundHandle, looped: bool, volume: f32) -> mlua::Result<()> {
+    // loop_start/loop_end are in seconds; 0.0/0.0 loops the whole clip
+    fn play_sound(
+        sound: &SoundHandle,
+        looped: bool,
+        loop_start: f32,
+        loop_end: f32,
+        volume: f32,
+    ) -> mlua::Result<()> {
         let sound_id = sound.id() as i32;
-        let volume = volume.clamp(0.0, 1.0);
+        let volume = super::effective_volume(volume);
         let result = sound.with_samples(|sample_rate, channels, samples| {
             if channels == 0 {
                 return Err(mlua::Error::external("sound must have at least one channel"));
@@ -214,6 +378,8 @@ mod native {
                     sample_rate as i32,
                     channels as i32,
                     if looped { 1 } else { 0 },
+                    loop_start,
+                    loop_end,
                     volume,
                 )
             })
@@ -221,6 +387,32 @@ mod native {
         check_bridge_result(result, "failed to play audio")
     }
 
+    fn play_panned(sound: &SoundHandle, pan: f32, volume: f32) -> mlua::Result<()> {
+        let (sample_rate, samples) = sound.panned_samples(pan)?;
+        if samples.is_empty() {
+            return Err(mlua::Error::external("sound has no samples"));
+        }
+        if samples.len() > i32::MAX as usize || sample_rate > i32::MAX as u32 {
+            return Err(mlua::Error::external(
+                "sound is too large for the web audio bridge",
+            ));
+        }
+        let result = unsafe {
+            neolove_web_audio_play(
+                sound.id() as i32,
+                samples.as_ptr(),
+                samples.len() as i32,
+                sample_rate as i32,
+                2,
+                0,
+                0.0,
+                0.0,
+                super::effective_volume(volume),
+            )
+        };
+        check_bridge_result(result, "failed to play audio")
+    }
+
     pub(crate) fn add_audio_module(lua: &Lua) -> mlua::Result<()> {
         let audio = lua.create_table()?;
 
@@ -230,7 +422,13 @@ mod native {
                 move |_lua, (sound_ud, looped, volume): (AnyUserData, Option<bool>, Option<f32>)| {
                     let sound = sound_ud.borrow::<SoundHandle>()?;
                     sound.ensure_uploaded()?;
-                    play_sound(&sound, looped.unwrap_or(false), volume.unwrap_or(1.0))
+                    play_sound(
+                        &sound,
+                        looped.unwrap_or(false),
+                        0.0,
+                        0.0,
+                        volume.unwrap_or(1.0),
+                    )
                 },
             )?,
         )?;
@@ -239,9 +437,43 @@ mod native {
             lua.create_function(move |_lua, (sound_ud, volume): (AnyUserData, Option<f32>)| {
                 let sound = sound_ud.borrow::<SoundHandle>()?;
                 sound.ensure_uploaded()?;
-                play_sound(&sound, false, volume.unwrap_or(1.0))
+                play_sound(&sound, false, 0.0, 0.0, volume.unwrap_or(1.0))
             })?,
         )?;
+        audio.set(
+            "playLooped",
+            lua.create_function(
+                move |_lua,
+                      (sound_ud, start, end, volume): (
+                    AnyUserData,
+                    f32,
+                    Option<f32>,
+                    Option<f32>,
+                )| {
+                    let sound = sound_ud.borrow::<SoundHandle>()?;
+                    sound.ensure_uploaded()?;
+                    let (start, end) = sound.loop_region(start, end)?;
+                    let (loop_start, loop_end) =
+                        sound.with_samples(|sample_rate, channels, _samples| {
+                            let seconds = |index: usize| {
+                                (index / channels as usize) as f32 / sample_rate as f32
+                            };
+                            Ok((seconds(start), seconds(end)))
+                        })?;
+                    play_sound(&sound, true, loop_start, loop_end, volume.unwrap_or(1.0))
+                },
+            )?,
+        )?;
+        audio.set(
+            "playPanned",
+            lua.create_function(
+                move |_lua, (sound_ud, pan, volume): (AnyUserData, f32, Option<f32>)| {
+                    let sound = sound_ud.borrow::<SoundHandle>()?;
+                    sound.ensure_uploaded()?;
+                    play_panned(&sound, pan, volume.unwrap_or(1.0))
+                },
+            )?,
+        )?;
         audio.set(
             "stop",
             lua.create_function(move |_lua, sound_ud: AnyUserData| {
@@ -253,6 +485,22 @@ mod native {
                 )
             })?,
         )?;
+        audio.set(
+            "stopAll",
+            lua.create_function(move |_lua, ()| {
+                check_bridge_result(
+                    unsafe { neolove_web_audio_stop_all() },
+                    "failed to stop audio",
+                )
+            })?,
+        )?;
+        audio.set(
+            "isPlaying",
+            lua.create_function(move |_lua, sound_ud: AnyUserData| {
+                let sound = sound_ud.borrow::<SoundHandle>()?;
+                Ok(unsafe { neolove_web_audio_is_playing(sound.id() as i32) } != 0)
+            })?,
+        )?;
         audio.set(
             "setVolume",
             lua.create_function(move |_lua, (sound_ud, volume): (AnyUserData, f32)| {
@@ -260,13 +508,18 @@ mod native {
                 sound.ensure_uploaded()?;
                 check_bridge_result(
                     unsafe {
-                        neolove_web_audio_set_volume(sound.id() as i32, volume.clamp(0.0, 1.0))
+                        neolove_web_audio_set_volume(
+                            sound.id() as i32,
+                            super::effective_volume(volume),
+                        )
                     },
                     "failed to set audio volume",
                 )
             })?,
         )?;
 
+        super::add_master_volume_functions(lua, &audio)?;
+
         lua.globals().set("audio", audio)?;
         Ok(())
     }
@@ -0,0 +1,235 @@
+use crate::platform::{SharedPlatformState, WindowState};
+use crate::renderer::{DrawCommand, Vec2};
+use mlua::Lua;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CameraState {
+    // world point shown at the window center; None keeps the default screen-space view
+    position: Option<Vec2>,
+    zoom: f32,
+}
+
+impl Default for CameraState {
+    fn default() -> Self {
+        Self {
+            position: None,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl CameraState {
+    pub(crate) fn is_identity(&self) -> bool {
+        self.position.is_none() && self.zoom == 1.0
+    }
+
+    fn target(&self, window: WindowState) -> Vec2 {
+        self.position.unwrap_or(Vec2 {
+            x: window.width * 0.5,
+            y: window.height * 0.5,
+        })
+    }
+
+    pub(crate) fn world_to_screen(&self, window: WindowState, x: f32, y: f32) -> Vec2 {
+        let target = self.target(window);
+        Vec2 {
+            x: (x - target.x) * self.zoom + window.width * 0.5,
+            y: (y - target.y) * self.zoom + window.height * 0.5,
+        }
+    }
+
+    pub(crate) fn screen_to_world(&self, window: WindowState, x: f32, y: f32) -> Vec2 {
+        let target = self.target(window);
+        Vec2 {
+            x: (x - window.width * 0.5) / self.zoom + target.x,
+            y: (y - window.height * 0.5) / self.zoom + target.y,
+        }
+    }
+
+    pub(crate) fn apply(&self, window: WindowState, commands: &mut [DrawCommand]) {
+        if self.is_identity() {
+            return;
+        }
+        transform_commands(commands, self.zoom, |point| {
+            self.world_to_screen(window, point.x, point.y)
+        });
+    }
+}
+
+// maps game units to framebuffer pixels when `[window] high_dpi` makes them differ
+pub(crate) fn scale_commands(commands: &mut [DrawCommand], scale: f32) {
+    if scale == 1.0 {
+        return;
+    }
+    transform_commands(commands, scale, |point| Vec2 {
+        x: point.x * scale,
+        y: point.y * scale,
+    });
+}
+
+// `to_screen` moves points; sizes are multiplied by `zoom`
+fn transform_commands(commands: &mut [DrawCommand], zoom: f32, to_screen: impl Fn(Vec2) -> Vec2) {
+    for command in commands {
+        match command {
+            DrawCommand::Rect { x, y, w, h, .. } => {
+                let origin = to_screen(Vec2 { x: *x, y: *y });
+                *x = origin.x;
+                *y = origin.y;
+                *w *= zoom;
+                *h *= zoom;
+            }
+            DrawCommand::Triangle { a, b, c, .. } => {
+                *a = to_screen(*a);
+                *b = to_screen(*b);
+                *c = to_screen(*c);
+            }
+            DrawCommand::Circle { center, radius, .. } => {
+                *center = to_screen(*center);
+                *radius *= zoom;
+            }
+            DrawCommand::Image { dest, pivot, .. } => {
+                let origin = to_screen(Vec2 {
+                    x: dest.x,
+                    y: dest.y,
+                });
+                dest.x = origin.x;
+                dest.y = origin.y;
+                dest.w *= zoom;
+                dest.h *= zoom;
+                *pivot = to_screen(*pivot);
+            }
+            DrawCommand::Text(request) => {
+                let origin = to_screen(Vec2 {
+                    x: request.bounds.x,
+                    y: request.bounds.y,
+                });
+                request.bounds.x = origin.x;
+                request.bounds.y = origin.y;
+                request.bounds.w *= zoom;
+                request.bounds.h *= zoom;
+                request.pivot = to_screen(request.pivot);
+                request.scale *= zoom;
+                request.min_scale *= zoom;
+                request.padding_x *= zoom;
+                request.padding_y *= zoom;
+                request.letter_spacing *= zoom;
+            }
+            DrawCommand::SetBlend(_) => {}
+        }
+    }
+}
+
+pub(crate) type SharedCameraState = Rc<RefCell<CameraState>>;
+
+pub(crate) fn new_shared_camera_state() -> SharedCameraState {
+    Rc::new(RefCell::new(CameraState::default()))
+}
+
+pub(crate) fn add_camera_module(
+    lua: &Lua,
+    camera: SharedCameraState,
+    platform: SharedPlatformState,
+) -> mlua::Result<()> {
+    let module = lua.create_table()?;
+
+    {
+        let camera = camera.clone();
+        module.set(
+            "setPosition",
+            lua.create_function(move |_lua, (x, y): (f32, f32)| {
+                if !x.is_finite() || !y.is_finite() {
+                    return Err(mlua::Error::external(
+                        "camera.setPosition expects finite numbers",
+                    ));
+                }
+                camera.borrow_mut().position = Some(Vec2 { x, y });
+                Ok(())
+            })?,
+        )?;
+    }
+
+    {
+        let camera = camera.clone();
+        let platform = platform.clone();
+        module.set(
+            "getPosition",
+            lua.create_function(move |_lua, ()| {
+                let window = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+                    .window();
+                let target = camera.borrow().target(window);
+                Ok((target.x, target.y))
+            })?,
+        )?;
+    }
+
+    {
+        let camera = camera.clone();
+        module.set(
+            "setZoom",
+            lua.create_function(move |_lua, zoom: f32| {
+                if !zoom.is_finite() || zoom <= 0.0 {
+                    return Err(mlua::Error::external(
+                        "camera.setZoom expects a positive number",
+                    ));
+                }
+                camera.borrow_mut().zoom = zoom;
+                Ok(())
+            })?,
+        )?;
+    }
+
+    {
+        let camera = camera.clone();
+        module.set(
+            "getZoom",
+            lua.create_function(move |_lua, ()| Ok(camera.borrow().zoom))?,
+        )?;
+    }
+
+    {
+        let camera = camera.clone();
+        module.set(
+            "reset",
+            lua.create_function(move |_lua, ()| {
+                *camera.borrow_mut() = CameraState::default();
+                Ok(())
+            })?,
+        )?;
+    }
+
+    lua.globals().set("camera", module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screen_and_world_conversions_round_trip() {
+        let window = WindowState {
+            width: 800.0,
+            height: 600.0,
+        };
+        let camera = CameraState {
+            position: Some(Vec2 { x: 1000.0, y: 50.0 }),
+            zoom: 2.0,
+        };
+
+        let center = camera.world_to_screen(window, 1000.0, 50.0);
+        assert_eq!((center.x, center.y), (400.0, 300.0));
+
+        let screen = camera.world_to_screen(window, 1010.0, 40.0);
+        assert_eq!((screen.x, screen.y), (420.0, 280.0));
+        let world = camera.screen_to_world(window, screen.x, screen.y);
+        assert_eq!((world.x, world.y), (1010.0, 40.0));
+
+        let default = CameraState::default();
+        let same = default.world_to_screen(window, 12.0, 34.0);
+        assert_eq!((same.x, same.y), (12.0, 34.0));
+    }
+}
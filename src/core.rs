@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use crate::assets::ImageHandle;
+use crate::camera::SharedCameraState;
 use crate::lua_error::protect_lua_call;
 use crate::platform::{Color, InputState, SharedPlatformState, WindowState};
 use crate::renderer::{
@@ -9,7 +10,8 @@ use crate::renderer::{
 };
 use mlua::{AnyUserData, Function, Lua, Table, Value};
 use std::path::{Component, Path, PathBuf};
-use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 fn color4(lua: &Lua, r: u8, g: u8, b: u8, a: u8) -> mlua::Result<Table> {
     let color = lua.create_table()?;
@@ -75,6 +77,10 @@ fn resolve_font_path(root: &Path, input: &str) -> Option<String> {
 
 fn parse_font_handle(root: &Path, value: Value) -> FontHandle {
     match value {
+        Value::UserData(user_data) => user_data
+            .borrow::<crate::assets::FontAssetHandle>()
+            .map(|font| FontHandle::Path(font.path().to_string()))
+            .unwrap_or(FontHandle::Default),
         Value::String(value) => value
             .to_str()
             .ok()
@@ -159,7 +165,7 @@ fn uses_entity_text_bounds(component: &Table) -> bool {
     }
 }
 
-fn app_texture_filter(lua: &Lua) -> TextureFilter {
+pub(crate) fn app_texture_filter(lua: &Lua) -> TextureFilter {
     let nearest = lua
         .globals()
         .get::<Table>("app")
@@ -243,17 +249,13 @@ struct UiPopupRegion {
     rotation: f32,
 }
 
+// kept in the Lua app data so every runtime has its own
 #[derive(Default)]
 struct UiFrameState {
     active_popups: Vec<UiPopupRegion>,
     next_popups: Vec<UiPopupRegion>,
 }
 
-fn ui_frame_state() -> &'static Mutex<UiFrameState> {
-    static STATE: OnceLock<Mutex<UiFrameState>> = OnceLock::new();
-    STATE.get_or_init(|| Mutex::new(UiFrameState::default()))
-}
-
 fn upsert_popup(popups: &mut Vec<UiPopupRegion>, popup: UiPopupRegion) {
     if let Some(existing) = popups
         .iter_mut()
@@ -265,22 +267,26 @@ fn upsert_popup(popups: &mut Vec<UiPopupRegion>, popup: UiPopupRegion) {
     }
 }
 
-pub(crate) fn begin_ui_frame() {
-    if let Ok(mut state) = ui_frame_state().lock() {
+pub(crate) fn begin_ui_frame(lua: &Lua) {
+    if let Some(mut state) = lua.app_data_mut::<UiFrameState>() {
         state.active_popups = std::mem::take(&mut state.next_popups);
     }
 }
 
-fn current_input_snapshot(platform: &SharedPlatformState) -> mlua::Result<UiInputSnapshot> {
+// widgets are laid out in world space like every other rendering component, so the mouse is
+// moved through the camera before it's tested against their bounds
+fn current_input_snapshot(
+    platform: &SharedPlatformState,
+    camera: &SharedCameraState,
+) -> mlua::Result<UiInputSnapshot> {
     let platform = platform
         .lock()
         .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
     let mouse = platform.mouse();
     Ok(UiInputSnapshot {
-        mouse: Vec2 {
-            x: mouse.x,
-            y: mouse.y,
-        },
+        mouse: camera
+            .borrow()
+            .screen_to_world(platform.window(), mouse.x, mouse.y),
         input: platform.input().clone(),
         window: platform.window(),
     })
@@ -361,21 +367,21 @@ fn component_owner_key(entity: &Table, component: &Table) -> String {
     format!("{entity_id}:{name}")
 }
 
-fn register_popup(owner: String, bounds: Rect, pivot: Vec2, rotation: f32) {
+fn register_popup(lua: &Lua, owner: String, bounds: Rect, pivot: Vec2, rotation: f32) {
     let popup = UiPopupRegion {
         owner,
         bounds,
         pivot,
         rotation,
     };
-    if let Ok(mut state) = ui_frame_state().lock() {
+    if let Some(mut state) = lua.app_data_mut::<UiFrameState>() {
         upsert_popup(&mut state.active_popups, popup.clone());
         upsert_popup(&mut state.next_popups, popup);
     }
 }
 
-fn point_blocked_by_popup(point: Vec2, owner: &str) -> bool {
-    if let Ok(state) = ui_frame_state().lock() {
+fn point_blocked_by_popup(lua: &Lua, point: Vec2, owner: &str) -> bool {
+    if let Some(state) = lua.app_data_ref::<UiFrameState>() {
         state.active_popups.iter().any(|popup| {
             popup.owner != owner
                 && point_in_bounds(point, popup.bounds, popup.pivot, popup.rotation)
@@ -600,6 +606,27 @@ fn get_number_field(component: &Table, snake_case: &str, camel_case: &str) -> Op
         .filter(|value| value.is_finite())
 }
 
+fn get_bool_field(component: &Table, snake_case: &str, camel_case: &str) -> Option<bool> {
+    component
+        .get::<bool>(snake_case)
+        .or_else(|_| component.get::<bool>(camel_case))
+        .ok()
+}
+
+// a negative source extent samples the texture backwards, which mirrors the sprite
+fn flip_source_rect(source: Rect, flip_x: bool, flip_y: bool) -> Rect {
+    let mut flipped = source;
+    if flip_x {
+        flipped.x = source.x + source.w;
+        flipped.w = -source.w;
+    }
+    if flip_y {
+        flipped.y = source.y + source.h;
+        flipped.h = -source.h;
+    }
+    flipped
+}
+
 fn get_image_field(component: &Table, key: &str) -> mlua::Result<Option<ImageHandle>> {
     let image: Option<AnyUserData> = component.get(key).unwrap_or(None);
     let Some(image) = image else {
@@ -643,6 +670,117 @@ fn get_source_rect(component: &Table, prefix: &str) -> Option<Rect> {
     Some(Rect { x, y, w, h })
 }
 
+// the part of `source` inside a width x height image; None when nothing of it is left
+fn clamp_source_rect(source: Rect, width: f32, height: f32) -> Option<Rect> {
+    let x0 = source.x.clamp(0.0, width);
+    let y0 = source.y.clamp(0.0, height);
+    let x1 = (source.x + source.w).clamp(0.0, width);
+    let y1 = (source.y + source.h).clamp(0.0, height);
+    (x1 > x0 && y1 > y0).then_some(Rect {
+        x: x0,
+        y: y0,
+        w: x1 - x0,
+        h: y1 - y0,
+    })
+}
+
+// the part of `dest` that `clamped` (cut out of `source`) still covers, so the clamped pixels keep
+// their size and place instead of stretching over the whole of it; flips mirror which side is cut
+fn clamped_dest_rect(dest: Rect, source: Rect, clamped: Rect, flip_x: bool, flip_y: bool) -> Rect {
+    let scale_x = dest.w / source.w;
+    let scale_y = dest.h / source.h;
+    let left = if flip_x {
+        source.x + source.w - (clamped.x + clamped.w)
+    } else {
+        clamped.x - source.x
+    };
+    let top = if flip_y {
+        source.y + source.h - (clamped.y + clamped.h)
+    } else {
+        clamped.y - source.y
+    };
+    Rect {
+        x: dest.x + left * scale_x,
+        y: dest.y + top * scale_y,
+        w: clamped.w * scale_x,
+        h: clamped.h * scale_y,
+    }
+}
+
+// splits `source` at the insets (left, right, top, bottom in source pixels) into up to nine
+// (source, dest) pairs: corners keep their size times `scale`, edges and center stretch
+fn nine_slice_pieces(source: Rect, insets: [f32; 4], dest: Rect, scale: f32) -> Vec<(Rect, Rect)> {
+    let [left, right, top, bottom] = insets.map(|inset| inset.max(0.0));
+    // insets wider than the image, or corners wider than the entity, shrink proportionally
+    let fit = |a: f32, b: f32, limit: f32| {
+        let total = a + b;
+        if total > limit && total > 0.0 {
+            (a * limit / total, b * limit / total)
+        } else {
+            (a, b)
+        }
+    };
+    let (left, right) = fit(left, right, source.w);
+    let (top, bottom) = fit(top, bottom, source.h);
+    let (dest_left, dest_right) = fit(left * scale, right * scale, dest.w);
+    let (dest_top, dest_bottom) = fit(top * scale, bottom * scale, dest.h);
+
+    let columns = [
+        (source.x, left, dest.x, dest_left),
+        (
+            source.x + left,
+            source.w - left - right,
+            dest.x + dest_left,
+            dest.w - dest_left - dest_right,
+        ),
+        (
+            source.x + source.w - right,
+            right,
+            dest.x + dest.w - dest_right,
+            dest_right,
+        ),
+    ];
+    let rows = [
+        (source.y, top, dest.y, dest_top),
+        (
+            source.y + top,
+            source.h - top - bottom,
+            dest.y + dest_top,
+            dest.h - dest_top - dest_bottom,
+        ),
+        (
+            source.y + source.h - bottom,
+            bottom,
+            dest.y + dest.h - dest_bottom,
+            dest_bottom,
+        ),
+    ];
+
+    let mut pieces = Vec::with_capacity(9);
+    for (src_y, src_h, dest_y, dest_h) in rows {
+        for (src_x, src_w, dest_x, dest_w) in columns {
+            if src_w <= 0.0 || src_h <= 0.0 || dest_w <= 0.0 || dest_h <= 0.0 {
+                continue;
+            }
+            pieces.push((
+                Rect {
+                    x: src_x,
+                    y: src_y,
+                    w: src_w,
+                    h: src_h,
+                },
+                Rect {
+                    x: dest_x,
+                    y: dest_y,
+                    w: dest_w,
+                    h: dest_h,
+                },
+            ));
+        }
+    }
+    pieces
+}
+
 fn parse_icon_side(raw: &str) -> UiIconSide {
     match raw.trim().to_ascii_lowercase().as_str() {
         "right" | "end" => UiIconSide::Right,
@@ -1240,11 +1378,39 @@ fn call_component_selection_callback(
     Ok(())
 }
 
-fn create_basic_drawable(lua: &Lua) -> mlua::Result<Table> {
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::rgba(
+        mix(from.r, to.r),
+        mix(from.g, to.g),
+        mix(from.b, to.b),
+        mix(from.a, to.a),
+    )
+}
+
+// xorshift32 so each emitter carries its own sequence in a single component field
+fn next_particle_random(state: &mut u32) -> f32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    (*state >> 8) as f32 / (1u32 << 24) as f32
+}
+
+fn particle_seed() -> u32 {
+    static COUNTER: AtomicU32 = AtomicU32::new(0x9e37_79b9);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or(0);
+    (COUNTER.fetch_add(0x6d2b_79f5, Ordering::Relaxed) ^ nanos) | 1
+}
+
+fn create_basic_drawable(lua: &Lua, name: &'static str) -> mlua::Result<Table> {
     let drawable = lua.create_table()?;
     drawable.set(
         "awake",
         lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
+            component.set("__neolove_component", name)?;
             component.set("color", color4(ctx, 255, 255, 255, 255)?)?;
             component.set("visible", true)?;
             Ok(())
@@ -1257,10 +1423,12 @@ fn create_basic_drawable(lua: &Lua) -> mlua::Result<Table> {
 pub fn add_core_components(
     lua: &Lua,
     platform: SharedPlatformState,
+    camera: SharedCameraState,
     render_state: SharedRenderState,
     env_root: PathBuf,
 ) -> mlua::Result<()> {
     let core_components = lua.create_table()?;
+    lua.set_app_data(UiFrameState::default());
 
     // Color4
     // not a component!? helper function to generate color4 values
@@ -1283,7 +1451,7 @@ pub fn add_core_components(
     // Rect2d
     // basic renderer
     {
-        let rect2d = create_basic_drawable(lua)?;
+        let rect2d = create_basic_drawable(lua, "Rect2D")?;
         let render_state = render_state.clone();
         rect2d.set(
             "update",
@@ -1323,11 +1491,12 @@ pub fn add_core_components(
     // Shape2D
     // renderer for box, circle, and right-triangle primitives
     {
-        let shape2d = create_basic_drawable(lua)?;
+        let shape2d = create_basic_drawable(lua, "Shape2D")?;
         let render_state = render_state.clone();
         shape2d.set(
             "awake",
             lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
+                component.set("__neolove_component", "Shape2D")?;
                 component.set("color", color4(ctx, 255, 255, 255, 255)?)?;
                 component.set("visible", true)?;
                 component.set("shape", "box")?;
@@ -1441,10 +1610,116 @@ pub fn add_core_components(
         core_components.set("Shape2D", shape2d)?;
     }
 
+    // Circle2D
+    // filled circle centered on the entity origin
+    {
+        let circle2d = create_basic_drawable(lua, "Circle2D")?;
+        let render_state = render_state.clone();
+        circle2d.set(
+            "awake",
+            lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
+                component.set("__neolove_component", "Circle2D")?;
+                component.set("color", color4(ctx, 255, 255, 255, 255)?)?;
+                component.set("visible", true)?;
+                component.set("radius", 16.0)?;
+                component.set("offset_x", 0.0)?;
+                component.set("offset_y", 0.0)?;
+                Ok(())
+            })?,
+        )?;
+        circle2d.set(
+            "update",
+            lua.create_function(move |_ctx, (entity, component, _dt): (Table, Table, f32)| {
+                if !component.get::<bool>("visible").unwrap_or(true) {
+                    return Ok(());
+                }
+                let (origin_x, origin_y, rotation) = crate::window::get_global_transform(&entity)?;
+                let entity_scale = crate::window::get_global_scale(&entity)?;
+                let radius = component.get::<f32>("radius").unwrap_or(16.0) * entity_scale;
+                if radius <= 0.0 {
+                    return Ok(());
+                }
+                let offset_x = component.get::<f32>("offset_x").unwrap_or(0.0) * entity_scale;
+                let offset_y = component.get::<f32>("offset_y").unwrap_or(0.0) * entity_scale;
+                let (rx, ry) = rotate_local(offset_x, offset_y, rotation);
+                let color = color4_to_color(component.get("color")?)?;
+
+                let mut renderer = render_state
+                    .lock()
+                    .map_err(|_| mlua::Error::external("render state lock poisoned"))?;
+                renderer.queue(DrawCommand::Circle {
+                    center: Vec2 {
+                        x: origin_x + rx,
+                        y: origin_y + ry,
+                    },
+                    radius,
+                    color,
+                });
+                Ok(())
+            })?,
+        )?;
+
+        core_components.set("Circle2D", circle2d)?;
+    }
+
+    // Line2D
+    // line segment from the entity origin to (x2, y2) in the entity's local space
+    {
+        let line2d = create_basic_drawable(lua, "Line2D")?;
+        let render_state = render_state.clone();
+        line2d.set(
+            "awake",
+            lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
+                component.set("__neolove_component", "Line2D")?;
+                component.set("color", color4(ctx, 255, 255, 255, 255)?)?;
+                component.set("visible", true)?;
+                component.set("x2", 32.0)?;
+                component.set("y2", 0.0)?;
+                component.set("thickness", 1.0)?;
+                Ok(())
+            })?,
+        )?;
+        line2d.set(
+            "update",
+            lua.create_function(move |_ctx, (entity, component, _dt): (Table, Table, f32)| {
+                if !component.get::<bool>("visible").unwrap_or(true) {
+                    return Ok(());
+                }
+                let (origin_x, origin_y, rotation) = crate::window::get_global_transform(&entity)?;
+                let entity_scale = crate::window::get_global_scale(&entity)?;
+                let end_x = component.get::<f32>("x2").unwrap_or(0.0) * entity_scale;
+                let end_y = component.get::<f32>("y2").unwrap_or(0.0) * entity_scale;
+                let thickness = component.get::<f32>("thickness").unwrap_or(1.0) * entity_scale;
+                let length = (end_x * end_x + end_y * end_y).sqrt();
+                if length <= 0.0 || thickness <= 0.0 {
+                    return Ok(());
+                }
+                let color = color4_to_color(component.get("color")?)?;
+
+                // a thin rect pivoting on its left-middle edge at the start point
+                let mut renderer = render_state
+                    .lock()
+                    .map_err(|_| mlua::Error::external("render state lock poisoned"))?;
+                renderer.queue(DrawCommand::Rect {
+                    x: origin_x,
+                    y: origin_y - thickness * 0.5,
+                    w: length,
+                    h: thickness,
+                    rotation: rotation + end_y.atan2(end_x),
+                    offset: Vec2 { x: 0.0, y: 0.5 },
+                    color,
+                });
+                Ok(())
+            })?,
+        )?;
+
+        core_components.set("Line2D", line2d)?;
+    }
+
     // TextBox
     // bounded text with optional auto-fit scaling, alignment, wrapping, and font selection
     {
-        let textbox = create_basic_drawable(lua)?;
+        let textbox = create_basic_drawable(lua, "TextBox")?;
         let render_state = render_state.clone();
         let text_root = env_root.clone();
         textbox.set(
@@ -1461,6 +1736,7 @@ pub fn add_core_components(
                 component.set("align_x", "left")?;
                 component.set("align_y", "top")?;
                 component.set("wrap", "none")?;
+                component.set("max_width", 0.0)?;
                 component.set("size_mode", "content")?;
                 component.set("padding", 0.0)?;
                 component.set("padding_x", 0.0)?;
@@ -1473,6 +1749,7 @@ pub fn add_core_components(
                 component.set("dx", 0.0)?;
                 component.set("dy", 0.0)?;
                 component.set("line_count", 0)?;
+                component.set("baseline", false)?;
                 Ok(())
             })?,
         )?;
@@ -1520,8 +1797,17 @@ pub fn add_core_components(
                         .or_else(|_| component.get::<String>("textScale"))
                         .unwrap_or_else(|_| "none".to_string()),
                 );
-                let wrap = parse_wrap_mode(component.get::<Value>("wrap").unwrap_or(Value::Nil));
+                let mut wrap =
+                    parse_wrap_mode(component.get::<Value>("wrap").unwrap_or(Value::Nil));
                 let size_mode_uses_entity = uses_entity_text_bounds(&component);
+                // content-sized text has no box to wrap in, so max_width supplies one
+                let max_width = component
+                    .get::<f32>("max_width")
+                    .ok()
+                    .filter(|width| !size_mode_uses_entity && width.is_finite() && *width > 0.0);
+                if max_width.is_some() && matches!(wrap, TextWrapMode::None) {
+                    wrap = TextWrapMode::Word;
+                }
                 let legacy_scale_x = component.get::<f32>("scale_x").unwrap_or(0.0);
                 let legacy_scale_y = component.get::<f32>("scale_y").unwrap_or(0.0);
                 let use_legacy_stretch =
@@ -1557,14 +1843,14 @@ pub fn add_core_components(
                         Rect {
                             x,
                             y,
-                            w: 0.0,
+                            w: max_width.unwrap_or(0.0),
                             h: 0.0,
                         },
                         Vec2 { x, y },
                     )
                 };
 
-                let request = TextRenderRequest {
+                let mut request = TextRenderRequest {
                     text,
                     bounds,
                     rotation,
@@ -1599,6 +1885,21 @@ pub fn add_core_components(
                 component.set("used_scale", metrics.used_scale)?;
                 component.set("line_count", metrics.line_count)?;
 
+                // content-sized text anchors on the entity origin: align picks which
+                // edge sits there and baseline puts the first baseline on the origin
+                if !size_mode_uses_entity {
+                    // wrapped lines are aligned inside max_width, so that box is what gets anchored
+                    let anchored_width = max_width.unwrap_or(metrics.width);
+                    request.bounds.x -= match align_x {
+                        TextAlignX::Left => 0.0,
+                        TextAlignX::Center => anchored_width * 0.5,
+                        TextAlignX::Right => anchored_width,
+                    };
+                    if component.get::<bool>("baseline").unwrap_or(false) {
+                        request.bounds.y -= metrics.baseline;
+                    }
+                }
+
                 let mut renderer = render_state
                     .lock()
                     .map_err(|_| mlua::Error::external("render state lock poisoned"))?;
@@ -1619,7 +1920,7 @@ pub fn add_core_components(
     // Frame
     // customizable UI panel with borders, rounded corners, and optional 9-slice background image
     {
-        let frame = create_basic_drawable(lua)?;
+        let frame = create_basic_drawable(lua, "Frame")?;
         frame.set(
             "awake",
             lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
@@ -1673,7 +1974,7 @@ pub fn add_core_components(
     // Button
     // interactive UI button with customizable panel states and text rendering
     {
-        let button = create_basic_drawable(lua)?;
+        let button = create_basic_drawable(lua, "Button")?;
         button.set(
             "awake",
             lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
@@ -1725,6 +2026,7 @@ pub fn add_core_components(
         )?;
 
         let button_platform = platform.clone();
+        let button_camera = camera.clone();
         let button_root = env_root.clone();
         let render_state = render_state.clone();
         button.set(
@@ -1735,12 +2037,12 @@ pub fn add_core_components(
                 }
 
                 let draw = get_entity_draw_context(&entity)?;
-                let snapshot = current_input_snapshot(&button_platform)?;
+                let snapshot = current_input_snapshot(&button_platform, &button_camera)?;
                 let owner_key = component_owner_key(&entity, &component);
                 let enabled = component.get::<bool>("enabled").unwrap_or(true);
                 let hovered = enabled
                     && point_in_bounds(snapshot.mouse, draw.bounds, draw.pivot, draw.rotation)
-                    && !point_blocked_by_popup(snapshot.mouse, &owner_key);
+                    && !point_blocked_by_popup(ctx, snapshot.mouse, &owner_key);
                 let was_hovered = component.get::<bool>("hovered").unwrap_or(false);
                 if hovered != was_hovered {
                     component.set("hovered", hovered)?;
@@ -1874,7 +2176,7 @@ pub fn add_core_components(
     // TextInput
     // single-line text field with focus, caret, placeholder, and submit/change callbacks
     {
-        let text_input = create_basic_drawable(lua)?;
+        let text_input = create_basic_drawable(lua, "TextInput")?;
         text_input.set(
             "awake",
             lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
@@ -1936,6 +2238,7 @@ pub fn add_core_components(
         )?;
 
         let input_platform = platform.clone();
+        let input_camera = camera.clone();
         let text_root = env_root.clone();
         let render_state = render_state.clone();
         text_input.set(
@@ -1946,12 +2249,12 @@ pub fn add_core_components(
                 }
 
                 let draw = get_entity_draw_context(&entity)?;
-                let snapshot = current_input_snapshot(&input_platform)?;
+                let snapshot = current_input_snapshot(&input_platform, &input_camera)?;
                 let owner_key = component_owner_key(&entity, &component);
                 let enabled = component.get::<bool>("enabled").unwrap_or(true);
                 let hovered = enabled
                     && point_in_bounds(snapshot.mouse, draw.bounds, draw.pivot, draw.rotation)
-                    && !point_blocked_by_popup(snapshot.mouse, &owner_key);
+                    && !point_blocked_by_popup(ctx, snapshot.mouse, &owner_key);
                 let was_focused = component.get::<bool>("focused").unwrap_or(false);
                 let was_hovered = component.get::<bool>("hovered").unwrap_or(false);
                 if hovered != was_hovered {
@@ -2024,7 +2327,9 @@ pub fn add_core_components(
                         }
                     }
 
-                    if let Some(ch) = snapshot.input.char_pressed.clone() {
+                    // everything typed this frame, so fast typing doesn't drop characters
+                    let ch = snapshot.input.text_input.clone();
+                    if !ch.is_empty() {
                         let max_length = component.get::<usize>("max_length").unwrap_or(0);
                         let text_len = char_count(&text);
                         let insert_len = char_count(&ch);
@@ -2222,7 +2527,7 @@ pub fn add_core_components(
     // Dropdown
     // selectable list with customizable closed/open state styling
     {
-        let dropdown = create_basic_drawable(lua)?;
+        let dropdown = create_basic_drawable(lua, "Dropdown")?;
         dropdown.set(
             "awake",
             lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
@@ -2300,6 +2605,7 @@ pub fn add_core_components(
         )?;
 
         let dropdown_platform = platform.clone();
+        let dropdown_camera = camera.clone();
         let dropdown_root = env_root.clone();
         let render_state = render_state.clone();
         dropdown.set(
@@ -2310,7 +2616,7 @@ pub fn add_core_components(
                 }
 
                 let draw = get_entity_draw_context(&entity)?;
-                let snapshot = current_input_snapshot(&dropdown_platform)?;
+                let snapshot = current_input_snapshot(&dropdown_platform, &dropdown_camera)?;
                 let owner_key = component_owner_key(&entity, &component);
                 let enabled = component.get::<bool>("enabled").unwrap_or(true);
                 let items =
@@ -2325,7 +2631,7 @@ pub fn add_core_components(
                 let mut open = component.get::<bool>("open").unwrap_or(false) && enabled;
                 let hovered = enabled
                     && point_in_bounds(snapshot.mouse, draw.bounds, draw.pivot, draw.rotation)
-                    && !point_blocked_by_popup(snapshot.mouse, &owner_key);
+                    && !point_blocked_by_popup(ctx, snapshot.mouse, &owner_key);
 
                 let item_height = component.get::<f32>("item_height").unwrap_or(32.0).max(1.0);
                 let item_corner_radius = component
@@ -2370,13 +2676,13 @@ pub fn add_core_components(
                     h: menu_height,
                 };
                 if open && visible_count > 0 {
-                    register_popup(owner_key.clone(), menu_bounds, draw.pivot, draw.rotation);
+                    register_popup(ctx, owner_key.clone(), menu_bounds, draw.pivot, draw.rotation);
                 }
 
                 let menu_hovered = open
                     && visible_count > 0
                     && point_in_bounds(snapshot.mouse, menu_bounds, draw.pivot, draw.rotation)
-                    && !point_blocked_by_popup(snapshot.mouse, &owner_key);
+                    && !point_blocked_by_popup(ctx, snapshot.mouse, &owner_key);
 
                 let mut hovered_index = 0usize;
                 if menu_hovered {
@@ -2693,7 +2999,7 @@ pub fn add_core_components(
     // ScrollList
     // scrolling list view with selection, keyboard navigation, and customizable item styling
     {
-        let scroll_list = create_basic_drawable(lua)?;
+        let scroll_list = create_basic_drawable(lua, "ScrollList")?;
         scroll_list.set(
             "awake",
             lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
@@ -2771,6 +3077,7 @@ pub fn add_core_components(
         )?;
 
         let scroll_list_platform = platform.clone();
+        let scroll_list_camera = camera.clone();
         let scroll_list_root = env_root.clone();
         let render_state = render_state.clone();
         scroll_list.set(
@@ -2781,12 +3088,12 @@ pub fn add_core_components(
                 }
 
                 let draw = get_entity_draw_context(&entity)?;
-                let snapshot = current_input_snapshot(&scroll_list_platform)?;
+                let snapshot = current_input_snapshot(&scroll_list_platform, &scroll_list_camera)?;
                 let owner_key = component_owner_key(&entity, &component);
                 let enabled = component.get::<bool>("enabled").unwrap_or(true);
                 let hovered = enabled
                     && point_in_bounds(snapshot.mouse, draw.bounds, draw.pivot, draw.rotation)
-                    && !point_blocked_by_popup(snapshot.mouse, &owner_key);
+                    && !point_blocked_by_popup(ctx, snapshot.mouse, &owner_key);
                 let was_focused = component.get::<bool>("focused").unwrap_or(false);
                 let mut focused = was_focused;
                 if !enabled {
@@ -2946,13 +3253,13 @@ pub fn add_core_components(
                 let track_hovered = track_bounds
                     .map(|track_bounds| {
                         point_in_bounds(snapshot.mouse, track_bounds, draw.pivot, draw.rotation)
-                            && !point_blocked_by_popup(snapshot.mouse, &owner_key)
+                            && !point_blocked_by_popup(ctx, snapshot.mouse, &owner_key)
                     })
                     .unwrap_or(false);
                 let thumb_hovered = thumb_bounds
                     .map(|thumb_bounds| {
                         point_in_bounds(snapshot.mouse, thumb_bounds, draw.pivot, draw.rotation)
-                            && !point_blocked_by_popup(snapshot.mouse, &owner_key)
+                            && !point_blocked_by_popup(ctx, snapshot.mouse, &owner_key)
                     })
                     .unwrap_or(false);
                 let mut scrollbar_dragging =
@@ -3072,7 +3379,7 @@ pub fn add_core_components(
                             continue;
                         }
                         if point_in_bounds(snapshot.mouse, item_bounds, draw.pivot, draw.rotation)
-                            && !point_blocked_by_popup(snapshot.mouse, &owner_key)
+                            && !point_blocked_by_popup(ctx, snapshot.mouse, &owner_key)
                         {
                             hovered_index = scroll_index + visible_index + 1;
                             break;
@@ -3312,7 +3619,7 @@ pub fn add_core_components(
     // Image2D
     // draw an image (texture) tinted by component.color, scaled to entity size
     {
-        let image2d = create_basic_drawable(lua)?;
+        let image2d = create_basic_drawable(lua, "Image2D")?;
         let render_state = render_state.clone();
 
         image2d.set(
@@ -3333,6 +3640,20 @@ pub fn add_core_components(
 
                 let image = image.borrow::<crate::assets::ImageHandle>()?;
                 image.ensure_uploaded()?;
+                let rotation = rotation + get_number_key(&component, "rotation").unwrap_or(0.0);
+                let flip_x = get_bool_field(&component, "flip_x", "flipX").unwrap_or(false);
+                let flip_y = get_bool_field(&component, "flip_y", "flipY").unwrap_or(false);
+                // src_x/src_y/src_w/src_h pick a sub-rectangle, e.g. one frame of a sprite sheet
+                let requested = get_source_rect(&component, "src");
+                let mut source = None;
+                if let Some(rect) = requested {
+                    let (image_w, image_h) = image.dimensions()?;
+                    // a rectangle hanging off the image would sample past its edges
+                    let Some(rect) = clamp_source_rect(rect, image_w as f32, image_h as f32) else {
+                        return Ok(());
+                    };
+                    source = Some(rect);
+                }
                 let (draw_x, draw_y, pivot) = if use_middle_pivot {
                     let (px, py) = crate::window::get_global_rotation_pivot(&entity)?;
                     // draw_texture_ex expects the unrotated rectangle origin when pivot is provided.
@@ -3340,18 +3661,112 @@ pub fn add_core_components(
                 } else {
                     (x, y, Vec2 { x, y })
                 };
+
+                if component.get::<bool>("tile").unwrap_or(false) {
+                    let full = match source {
+                        Some(source) => source,
+                        None => {
+                            let (image_w, image_h) = image.dimensions()?;
+                            Rect {
+                                x: 0.0,
+                                y: 0.0,
+                                w: image_w as f32,
+                                h: image_h as f32,
+                            }
+                        }
+                    };
+                    let tile_scale = get_number_key(&component, "tile_scale")
+                        .filter(|scale| scale.is_finite() && *scale > 0.0)
+                        .unwrap_or(1.0)
+                        * crate::window::get_global_scale(&entity)?;
+                    let tile_w = full.w * tile_scale;
+                    let tile_h = full.h * tile_scale;
+                    if w <= 0.0 || h <= 0.0 || tile_w <= 0.0 || tile_h <= 0.0 {
+                        return Ok(());
+                    }
+                    // a degenerate tile size would queue an unbounded number of draws
+                    if (w / tile_w).ceil() * (h / tile_h).ceil() > 65_536.0 {
+                        return Ok(());
+                    }
+
+                    let mut renderer = render_state
+                        .lock()
+                        .map_err(|_| mlua::Error::external("render state lock poisoned"))?;
+                    let mut tile_y = 0.0;
+                    while tile_y < h {
+                        let dest_h = tile_h.min(h - tile_y);
+                        let mut tile_x = 0.0;
+                        while tile_x < w {
+                            let dest_w = tile_w.min(w - tile_x);
+                            // edge tiles are cropped, keeping the part that would be on screen
+                            let src_w = full.w * dest_w / tile_w;
+                            let src_h = full.h * dest_h / tile_h;
+                            let cropped = Rect {
+                                x: if flip_x {
+                                    full.x + full.w - src_w
+                                } else {
+                                    full.x
+                                },
+                                y: if flip_y {
+                                    full.y + full.h - src_h
+                                } else {
+                                    full.y
+                                },
+                                w: src_w,
+                                h: src_h,
+                            };
+                            renderer.queue(DrawCommand::Image {
+                                image: image.clone(),
+                                dest: Rect {
+                                    x: draw_x + tile_x,
+                                    y: draw_y + tile_y,
+                                    w: dest_w,
+                                    h: dest_h,
+                                },
+                                source: Some(flip_source_rect(cropped, flip_x, flip_y)),
+                                rotation,
+                                pivot,
+                                tint,
+                                filter: app_texture_filter(ctx),
+                            });
+                            tile_x += tile_w;
+                        }
+                        tile_y += tile_h;
+                    }
+                    return Ok(());
+                }
+
+                let mut dest = Rect {
+                    x: draw_x,
+                    y: draw_y,
+                    w,
+                    h,
+                };
+                if let (Some(requested), Some(clamped)) = (requested, source) {
+                    dest = clamped_dest_rect(dest, requested, clamped, flip_x, flip_y);
+                }
+                if flip_x || flip_y {
+                    let full = match source {
+                        Some(source) => source,
+                        None => {
+                            let (image_w, image_h) = image.dimensions()?;
+                            Rect {
+                                x: 0.0,
+                                y: 0.0,
+                                w: image_w as f32,
+                                h: image_h as f32,
+                            }
+                        }
+                    };
+                    source = Some(flip_source_rect(full, flip_x, flip_y));
+                }
                 let mut renderer = render_state
                     .lock()
                     .map_err(|_| mlua::Error::external("render state lock poisoned"))?;
                 renderer.queue(DrawCommand::Image {
                     image: image.clone(),
-                    dest: Rect {
-                        x: draw_x,
-                        y: draw_y,
-                        w,
-                        h,
-                    },
-                    source: None,
+                    dest,
+                    source,
                     rotation,
                     pivot,
                     tint,
@@ -3368,12 +3783,13 @@ pub fn add_core_components(
     // TileTexture2D
     // draw an image repeatedly to fill entity size, with optional tile sizing and offset
     {
-        let tile_texture2d = create_basic_drawable(lua)?;
+        let tile_texture2d = create_basic_drawable(lua, "TileTexture2D")?;
         let platform = platform.clone();
         let render_state = render_state.clone();
         tile_texture2d.set(
             "awake",
             lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
+                component.set("__neolove_component", "TileTexture2D")?;
                 component.set("color", color4(ctx, 255, 255, 255, 255)?)?;
                 component.set("visible", true)?;
                 component.set("tile_width", 0.0)?;
@@ -3544,6 +3960,426 @@ pub fn add_core_components(
         core_components.set("TileTexture2D", tile_texture2d)?;
     }
 
+    // NineSlice2D
+    // a scalable panel: the image's corners keep their size, edges and center stretch
+    {
+        let nine_slice = create_basic_drawable(lua, "NineSlice2D")?;
+        let render_state = render_state.clone();
+        nine_slice.set(
+            "awake",
+            lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
+                component.set("__neolove_component", "NineSlice2D")?;
+                component.set("color", color4(ctx, 255, 255, 255, 255)?)?;
+                component.set("visible", true)?;
+                component.set("left", 0.0)?;
+                component.set("right", 0.0)?;
+                component.set("top", 0.0)?;
+                component.set("bottom", 0.0)?;
+                Ok(())
+            })?,
+        )?;
+
+        nine_slice.set(
+            "update",
+            lua.create_function(move |ctx, (entity, component, _dt): (Table, Table, f32)| {
+                if !component.get::<bool>("visible").unwrap_or(true) {
+                    return Ok(());
+                }
+                let (x, y, rotation) = crate::window::get_global_transform(&entity)?;
+                let (w, h) = crate::window::get_global_size(&entity)?;
+                if w <= 0.0 || h <= 0.0 {
+                    return Ok(());
+                }
+                let use_middle_pivot = crate::window::uses_middle_pivot(&entity);
+
+                let tint: Color = color4_to_color(component.get("color")?)?;
+                let image: Option<AnyUserData> = component.get("image")?;
+                let Some(image) = image else {
+                    return Ok(());
+                };
+
+                let image = image.borrow::<crate::assets::ImageHandle>()?;
+                image.ensure_uploaded()?;
+                // src_x/src_y/src_w/src_h select the panel inside an atlas
+                let source = match get_source_rect(&component, "src") {
+                    Some(source) => {
+                        let (image_w, image_h) = image.dimensions()?;
+                        // the panel stretches over the entity anyway, so only the source is cut
+                        let Some(source) =
+                            clamp_source_rect(source, image_w as f32, image_h as f32)
+                        else {
+                            return Ok(());
+                        };
+                        source
+                    }
+                    None => {
+                        let (image_w, image_h) = image.dimensions()?;
+                        Rect {
+                            x: 0.0,
+                            y: 0.0,
+                            w: image_w as f32,
+                            h: image_h as f32,
+                        }
+                    }
+                };
+                let insets = ["left", "right", "top", "bottom"]
+                    .map(|key| get_number_key(&component, key).unwrap_or(0.0));
+                let (draw_x, draw_y, pivot) = if use_middle_pivot {
+                    let (px, py) = crate::window::get_global_rotation_pivot(&entity)?;
+                    (px - w * 0.5, py - h * 0.5, Vec2 { x: px, y: py })
+                } else {
+                    (x, y, Vec2 { x, y })
+                };
+                let dest = Rect {
+                    x: draw_x,
+                    y: draw_y,
+                    w,
+                    h,
+                };
+                let scale = crate::window::get_global_scale(&entity)?;
+
+                let mut renderer = render_state
+                    .lock()
+                    .map_err(|_| mlua::Error::external("render state lock poisoned"))?;
+                // every piece shares the panel's pivot, so a rotated panel stays in one piece
+                for (source, dest) in nine_slice_pieces(source, insets, dest, scale) {
+                    renderer.queue(DrawCommand::Image {
+                        image: image.clone(),
+                        dest,
+                        source: Some(source),
+                        rotation,
+                        pivot,
+                        tint,
+                        filter: app_texture_filter(ctx),
+                    });
+                }
+                Ok(())
+            })?,
+        )?;
+
+        core_components.set("NineSlice2D", nine_slice)?;
+    }
+
+    // Tilemap2D
+    // a grid of tileset cells drawn in one pass; `tiles` is row-major, 0 marks an empty cell
+    {
+        // a 4096x4096 map; far past any tile layer, and keeps setSize from asking for a table
+        // too big to allocate
+        const MAX_TILEMAP_CELLS: i64 = 4096 * 4096;
+
+        let tilemap2d = create_basic_drawable(lua, "Tilemap2D")?;
+        let render_state = render_state.clone();
+        tilemap2d.set(
+            "awake",
+            lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
+                component.set("__neolove_component", "Tilemap2D")?;
+                component.set("color", color4(ctx, 255, 255, 255, 255)?)?;
+                component.set("visible", true)?;
+                component.set("tile_width", 16.0)?;
+                component.set("tile_height", 16.0)?;
+                component.set("columns", 0)?;
+                component.set("rows", 0)?;
+                component.set("tiles", ctx.create_table()?)?;
+                Ok(())
+            })?,
+        )?;
+
+        tilemap2d.set(
+            "setSize",
+            lua.create_function(move |ctx, (component, columns, rows): (Table, i64, i64)| {
+                if columns < 0 || rows < 0 {
+                    return Err(mlua::Error::external(
+                        "Tilemap2D:setSize expects non-negative dimensions",
+                    ));
+                }
+                let cells = columns
+                    .checked_mul(rows)
+                    .filter(|cells| *cells <= MAX_TILEMAP_CELLS)
+                    .ok_or_else(|| {
+                        mlua::Error::external(format!(
+                            "Tilemap2D:setSize {columns}x{rows} is over the {MAX_TILEMAP_CELLS} cell limit"
+                        ))
+                    })?;
+                // keep the overlapping part of the old grid
+                let old_columns = component.get::<i64>("columns").unwrap_or(0).max(0);
+                let old_rows = component.get::<i64>("rows").unwrap_or(0).max(0);
+                let old_tiles: Option<Table> = component.get("tiles").unwrap_or(None);
+                let tiles = ctx.create_table_with_capacity(cells as usize, 0)?;
+                for y in 0..rows {
+                    for x in 0..columns {
+                        let index = match &old_tiles {
+                            Some(old) if x < old_columns && y < old_rows => old
+                                .raw_get::<Option<i64>>(y * old_columns + x + 1)?
+                                .unwrap_or(0),
+                            _ => 0,
+                        };
+                        tiles.raw_set(y * columns + x + 1, index)?;
+                    }
+                }
+                component.set("columns", columns)?;
+                component.set("rows", rows)?;
+                component.set("tiles", tiles)?;
+                Ok(())
+            })?,
+        )?;
+
+        tilemap2d.set(
+            "setTile",
+            lua.create_function(
+                move |_ctx, (component, x, y, index): (Table, i64, i64, i64)| {
+                    let columns = component.get::<i64>("columns").unwrap_or(0);
+                    let rows = component.get::<i64>("rows").unwrap_or(0);
+                    if x < 0 || y < 0 || x >= columns || y >= rows {
+                        return Err(mlua::Error::external(format!(
+                            "tile {x}, {y} is outside the {columns}x{rows} map"
+                        )));
+                    }
+                    let tiles: Table = component.get("tiles")?;
+                    tiles.raw_set(y * columns + x + 1, index.max(0))
+                },
+            )?,
+        )?;
+
+        tilemap2d.set(
+            "getTile",
+            lua.create_function(move |_ctx, (component, x, y): (Table, i64, i64)| {
+                let columns = component.get::<i64>("columns").unwrap_or(0);
+                let rows = component.get::<i64>("rows").unwrap_or(0);
+                if x < 0 || y < 0 || x >= columns || y >= rows {
+                    return Ok(0);
+                }
+                let tiles: Table = component.get("tiles")?;
+                Ok(tiles
+                    .raw_get::<Option<i64>>(y * columns + x + 1)?
+                    .unwrap_or(0))
+            })?,
+        )?;
+
+        tilemap2d.set(
+            "update",
+            lua.create_function(move |ctx, (entity, component, _dt): (Table, Table, f32)| {
+                if !component.get::<bool>("visible").unwrap_or(true) {
+                    return Ok(());
+                }
+                let Some(tileset) = get_image_field(&component, "tileset")? else {
+                    return Ok(());
+                };
+                let tiles: Option<Table> = component.get("tiles").unwrap_or(None);
+                let Some(tiles) = tiles else {
+                    return Ok(());
+                };
+                let tile_w = component.get::<f32>("tile_width").unwrap_or(0.0);
+                let tile_h = component.get::<f32>("tile_height").unwrap_or(0.0);
+                let columns = component.get::<i64>("columns").unwrap_or(0);
+                let rows = component.get::<i64>("rows").unwrap_or(0);
+                if tile_w <= 0.0 || tile_h <= 0.0 || columns <= 0 || rows <= 0 {
+                    return Ok(());
+                }
+                let (image_w, image_h) = tileset.dimensions()?;
+                let tileset_columns = (image_w as f32 / tile_w).floor() as i64;
+                let tileset_rows = (image_h as f32 / tile_h).floor() as i64;
+                if tileset_columns <= 0 || tileset_rows <= 0 {
+                    return Ok(());
+                }
+
+                let (x, y, rotation) = crate::window::get_global_transform(&entity)?;
+                let scale = crate::window::get_global_scale(&entity)?;
+                let (draw_w, draw_h) = (tile_w * scale, tile_h * scale);
+                let tint: Color = color4_to_color(component.get("color")?)?;
+                let filter = app_texture_filter(ctx);
+                let pivot = Vec2 { x, y };
+
+                let mut renderer = render_state
+                    .lock()
+                    .map_err(|_| mlua::Error::external("render state lock poisoned"))?;
+                for row in 0..rows {
+                    for column in 0..columns {
+                        let index = tiles
+                            .raw_get::<Option<i64>>(row * columns + column + 1)?
+                            .unwrap_or(0);
+                        // tileset cells count from 1, left to right then top to bottom
+                        if index <= 0 || index > tileset_columns * tileset_rows {
+                            continue;
+                        }
+                        let cell = index - 1;
+                        renderer.queue(DrawCommand::Image {
+                            image: tileset.clone(),
+                            dest: Rect {
+                                x: x + column as f32 * draw_w,
+                                y: y + row as f32 * draw_h,
+                                w: draw_w,
+                                h: draw_h,
+                            },
+                            source: Some(Rect {
+                                x: (cell % tileset_columns) as f32 * tile_w,
+                                y: (cell / tileset_columns) as f32 * tile_h,
+                                w: tile_w,
+                                h: tile_h,
+                            }),
+                            rotation,
+                            pivot,
+                            tint,
+                            filter,
+                        });
+                    }
+                }
+                Ok(())
+            })?,
+        )?;
+
+        core_components.set("Tilemap2D", tilemap2d)?;
+    }
+
+    // ParticleEmitter2D
+    // spawns particles at the entity's world position; live particles stay in world space
+    {
+        let emitter = create_basic_drawable(lua, "ParticleEmitter2D")?;
+        let render_state = render_state.clone();
+        emitter.set(
+            "awake",
+            lua.create_function(move |ctx, (_entity, component): (Table, Table)| {
+                component.set("__neolove_component", "ParticleEmitter2D")?;
+                component.set("color", color4(ctx, 255, 255, 255, 255)?)?;
+                component.set("visible", true)?;
+                component.set("emitting", true)?;
+                component.set("rate", 20.0)?;
+                component.set("lifetime", 1.0)?;
+                component.set("max_particles", 256)?;
+                component.set("velocity_min_x", -50.0)?;
+                component.set("velocity_max_x", 50.0)?;
+                component.set("velocity_min_y", -100.0)?;
+                component.set("velocity_max_y", -50.0)?;
+                component.set("gravity", 0.0)?;
+                component.set("size", 4.0)?;
+                component.set("start_color", color4(ctx, 255, 255, 255, 255)?)?;
+                component.set("end_color", color4(ctx, 255, 255, 255, 0)?)?;
+                component.set("particles", ctx.create_table()?)?;
+                component.set("__neolove_particle_spawn", 0.0)?;
+                component.set("__neolove_particle_seed", particle_seed())?;
+                Ok(())
+            })?,
+        )?;
+
+        emitter.set(
+            "update",
+            lua.create_function(move |ctx, (entity, component, dt): (Table, Table, f32)| {
+                let dt = if dt.is_finite() { dt.max(0.0) } else { 0.0 };
+                let lifetime = component.get::<f32>("lifetime").unwrap_or(1.0);
+                let gravity = component.get::<f32>("gravity").unwrap_or(0.0);
+                let old_particles: Option<Table> = component.get("particles").unwrap_or(None);
+
+                let particles = ctx.create_table()?;
+                let mut count = 0;
+                if let Some(old_particles) = old_particles {
+                    for particle in old_particles.sequence_values::<Table>() {
+                        let particle = particle?;
+                        let age = particle.get::<f32>("age")? + dt;
+                        if age >= lifetime {
+                            continue;
+                        }
+                        let vx = particle.get::<f32>("vx")?;
+                        let vy = particle.get::<f32>("vy")? + gravity * dt;
+                        particle.set("x", particle.get::<f32>("x")? + vx * dt)?;
+                        particle.set("y", particle.get::<f32>("y")? + vy * dt)?;
+                        particle.set("vy", vy)?;
+                        particle.set("age", age)?;
+                        count += 1;
+                        particles.raw_set(count, particle)?;
+                    }
+                }
+
+                let max_particles = component.get::<i64>("max_particles").unwrap_or(256);
+                let mut pending = component
+                    .get::<f32>("__neolove_particle_spawn")
+                    .unwrap_or(0.0);
+                if component.get::<bool>("emitting").unwrap_or(true) && lifetime > 0.0 {
+                    let rate = component.get::<f32>("rate").unwrap_or(0.0);
+                    if rate.is_finite() && rate > 0.0 {
+                        pending += rate * dt;
+                    }
+                    let (x, y, _) = crate::window::get_global_transform(&entity)?;
+                    let min_x = component.get::<f32>("velocity_min_x").unwrap_or(0.0);
+                    let max_x = component.get::<f32>("velocity_max_x").unwrap_or(0.0);
+                    let min_y = component.get::<f32>("velocity_min_y").unwrap_or(0.0);
+                    let max_y = component.get::<f32>("velocity_max_y").unwrap_or(0.0);
+                    let mut seed = component
+                        .get::<u32>("__neolove_particle_seed")
+                        .unwrap_or(1)
+                        .max(1);
+                    while pending >= 1.0 && (count as i64) < max_particles {
+                        pending -= 1.0;
+                        let particle = ctx.create_table()?;
+                        particle.set("x", x)?;
+                        particle.set("y", y)?;
+                        particle.set(
+                            "vx",
+                            min_x + (max_x - min_x) * next_particle_random(&mut seed),
+                        )?;
+                        particle.set(
+                            "vy",
+                            min_y + (max_y - min_y) * next_particle_random(&mut seed),
+                        )?;
+                        particle.set("age", 0.0)?;
+                        count += 1;
+                        particles.raw_set(count, particle)?;
+                    }
+                    component.set("__neolove_particle_seed", seed)?;
+                }
+                // a full pool drops the backlog instead of bursting once particles expire
+                component.set("__neolove_particle_spawn", pending.min(1.0))?;
+                component.set("particles", particles.clone())?;
+
+                if !component.get::<bool>("visible").unwrap_or(true) || count == 0 {
+                    return Ok(());
+                }
+                let size = component.get::<f32>("size").unwrap_or(4.0).max(0.0);
+                let start_color =
+                    get_color_field(&component, "start_color").unwrap_or(Color::WHITE);
+                let end_color = get_color_field(&component, "end_color").unwrap_or(start_color);
+                let texture = get_image_field(&component, "texture")?;
+                let filter = app_texture_filter(ctx);
+
+                let mut renderer = render_state
+                    .lock()
+                    .map_err(|_| mlua::Error::external("render state lock poisoned"))?;
+                for particle in particles.sequence_values::<Table>() {
+                    let particle = particle?;
+                    let (x, y) = (particle.get::<f32>("x")?, particle.get::<f32>("y")?);
+                    let t = (particle.get::<f32>("age")? / lifetime).clamp(0.0, 1.0);
+                    let color = lerp_color(start_color, end_color, t);
+                    match &texture {
+                        Some(image) => renderer.queue(DrawCommand::Image {
+                            image: image.clone(),
+                            dest: Rect {
+                                x: x - size * 0.5,
+                                y: y - size * 0.5,
+                                w: size,
+                                h: size,
+                            },
+                            source: None,
+                            rotation: 0.0,
+                            pivot: Vec2 { x, y },
+                            tint: color,
+                            filter,
+                        }),
+                        None => renderer.queue(DrawCommand::Rect {
+                            x,
+                            y,
+                            w: size,
+                            h: size,
+                            rotation: 0.0,
+                            offset: Vec2 { x: 0.5, y: 0.5 },
+                            color,
+                        }),
+                    }
+                }
+                Ok(())
+            })?,
+        )?;
+
+        core_components.set("ParticleEmitter2D", emitter)?;
+    }
+
     // Collider2D
     // axis-aligned collider used by Rigidbody2D collision solver
     {
@@ -0,0 +1,365 @@
+use crate::assets::{ImageHandle, color4_table_to_color};
+use crate::core::app_texture_filter;
+use crate::platform::Color;
+use crate::renderer::{
+    DrawCommand, FontHandle, Rect, SharedRenderState, TextAlignX, TextAlignY, TextRenderRequest,
+    TextScaleMode, TextWrapMode, Vec2,
+};
+use mlua::{AnyUserData, Lua, Table};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+// immediate-mode shapes go to the overlay: on top of every entity, in screen space
+fn queue(render_state: &SharedRenderState, command: DrawCommand) -> mlua::Result<()> {
+    queue_all(render_state, vec![command])
+}
+
+fn queue_all(render_state: &SharedRenderState, commands: Vec<DrawCommand>) -> mlua::Result<()> {
+    render_state
+        .lock()
+        .map_err(|_| mlua::Error::external("render state lock poisoned"))?
+        .extend_overlay(commands);
+    Ok(())
+}
+
+// triangles from `center` covering the arc between two angles (radians, clockwise on screen)
+fn arc_fan(
+    center: Vec2,
+    radius: f32,
+    start: f32,
+    end: f32,
+    segments: u32,
+    color: Color,
+) -> impl Iterator<Item = DrawCommand> {
+    let point = move |angle: f32| Vec2 {
+        x: center.x + angle.cos() * radius,
+        y: center.y + angle.sin() * radius,
+    };
+    (0..segments).map(move |index| {
+        let step = (end - start) / segments as f32;
+        DrawCommand::Triangle {
+            a: center,
+            b: point(start + step * index as f32),
+            c: point(start + step * (index + 1) as f32),
+            color,
+        }
+    })
+}
+
+// enough segments per quarter turn that the edge stays smooth at this radius
+fn quarter_segments(radius: f32) -> u32 {
+    ((radius * FRAC_PI_2 / 4.0).ceil() as u32).clamp(6, 32)
+}
+
+// the straight parts as three side-by-side rects and the corners as quarter fans, so no
+// pixel is covered twice and translucent colors blend evenly
+fn rounded_rect_commands(rect: Rect, radius: f32, color: Color) -> Vec<DrawCommand> {
+    let Rect { x, y, w, h } = rect;
+    let radius = radius.clamp(0.0, w.min(h) * 0.5);
+    let rect = |x, y, w, h| DrawCommand::Rect {
+        x,
+        y,
+        w,
+        h,
+        rotation: 0.0,
+        offset: Vec2::default(),
+        color,
+    };
+    let mut commands = vec![rect(x + radius, y, w - radius * 2.0, h)];
+    if radius <= 0.0 {
+        return commands;
+    }
+    commands.push(rect(x, y + radius, radius, h - radius * 2.0));
+    commands.push(rect(x + w - radius, y + radius, radius, h - radius * 2.0));
+
+    let segments = quarter_segments(radius);
+    let corners = [
+        (x + w - radius, y + h - radius, 0.0),
+        (x + radius, y + h - radius, FRAC_PI_2),
+        (x + radius, y + radius, PI),
+        (x + w - radius, y + radius, PI + FRAC_PI_2),
+    ];
+    for (cx, cy, start) in corners {
+        commands.extend(arc_fan(
+            Vec2 { x: cx, y: cy },
+            radius,
+            start,
+            start + FRAC_PI_2,
+            segments,
+            color,
+        ));
+    }
+    commands
+}
+
+fn color_or_white(color: Option<Table>) -> mlua::Result<Color> {
+    color.map_or(Ok(Color::WHITE), color4_table_to_color)
+}
+
+fn ensure_finite(name: &str, values: &[f32]) -> mlua::Result<()> {
+    if values.iter().all(|value| value.is_finite()) {
+        Ok(())
+    } else {
+        Err(mlua::Error::external(format!(
+            "draw.{name} expects finite numbers"
+        )))
+    }
+}
+
+pub(crate) fn add_draw_module(lua: &Lua, render_state: SharedRenderState) -> mlua::Result<()> {
+    let module = lua.create_table()?;
+
+    {
+        let render_state = render_state.clone();
+        module.set(
+            "rect",
+            lua.create_function(
+                move |_lua, (x, y, w, h, color): (f32, f32, f32, f32, Option<Table>)| {
+                    ensure_finite("rect", &[x, y, w, h])?;
+                    queue(
+                        &render_state,
+                        DrawCommand::Rect {
+                            x,
+                            y,
+                            w,
+                            h,
+                            rotation: 0.0,
+                            offset: Vec2::default(),
+                            color: color_or_white(color)?,
+                        },
+                    )
+                },
+            )?,
+        )?;
+    }
+
+    {
+        let render_state = render_state.clone();
+        module.set(
+            "line",
+            lua.create_function(
+                move |_lua,
+                      (x1, y1, x2, y2, thickness, color): (
+                    f32,
+                    f32,
+                    f32,
+                    f32,
+                    Option<f32>,
+                    Option<Table>,
+                )| {
+                    let thickness = thickness.unwrap_or(1.0);
+                    ensure_finite("line", &[x1, y1, x2, y2, thickness])?;
+                    let (dx, dy) = (x2 - x1, y2 - y1);
+                    // a rect as long as the line, rotated around its start point
+                    queue(
+                        &render_state,
+                        DrawCommand::Rect {
+                            x: x1,
+                            y: y1 - thickness * 0.5,
+                            w: (dx * dx + dy * dy).sqrt(),
+                            h: thickness,
+                            rotation: dy.atan2(dx),
+                            offset: Vec2 { x: 0.0, y: 0.5 },
+                            color: color_or_white(color)?,
+                        },
+                    )
+                },
+            )?,
+        )?;
+    }
+
+    {
+        let render_state = render_state.clone();
+        module.set(
+            "circle",
+            lua.create_function(
+                move |_lua,
+                      (x, y, radius, color, segments): (
+                    f32,
+                    f32,
+                    f32,
+                    Option<Table>,
+                    Option<u32>,
+                )| {
+                    ensure_finite("circle", &[x, y, radius])?;
+                    let center = Vec2 { x, y };
+                    let radius = radius.max(0.0);
+                    let color = color_or_white(color)?;
+                    match segments {
+                        // an explicit polygon, for large circles or deliberately faceted shapes
+                        Some(segments) => {
+                            if !(3..=1024).contains(&segments) {
+                                return Err(mlua::Error::external(
+                                    "draw.circle expects between 3 and 1024 segments",
+                                ));
+                            }
+                            let fan = arc_fan(center, radius, 0.0, TAU, segments, color);
+                            queue_all(&render_state, fan.collect())
+                        }
+                        None => queue(
+                            &render_state,
+                            DrawCommand::Circle {
+                                center,
+                                radius,
+                                color,
+                            },
+                        ),
+                    }
+                },
+            )?,
+        )?;
+    }
+
+    {
+        let render_state = render_state.clone();
+        module.set(
+            "roundedRect",
+            lua.create_function(
+                move |_lua,
+                      (x, y, w, h, radius, color): (
+                    f32,
+                    f32,
+                    f32,
+                    f32,
+                    f32,
+                    Option<Table>,
+                )| {
+                    ensure_finite("roundedRect", &[x, y, w, h, radius])?;
+                    if w <= 0.0 || h <= 0.0 {
+                        return Ok(());
+                    }
+                    queue_all(
+                        &render_state,
+                        rounded_rect_commands(Rect { x, y, w, h }, radius, color_or_white(color)?),
+                    )
+                },
+            )?,
+        )?;
+    }
+
+    {
+        let render_state = render_state.clone();
+        module.set(
+            "text",
+            lua.create_function(
+                move |_lua,
+                      (text, x, y, size, color): (
+                    String,
+                    f32,
+                    f32,
+                    Option<f32>,
+                    Option<Table>,
+                )| {
+                    let size = size.unwrap_or(24.0);
+                    ensure_finite("text", &[x, y, size])?;
+                    queue(
+                        &render_state,
+                        DrawCommand::Text(TextRenderRequest {
+                            text,
+                            bounds: Rect { x, y, w: 0.0, h: 0.0 },
+                            rotation: 0.0,
+                            pivot: Vec2 { x, y },
+                            color: color_or_white(color)?,
+                            font: FontHandle::Default,
+                            scale: size.max(1.0),
+                            min_scale: 1.0,
+                            text_scale: TextScaleMode::None,
+                            align_x: TextAlignX::Left,
+                            align_y: TextAlignY::Top,
+                            wrap: TextWrapMode::None,
+                            padding_x: 0.0,
+                            padding_y: 0.0,
+                            line_spacing: 1.0,
+                            letter_spacing: 0.0,
+                            stretch_width: 0.0,
+                            stretch_height: 0.0,
+                        }),
+                    )
+                },
+            )?,
+        )?;
+    }
+
+    {
+        let render_state = render_state.clone();
+        module.set(
+            "texture",
+            lua.create_function(
+                move |lua,
+                      (image, x, y, w, h, tint): (
+                    AnyUserData,
+                    f32,
+                    f32,
+                    Option<f32>,
+                    Option<f32>,
+                    Option<Table>,
+                )| {
+                    let image = image.borrow::<ImageHandle>()?.clone();
+                    let (width, height) = image.dimensions()?;
+                    let w = w.unwrap_or(width as f32);
+                    let h = h.unwrap_or(height as f32);
+                    ensure_finite("texture", &[x, y, w, h])?;
+                    queue(
+                        &render_state,
+                        DrawCommand::Image {
+                            image,
+                            dest: Rect { x, y, w, h },
+                            source: None,
+                            rotation: 0.0,
+                            pivot: Vec2 { x, y },
+                            tint: color_or_white(tint)?,
+                            filter: app_texture_filter(lua),
+                        },
+                    )
+                },
+            )?,
+        )?;
+    }
+
+    lua.globals().set("draw", module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{drain_commands, new_shared_render_state};
+
+    fn covered_area(commands: &[DrawCommand]) -> f32 {
+        commands
+            .iter()
+            .map(|command| match command {
+                DrawCommand::Rect { w, h, .. } => w * h,
+                DrawCommand::Triangle { a, b, c, .. } => {
+                    ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs() * 0.5
+                }
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn rounded_rects_and_segmented_circles_cover_their_shape() -> mlua::Result<()> {
+        let lua = Lua::new();
+        let render_state = new_shared_render_state();
+        add_draw_module(&lua, render_state.clone())?;
+
+        lua.load("draw.roundedRect(10, 10, 100, 40, 8)").exec()?;
+        let rounded = drain_commands(&render_state).map_err(mlua::Error::external)?;
+        // the four corners together are one circle of the radius cut out of the square corners
+        let expected = 100.0 * 40.0 - (4.0 - PI) * 64.0;
+        assert!((covered_area(&rounded) - expected).abs() < 3.0);
+
+        // the radius is capped at half the shorter side
+        lua.load("draw.roundedRect(0, 0, 20, 10, 50)").exec()?;
+        let pill = drain_commands(&render_state).map_err(mlua::Error::external)?;
+        let expected = 10.0 * 10.0 + PI * 25.0;
+        assert!((covered_area(&pill) - expected).abs() < 1.5);
+
+        lua.load("draw.circle(0, 0, 10, nil, 64)").exec()?;
+        let circle = drain_commands(&render_state).map_err(mlua::Error::external)?;
+        assert_eq!(circle.len(), 64);
+        assert!((covered_area(&circle) - PI * 100.0).abs() < 1.0);
+        assert!(lua.load("draw.circle(0, 0, 10, nil, 2)").exec().is_err());
+        Ok(())
+    }
+}
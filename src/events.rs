@@ -0,0 +1,115 @@
+use crate::lua_error::protect_lua_call;
+use mlua::{Function, Lua, MultiValue, RegistryKey};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// handlers per event name, in the order they subscribed
+pub(crate) type SharedEventHandlers = Rc<RefCell<HashMap<String, Vec<RegistryKey>>>>;
+
+pub(crate) fn new_shared_event_handlers() -> SharedEventHandlers {
+    Rc::new(RefCell::new(HashMap::new()))
+}
+
+pub(crate) fn add_events_module(lua: &Lua, handlers: SharedEventHandlers) -> mlua::Result<()> {
+    let module = lua.create_table()?;
+
+    {
+        let handlers = handlers.clone();
+        module.set(
+            "on",
+            lua.create_function(move |lua, (name, callback): (String, Function)| {
+                let key = lua.create_registry_value(callback)?;
+                handlers.borrow_mut().entry(name).or_default().push(key);
+                Ok(())
+            })?,
+        )?;
+    }
+
+    {
+        let handlers = handlers.clone();
+        module.set(
+            "off",
+            lua.create_function(move |lua, (name, callback): (String, Function)| {
+                let mut handlers = handlers.borrow_mut();
+                let Some(keys) = handlers.get_mut(&name) else {
+                    return Ok(false);
+                };
+                let before = keys.len();
+                keys.retain(|key| {
+                    !lua.registry_value::<Function>(key)
+                        .is_ok_and(|handler| handler == callback)
+                });
+                let removed = keys.len() != before;
+                if keys.is_empty() {
+                    handlers.remove(&name);
+                }
+                Ok(removed)
+            })?,
+        )?;
+    }
+
+    {
+        let handlers = handlers.clone();
+        module.set(
+            "emit",
+            lua.create_function(move |lua, (name, args): (String, MultiValue)| {
+                // resolved up front so handlers can subscribe or unsubscribe while it runs;
+                // changes apply from the next emit
+                let callbacks = match handlers.borrow().get(&name) {
+                    Some(keys) => keys
+                        .iter()
+                        .map(|key| lua.registry_value::<Function>(key))
+                        .collect::<mlua::Result<Vec<_>>>()?,
+                    None => Vec::new(),
+                };
+                for callback in &callbacks {
+                    protect_lua_call(&format!("running '{name}' event handler"), || {
+                        callback.call::<()>(args.clone())
+                    })?;
+                }
+                Ok(callbacks.len())
+            })?,
+        )?;
+    }
+
+    lua.globals().set("events", module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handlers_run_in_subscription_order_until_removed() -> mlua::Result<()> {
+        let lua = Lua::new();
+        add_events_module(&lua, new_shared_event_handlers())?;
+
+        let (log, first, second, removed, missing): (String, usize, usize, bool, bool) = lua
+            .load(
+                r#"
+                local log = {}
+                local function scored(points, by)
+                    table.insert(log, "a" .. points .. by)
+                end
+                events.on("scored", scored)
+                events.on("scored", function(points)
+                    table.insert(log, "b" .. points)
+                end)
+                local first = events.emit("scored", 10, "ada")
+                local removed = events.off("scored", scored)
+                local second = events.emit("scored", 5)
+                return table.concat(log, ","), first, second, removed, events.off("nothing", scored)
+                "#,
+            )
+            .eval()?;
+        assert_eq!(log, "a10ada,b10,b5");
+        assert_eq!((first, second, removed, missing), (2, 1, true, false));
+        assert_eq!(
+            lua.load("return events.emit('unheard')").eval::<usize>()?,
+            0
+        );
+        Ok(())
+    }
+}
@@ -1,17 +1,18 @@
 use crate::assets::ImageHandle;
 use crate::platform::{Color, SharedPlatformState};
-use crate::renderer::{self, DrawCommand, Rect, SharedRenderState, TextureFilter, Vec2};
+use crate::renderer::{self, BlendMode, DrawCommand, Rect, SharedRenderState, TextureFilter, Vec2};
 use bytemuck::{Pod, Zeroable};
 use image::RgbaImage;
 use std::collections::HashMap;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
-use vulkano::buffer::{Buffer, BufferCreateInfo, BufferUsage};
+use vulkano::buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer};
 use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
 use vulkano::command_buffer::{
-    AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer, RenderPassBeginInfo,
-    SubpassBeginInfo, SubpassContents, SubpassEndInfo,
+    AutoCommandBufferBuilder, CommandBufferUsage, CopyImageInfo, CopyImageToBufferInfo,
+    PrimaryAutoCommandBuffer, RenderPassBeginInfo, SubpassBeginInfo, SubpassContents,
+    SubpassEndInfo,
 };
 use vulkano::descriptor_set::allocator::StandardDescriptorSetAllocator;
 use vulkano::descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet};
@@ -22,7 +23,8 @@ use vulkano::image::view::ImageView;
 use vulkano::image::{Image, ImageCreateInfo, ImageLayout, ImageUsage, SampleCount, SampleCounts};
 use vulkano::memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator};
 use vulkano::pipeline::graphics::color_blend::{
-    AttachmentBlend, ColorBlendAttachmentState, ColorBlendState, ColorComponents,
+    AttachmentBlend, BlendFactor, BlendOp, ColorBlendAttachmentState, ColorBlendState,
+    ColorComponents,
 };
 use vulkano::pipeline::graphics::input_assembly::{InputAssemblyState, PrimitiveTopology};
 use vulkano::pipeline::graphics::multisample::MultisampleState;
@@ -101,9 +103,55 @@ struct TextureKey(u64);
 struct TextureBatch {
     texture: TextureKey,
     filter: TextureFilter,
+    blend: BlendMode,
     vertices: Vec<GpuVertex>,
+    // clip-space [min_x, min_y, max_x, max_y] covering every vertex in the batch
+    bounds: [f32; 4],
 }
 
+struct BlendPipelines {
+    alpha: Arc<GraphicsPipeline>,
+    additive: Arc<GraphicsPipeline>,
+    replace: Arc<GraphicsPipeline>,
+    premultiplied: Arc<GraphicsPipeline>,
+}
+
+impl BlendPipelines {
+    fn get(&self, mode: BlendMode) -> &Arc<GraphicsPipeline> {
+        match mode {
+            BlendMode::Alpha => &self.alpha,
+            BlendMode::Additive => &self.additive,
+            BlendMode::None => &self.replace,
+            BlendMode::Premultiplied => &self.premultiplied,
+        }
+    }
+}
+
+// the render passes used while app.setClearEnabled(false) is in effect; both keep what they
+// draw for the next frame, `start` clears first and `keep` draws over the last frame
+struct KeepPasses {
+    start: Arc<RenderPass>,
+    keep: Arc<RenderPass>,
+}
+
+// without msaa the swapchain image is the only attachment, and the swapchain hands its
+// images back in no particular order, so frames with clearing off draw here instead and are
+// copied over
+struct Canvas {
+    image: Arc<Image>,
+    framebuffer: Arc<Framebuffer>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FramePass {
+    Clear,
+    StartKeep,
+    Keep,
+}
+
+// how many recent batches a draw may skip back over to join one with the same texture
+const BATCH_LOOKBACK: usize = 16;
+
 struct CachedTexture {
     revision: u64,
     descriptor_nearest: Arc<PersistentDescriptorSet>,
@@ -120,9 +168,16 @@ pub(crate) struct VulkanPresenter {
     descriptor_set_allocator: StandardDescriptorSetAllocator,
     previous_frame_end: Option<Box<dyn GpuFuture>>,
     render_pass: Arc<RenderPass>,
+    // None when the swapchain images can't be copied into, which the canvas needs
+    keep_passes: Option<KeepPasses>,
     framebuffers: Vec<Arc<Framebuffer>>,
-    pipeline: Arc<GraphicsPipeline>,
+    canvas: Option<Canvas>,
+    // whether the msaa image or canvas still holds the last frame for a keep pass to load
+    canvas_holds_last_frame: bool,
+    pipelines: BlendPipelines,
     recreate_swapchain: bool,
+    supported_present_modes: Vec<PresentMode>,
+    present_mode: PresentMode,
     nearest_sampler: Arc<Sampler>,
     linear_sampler: Arc<Sampler>,
     msaa_samples: SampleCount,
@@ -137,6 +192,8 @@ impl VulkanPresenter {
     pub(crate) fn new(
         event_loop: &EventLoop<()>,
         window: Arc<Window>,
+        vsync: bool,
+        msaa: u32,
     ) -> Result<(Self, Arc<Surface>), String> {
         let library = VulkanLibrary::new().map_err(|e| e.to_string())?;
         let instance = vulkano::instance::Instance::new(
@@ -184,21 +241,17 @@ impl VulkanPresenter {
             })
             .ok_or_else(|| "no suitable Vulkan physical device found".to_string())?;
 
-        let msaa_samples = if physical
-            .properties()
-            .framebuffer_color_sample_counts
-            .intersects(SampleCounts::SAMPLE_4)
-        {
-            SampleCount::Sample4
-        } else if physical
-            .properties()
-            .framebuffer_color_sample_counts
-            .intersects(SampleCounts::SAMPLE_2)
-        {
-            SampleCount::Sample2
-        } else {
-            SampleCount::Sample1
-        };
+        // the largest count the device supports without going over the requested one
+        let supported_samples = physical.properties().framebuffer_color_sample_counts;
+        let msaa_samples = [
+            (16, SampleCounts::SAMPLE_16, SampleCount::Sample16),
+            (8, SampleCounts::SAMPLE_8, SampleCount::Sample8),
+            (4, SampleCounts::SAMPLE_4, SampleCount::Sample4),
+            (2, SampleCounts::SAMPLE_2, SampleCount::Sample2),
+        ]
+        .into_iter()
+        .find(|(count, flag, _)| *count <= msaa && supported_samples.intersects(*flag))
+        .map_or(SampleCount::Sample1, |(_, _, samples)| samples);
 
         let (device, mut queues) = Device::new(
             physical.clone(),
@@ -240,8 +293,9 @@ impl VulkanPresenter {
             .max_image_count
             .map(|limit| limit.min(surface_caps.min_image_count.max(2)))
             .unwrap_or(surface_caps.min_image_count.max(2));
+        // TRANSFER_SRC lets screenshots copy the presented image back to the cpu
         let image_usage = surface_caps.supported_usage_flags
-            & (ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_DST);
+            & (ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_DST | ImageUsage::TRANSFER_SRC);
         if !image_usage.intersects(ImageUsage::COLOR_ATTACHMENT) {
             return Err(format!(
                 "surface does not support color-attachment swapchain images; supported usage flags: {:?}",
@@ -253,11 +307,7 @@ impl VulkanPresenter {
         } else {
             SampleCount::Sample1
         };
-        let present_mode = if present_modes.contains(&PresentMode::Immediate) {
-            PresentMode::Immediate
-        } else {
-            PresentMode::Fifo
-        };
+        let present_mode = choose_present_mode(&present_modes, vsync);
 
         let (swapchain, images) = Swapchain::new(
             device.clone(),
@@ -290,6 +340,15 @@ impl VulkanPresenter {
             StandardDescriptorSetAllocator::new(device.clone(), Default::default());
         let render_pass =
             Self::create_render_pass(device.clone(), swapchain.image_format(), msaa_samples)?;
+        let keep_passes = if image_usage.intersects(ImageUsage::TRANSFER_DST) {
+            Some(Self::create_keep_passes(
+                device.clone(),
+                swapchain.image_format(),
+                msaa_samples,
+            )?)
+        } else {
+            None
+        };
         let framebuffers = Self::create_framebuffers(
             &images,
             render_pass.clone(),
@@ -297,7 +356,14 @@ impl VulkanPresenter {
             swapchain.image_format(),
             msaa_samples,
         )?;
-        let pipeline = Self::create_pipeline(
+        let canvas = Self::create_canvas(
+            &images,
+            keep_passes.as_ref(),
+            memory_allocator.clone(),
+            swapchain.image_format(),
+            msaa_samples,
+        )?;
+        let pipelines = Self::create_pipelines(
             device.clone(),
             render_pass.clone(),
             size.width,
@@ -336,9 +402,14 @@ impl VulkanPresenter {
             descriptor_set_allocator,
             previous_frame_end: Some(sync::now(device).boxed()),
             render_pass,
+            keep_passes,
             framebuffers,
-            pipeline,
+            canvas,
+            canvas_holds_last_frame: false,
+            pipelines,
             recreate_swapchain: false,
+            supported_present_modes: present_modes,
+            present_mode,
             nearest_sampler,
             linear_sampler,
             msaa_samples,
@@ -404,6 +475,66 @@ impl VulkanPresenter {
         .map_err(|e| e.to_string())
     }
 
+    // same layouts as the passes above, so framebuffers and pipelines work with all of them;
+    // without msaa they draw into the canvas rather than the swapchain image
+    fn create_keep_passes(
+        device: Arc<Device>,
+        image_format: Format,
+        msaa_samples: SampleCount,
+    ) -> Result<KeepPasses, String> {
+        macro_rules! keep_pass {
+            ($load_op:ident) => {
+                if msaa_samples == SampleCount::Sample1 {
+                    single_pass_renderpass!(
+                        device.clone(),
+                        attachments: {
+                            canvas: {
+                                format: image_format,
+                                samples: 1,
+                                load_op: $load_op,
+                                store_op: Store,
+                            }
+                        },
+                        pass: {
+                            color: [canvas],
+                            depth_stencil: {}
+                        }
+                    )
+                } else {
+                    single_pass_renderpass!(
+                        device.clone(),
+                        attachments: {
+                            color_msaa: {
+                                format: image_format,
+                                samples: u32::from(msaa_samples),
+                                load_op: $load_op,
+                                store_op: Store,
+                            },
+                            color_resolve: {
+                                format: image_format,
+                                samples: 1,
+                                load_op: DontCare,
+                                store_op: Store,
+                                final_layout: ImageLayout::PresentSrc,
+                            }
+                        },
+                        pass: {
+                            color: [color_msaa],
+                            color_resolve: [color_resolve],
+                            depth_stencil: {}
+                        }
+                    )
+                }
+                .map_err(|e| e.to_string())?
+            };
+        }
+
+        Ok(KeepPasses {
+            start: keep_pass!(Clear),
+            keep: keep_pass!(Load),
+        })
+    }
+
     fn create_framebuffers(
         images: &[Arc<Image>],
         render_pass: Arc<RenderPass>,
@@ -411,32 +542,37 @@ impl VulkanPresenter {
         image_format: Format,
         msaa_samples: SampleCount,
     ) -> Result<Vec<Arc<Framebuffer>>, String> {
+        // one msaa image shared by every framebuffer: frames are drawn one after another, and
+        // sharing it is what lets a frame with clearing off build on the previous one
+        let msaa_view = match images.first() {
+            Some(image) if msaa_samples != SampleCount::Sample1 => {
+                let msaa_image = Image::new(
+                    memory_allocator,
+                    ImageCreateInfo {
+                        format: image_format,
+                        extent: image.extent(),
+                        usage: ImageUsage::COLOR_ATTACHMENT,
+                        samples: msaa_samples,
+                        ..Default::default()
+                    },
+                    AllocationCreateInfo {
+                        memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                        ..Default::default()
+                    },
+                )
+                .map_err(|e| e.to_string())?;
+                Some(ImageView::new_default(msaa_image).map_err(|e| e.to_string())?)
+            }
+            _ => None,
+        };
         images
             .iter()
             .map(|image| {
                 let swapchain_view =
                     ImageView::new_default(image.clone()).map_err(|e| e.to_string())?;
-                let attachments = if msaa_samples == SampleCount::Sample1 {
-                    vec![swapchain_view]
-                } else {
-                    let msaa_image = Image::new(
-                        memory_allocator.clone(),
-                        ImageCreateInfo {
-                            format: image_format,
-                            extent: image.extent(),
-                            usage: ImageUsage::COLOR_ATTACHMENT,
-                            samples: msaa_samples,
-                            ..Default::default()
-                        },
-                        AllocationCreateInfo {
-                            memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
-                            ..Default::default()
-                        },
-                    )
-                    .map_err(|e| e.to_string())?;
-                    let msaa_view =
-                        ImageView::new_default(msaa_image).map_err(|e| e.to_string())?;
-                    vec![msaa_view, swapchain_view]
+                let attachments = match &msaa_view {
+                    Some(msaa_view) => vec![msaa_view.clone(), swapchain_view],
+                    None => vec![swapchain_view],
                 };
 
                 Framebuffer::new(
@@ -451,13 +587,58 @@ impl VulkanPresenter {
             .collect()
     }
 
-    fn create_pipeline(
+    // only needed without msaa; with it the shared msaa image already outlives each frame
+    fn create_canvas(
+        images: &[Arc<Image>],
+        keep_passes: Option<&KeepPasses>,
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        image_format: Format,
+        msaa_samples: SampleCount,
+    ) -> Result<Option<Canvas>, String> {
+        let (Some(image), Some(keep_passes)) = (images.first(), keep_passes) else {
+            return Ok(None);
+        };
+        if msaa_samples != SampleCount::Sample1 {
+            return Ok(None);
+        }
+        let canvas_image = Image::new(
+            memory_allocator,
+            ImageCreateInfo {
+                format: image_format,
+                extent: image.extent(),
+                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| e.to_string())?;
+        let framebuffer = Framebuffer::new(
+            keep_passes.keep.clone(),
+            FramebufferCreateInfo {
+                attachments: vec![
+                    ImageView::new_default(canvas_image.clone()).map_err(|e| e.to_string())?,
+                ],
+                ..Default::default()
+            },
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(Some(Canvas {
+            image: canvas_image,
+            framebuffer,
+        }))
+    }
+
+    // one pipeline per BlendMode, all sharing a layout so descriptor sets work with each
+    fn create_pipelines(
         device: Arc<Device>,
         render_pass: Arc<RenderPass>,
         width: u32,
         height: u32,
         msaa_samples: SampleCount,
-    ) -> Result<Arc<GraphicsPipeline>, String> {
+    ) -> Result<BlendPipelines, String> {
         let vs = vs::load(device.clone()).map_err(|e| e.to_string())?;
         let fs = fs::load(device.clone()).map_err(|e| e.to_string())?;
         let vs_entry = vs
@@ -483,44 +664,69 @@ impl VulkanPresenter {
         let subpass = Subpass::from(render_pass.clone(), 0)
             .ok_or_else(|| "missing render subpass".to_string())?;
 
-        GraphicsPipeline::new(
-            device,
-            None,
-            vulkano::pipeline::graphics::GraphicsPipelineCreateInfo {
-                stages: stages.into_iter().collect(),
-                vertex_input_state: Some(vertex_input_state),
-                input_assembly_state: Some(InputAssemblyState {
-                    topology: PrimitiveTopology::TriangleList,
-                    ..Default::default()
-                }),
-                viewport_state: Some({
-                    let mut state = ViewportState::default();
-                    state.viewports[0] = Viewport {
-                        offset: [0.0, 0.0],
-                        extent: [width.max(1) as f32, height.max(1) as f32],
-                        depth_range: 0.0..=1.0,
-                    };
-                    state
-                }),
-                rasterization_state: Some(RasterizationState::default()),
-                multisample_state: Some(MultisampleState {
-                    rasterization_samples: msaa_samples,
-                    ..Default::default()
-                }),
-                color_blend_state: Some(ColorBlendState::with_attachment_states(
-                    1,
-                    ColorBlendAttachmentState {
-                        blend: Some(AttachmentBlend::alpha()),
-                        color_write_mask: ColorComponents::all(),
-                        color_write_enable: true,
-                    },
-                )),
-                dynamic_state: [DynamicState::Viewport].into_iter().collect(),
-                subpass: Some(PipelineSubpassType::BeginRenderPass(subpass)),
-                ..vulkano::pipeline::graphics::GraphicsPipelineCreateInfo::layout(layout)
-            },
-        )
-        .map_err(|e| e.to_string())
+        let create = |blend: Option<AttachmentBlend>| {
+            GraphicsPipeline::new(
+                device.clone(),
+                None,
+                vulkano::pipeline::graphics::GraphicsPipelineCreateInfo {
+                    stages: stages.iter().cloned().collect(),
+                    vertex_input_state: Some(vertex_input_state.clone()),
+                    input_assembly_state: Some(InputAssemblyState {
+                        topology: PrimitiveTopology::TriangleList,
+                        ..Default::default()
+                    }),
+                    viewport_state: Some({
+                        let mut state = ViewportState::default();
+                        state.viewports[0] = Viewport {
+                            offset: [0.0, 0.0],
+                            extent: [width.max(1) as f32, height.max(1) as f32],
+                            depth_range: 0.0..=1.0,
+                        };
+                        state
+                    }),
+                    rasterization_state: Some(RasterizationState::default()),
+                    multisample_state: Some(MultisampleState {
+                        rasterization_samples: msaa_samples,
+                        ..Default::default()
+                    }),
+                    color_blend_state: Some(ColorBlendState::with_attachment_states(
+                        1,
+                        ColorBlendAttachmentState {
+                            blend,
+                            color_write_mask: ColorComponents::all(),
+                            color_write_enable: true,
+                        },
+                    )),
+                    dynamic_state: [DynamicState::Viewport].into_iter().collect(),
+                    subpass: Some(PipelineSubpassType::BeginRenderPass(subpass.clone())),
+                    ..vulkano::pipeline::graphics::GraphicsPipelineCreateInfo::layout(
+                        layout.clone(),
+                    )
+                },
+            )
+            .map_err(|e| e.to_string())
+        };
+
+        Ok(BlendPipelines {
+            alpha: create(Some(AttachmentBlend::alpha()))?,
+            additive: create(Some(AttachmentBlend {
+                src_color_blend_factor: BlendFactor::SrcAlpha,
+                dst_color_blend_factor: BlendFactor::One,
+                color_blend_op: BlendOp::Add,
+                src_alpha_blend_factor: BlendFactor::Zero,
+                dst_alpha_blend_factor: BlendFactor::One,
+                alpha_blend_op: BlendOp::Add,
+            }))?,
+            replace: create(None)?,
+            premultiplied: create(Some(AttachmentBlend {
+                src_color_blend_factor: BlendFactor::One,
+                dst_color_blend_factor: BlendFactor::OneMinusSrcAlpha,
+                color_blend_op: BlendOp::Add,
+                src_alpha_blend_factor: BlendFactor::One,
+                dst_alpha_blend_factor: BlendFactor::OneMinusSrcAlpha,
+                alpha_blend_op: BlendOp::Add,
+            }))?,
+        })
     }
 
     fn init_white_texture(&mut self) -> Result<(), String> {
@@ -535,6 +741,7 @@ impl VulkanPresenter {
             .swapchain
             .recreate(SwapchainCreateInfo {
                 image_extent: [width.max(1), height.max(1)],
+                present_mode: self.present_mode,
                 ..self.swapchain.create_info()
             })
             .map_err(|e| e.to_string())?;
@@ -547,7 +754,16 @@ impl VulkanPresenter {
             self.swapchain.image_format(),
             self.msaa_samples,
         )?;
-        self.pipeline = Self::create_pipeline(
+        self.canvas = Self::create_canvas(
+            &self.images,
+            self.keep_passes.as_ref(),
+            self.memory_allocator.clone(),
+            self.swapchain.image_format(),
+            self.msaa_samples,
+        )?;
+        // the msaa image and canvas were just recreated, so there's no last frame to load
+        self.canvas_holds_last_frame = false;
+        self.pipelines = Self::create_pipelines(
             self.device.clone(),
             self.render_pass.clone(),
             width.max(1),
@@ -558,13 +774,16 @@ impl VulkanPresenter {
         Ok(())
     }
 
+    // with `capture` set, also returns the rendered frame as rgba; None means the frame was
+    // skipped (e.g. while the swapchain is recreated) and the capture should be retried
     pub(crate) fn render(
         &mut self,
         platform: &SharedPlatformState,
         render_state: &SharedRenderState,
         width: u32,
         height: u32,
-    ) -> Result<(), String> {
+        capture: bool,
+    ) -> Result<Option<RgbaImage>, String> {
         if let Some(previous) = self.previous_frame_end.as_mut() {
             previous.cleanup_finished();
         }
@@ -574,10 +793,17 @@ impl VulkanPresenter {
         }
 
         let commands = renderer::drain_commands(render_state)?;
-        let clear_color = platform
-            .lock()
-            .map_err(|_| "platform lock poisoned".to_string())?
-            .clear_color();
+        let (frame_pass, clear_color) = {
+            let platform = platform
+                .lock()
+                .map_err(|_| "platform lock poisoned".to_string())?;
+            let frame_pass = choose_frame_pass(
+                platform.clear_enabled(),
+                self.keep_passes.is_some(),
+                self.canvas_holds_last_frame,
+            );
+            (frame_pass, platform.clear_color())
+        };
         let batches = self.build_batches(commands, width.max(1), height.max(1))?;
 
         let (image_index, suboptimal, acquire_future) =
@@ -587,17 +813,24 @@ impl VulkanPresenter {
                 Ok(result) => result,
                 Err(VulkanError::OutOfDate) => {
                     self.recreate_swapchain = true;
-                    return Ok(());
+                    return Ok(None);
                 }
                 Err(error) => return Err(error.to_string()),
             };
 
+        let capture_buffer = if capture {
+            Some(self.create_capture_buffer(image_index as usize)?)
+        } else {
+            None
+        };
         let command_buffer = self.build_command_buffer(
             image_index as usize,
             width.max(1),
             height.max(1),
+            frame_pass,
             clear_color,
             batches,
+            capture_buffer.clone(),
         )?;
 
         let previous = self
@@ -620,10 +853,12 @@ impl VulkanPresenter {
                 // swapchain image/framebuffer usage ordered and avoids Vulkano validation failures.
                 future.wait(None).map_err(|e| e.to_string())?;
                 self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+                self.canvas_holds_last_frame = frame_pass != FramePass::Clear;
             }
             Err(VulkanError::OutOfDate) => {
                 self.recreate_swapchain = true;
                 self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+                return Ok(None);
             }
             Err(error) => return Err(error.to_string()),
         }
@@ -632,7 +867,69 @@ impl VulkanPresenter {
             self.recreate_swapchain = true;
         }
 
-        Ok(())
+        match capture_buffer {
+            Some(buffer) => self
+                .read_capture_buffer(&buffer, image_index as usize)
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn create_capture_buffer(&self, image_index: usize) -> Result<Subbuffer<[u8]>, String> {
+        if !self
+            .swapchain
+            .image_usage()
+            .intersects(ImageUsage::TRANSFER_SRC)
+        {
+            return Err("this display surface does not support reading frames back".to_string());
+        }
+        let [width, height, _] = self.images[image_index].extent();
+        Buffer::new_slice::<u8>(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                ..Default::default()
+            },
+            width as u64 * height as u64 * 4,
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    fn read_capture_buffer(
+        &self,
+        buffer: &Subbuffer<[u8]>,
+        image_index: usize,
+    ) -> Result<RgbaImage, String> {
+        let [width, height, _] = self.images[image_index].extent();
+        let mut pixels = buffer.read().map_err(|e| e.to_string())?.to_vec();
+        match self.swapchain.image_format() {
+            Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB => {
+                for pixel in pixels.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+            }
+            Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB => {}
+            other => return Err(format!("cannot capture frames in format {other:?}")),
+        }
+        // the window is opaque, so drop whatever alpha the clear color left behind
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+        RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| "captured frame has an unexpected size".to_string())
+    }
+
+    pub(crate) fn set_vsync(&mut self, vsync: bool) {
+        let present_mode = choose_present_mode(&self.supported_present_modes, vsync);
+        if present_mode != self.present_mode {
+            self.present_mode = present_mode;
+            self.recreate_swapchain = true;
+        }
     }
 
     pub(crate) fn request_swapchain_recreate(&mut self) {
@@ -644,8 +941,10 @@ impl VulkanPresenter {
         image_index: usize,
         width: u32,
         height: u32,
+        frame_pass: FramePass,
         clear: Color,
         batches: Vec<TextureBatch>,
+        capture_buffer: Option<Subbuffer<[u8]>>,
     ) -> Result<Arc<PrimaryAutoCommandBuffer>, String> {
         let mut builder = AutoCommandBufferBuilder::primary(
             &self.command_buffer_allocator,
@@ -654,30 +953,40 @@ impl VulkanPresenter {
         )
         .map_err(|e| e.to_string())?;
 
-        let clear_values = if self.msaa_samples == SampleCount::Sample1 {
-            vec![Some(ClearValue::Float([
+        let keep_pass = match (frame_pass, &self.keep_passes) {
+            (FramePass::StartKeep, Some(passes)) => Some(passes.start.clone()),
+            (FramePass::Keep, Some(passes)) => Some(passes.keep.clone()),
+            _ => None,
+        };
+        let canvas = keep_pass.as_ref().and(self.canvas.as_ref());
+        let (render_pass, framebuffer) = match (keep_pass, canvas) {
+            (Some(keep_pass), Some(canvas)) => (keep_pass, canvas.framebuffer.clone()),
+            (Some(keep_pass), None) => (keep_pass, self.framebuffers[image_index].clone()),
+            (None, _) => (
+                self.render_pass.clone(),
+                self.framebuffers[image_index].clone(),
+            ),
+        };
+        let clear_value = (frame_pass != FramePass::Keep).then(|| {
+            ClearValue::Float([
                 clear.r as f32 / 255.0,
                 clear.g as f32 / 255.0,
                 clear.b as f32 / 255.0,
                 clear.a as f32 / 255.0,
-            ]))]
+            ])
+        });
+        let clear_values = if self.msaa_samples == SampleCount::Sample1 {
+            vec![clear_value]
         } else {
-            vec![
-                Some(ClearValue::Float([
-                    clear.r as f32 / 255.0,
-                    clear.g as f32 / 255.0,
-                    clear.b as f32 / 255.0,
-                    clear.a as f32 / 255.0,
-                ])),
-                None,
-            ]
+            vec![clear_value, None]
         };
 
         builder
             .begin_render_pass(
                 RenderPassBeginInfo {
+                    render_pass,
                     clear_values,
-                    ..RenderPassBeginInfo::framebuffer(self.framebuffers[image_index].clone())
+                    ..RenderPassBeginInfo::framebuffer(framebuffer)
                 },
                 SubpassBeginInfo {
                     contents: SubpassContents::Inline,
@@ -698,14 +1007,18 @@ impl VulkanPresenter {
                 .collect(),
             )
             .map_err(|e| e.to_string())?;
-        builder
-            .bind_pipeline_graphics(self.pipeline.clone())
-            .map_err(|e| e.to_string())?;
-
+        let mut bound_blend = None;
         for batch in batches {
             if batch.vertices.is_empty() {
                 continue;
             }
+            let pipeline = self.pipelines.get(batch.blend);
+            if bound_blend != Some(batch.blend) {
+                builder
+                    .bind_pipeline_graphics(pipeline.clone())
+                    .map_err(|e| e.to_string())?;
+                bound_blend = Some(batch.blend);
+            }
             let descriptor = self
                 .descriptor_for(batch.texture, batch.filter)
                 .ok_or_else(|| "missing cached texture descriptor".to_string())?;
@@ -728,7 +1041,7 @@ impl VulkanPresenter {
             builder
                 .bind_descriptor_sets(
                     PipelineBindPoint::Graphics,
-                    self.pipeline.layout().clone(),
+                    pipeline.layout().clone(),
                     0,
                     descriptor,
                 )
@@ -742,6 +1055,22 @@ impl VulkanPresenter {
         builder
             .end_render_pass(SubpassEndInfo::default())
             .map_err(|e| e.to_string())?;
+        if let Some(canvas) = canvas {
+            builder
+                .copy_image(CopyImageInfo::images(
+                    canvas.image.clone(),
+                    self.images[image_index].clone(),
+                ))
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(buffer) = capture_buffer {
+            builder
+                .copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(
+                    self.images[image_index].clone(),
+                    buffer,
+                ))
+                .map_err(|e| e.to_string())?;
+        }
         builder.build().map_err(|e| e.to_string())
     }
 
@@ -764,7 +1093,7 @@ impl VulkanPresenter {
         height: u32,
     ) -> Result<Vec<TextureBatch>, String> {
         let mut batches = Vec::new();
-        let mut current: Option<TextureBatch> = None;
+        let mut blend = BlendMode::Alpha;
 
         for command in commands {
             if !renderer::command_intersects_viewport(&command, width, height) {
@@ -795,8 +1124,8 @@ impl VulkanPresenter {
                         color,
                     );
                     push_vertices(
-                        &mut current,
                         &mut batches,
+                        blend,
                         self.white_texture,
                         TextureFilter::Nearest,
                         verts,
@@ -804,8 +1133,8 @@ impl VulkanPresenter {
                 }
                 DrawCommand::Triangle { a, b, c, color } => {
                     push_vertices(
-                        &mut current,
                         &mut batches,
+                        blend,
                         self.white_texture,
                         TextureFilter::Nearest,
                         vec![
@@ -840,8 +1169,8 @@ impl VulkanPresenter {
                         verts.push(vertex_from_point(width, height, p2, color, [0.0, 1.0]));
                     }
                     push_vertices(
-                        &mut current,
                         &mut batches,
+                        blend,
                         self.white_texture,
                         TextureFilter::Nearest,
                         verts,
@@ -856,11 +1185,17 @@ impl VulkanPresenter {
                     tint,
                     filter,
                 } => {
+                    let filter = image.filter_override().unwrap_or(filter);
                     let texture = self.texture_for_image(&image)?;
                     let uv = image_uvs(&image, source)?;
                     let corners = image_corners(dest, rotation, pivot);
+                    let (blend, tint) = if image.is_premultiplied() && blend == BlendMode::Alpha {
+                        (BlendMode::Premultiplied, renderer::premultiply_color(tint))
+                    } else {
+                        (blend, tint)
+                    };
                     let verts = quad_vertices(width, height, corners, uv, tint);
-                    push_vertices(&mut current, &mut batches, texture, filter, verts);
+                    push_vertices(&mut batches, blend, texture, filter, verts);
                 }
                 DrawCommand::Text(request) => {
                     let Some(sprite) = renderer::rasterize_text_sprite(&request) else {
@@ -875,14 +1210,12 @@ impl VulkanPresenter {
                         [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
                         Color::WHITE,
                     );
-                    push_vertices(&mut current, &mut batches, texture, sprite.filter, verts);
+                    push_vertices(&mut batches, blend, texture, sprite.filter, verts);
                 }
+                DrawCommand::SetBlend(mode) => blend = mode,
             }
         }
 
-        if let Some(batch) = current.take() {
-            batches.push(batch);
-        }
         Ok(batches)
     }
 
@@ -1036,7 +1369,8 @@ impl VulkanPresenter {
 
         let view = ImageView::new_default(image).map_err(|e| e.to_string())?;
         let layout = self
-            .pipeline
+            .pipelines
+            .alpha
             .layout()
             .set_layouts()
             .first()
@@ -1076,33 +1410,102 @@ impl VulkanPresenter {
     }
 }
 
+// Fifo waits for the display refresh (vsync); Immediate lets app.setMaxFps go past it
+fn choose_present_mode(supported: &[PresentMode], vsync: bool) -> PresentMode {
+    if !vsync && supported.contains(&PresentMode::Immediate) {
+        PresentMode::Immediate
+    } else {
+        PresentMode::Fifo
+    }
+}
+
+// Frames clear as usual while clearing is on. Once it's off, the first frame clears the msaa
+// image or canvas and stores it, and later frames load it and draw over the last frame.
+fn choose_frame_pass(
+    clear_enabled: bool,
+    keep_supported: bool,
+    canvas_holds_last_frame: bool,
+) -> FramePass {
+    if clear_enabled || !keep_supported {
+        FramePass::Clear
+    } else if canvas_holds_last_frame {
+        FramePass::Keep
+    } else {
+        FramePass::StartKeep
+    }
+}
+
+// Draws normally append to the last batch. A draw whose texture differs may instead join an
+// earlier batch with the same blend mode, texture and filter, as long as it overlaps none of the batches
+// in between; disjoint draws can swap order without changing the picture, so sprites that
+// alternate between a few textures still end up in a handful of batches.
 fn push_vertices(
-    current: &mut Option<TextureBatch>,
     batches: &mut Vec<TextureBatch>,
+    blend: BlendMode,
     texture: TextureKey,
     filter: TextureFilter,
     vertices: Vec<GpuVertex>,
 ) {
-    match current {
-        Some(batch) if batch.texture == texture && batch.filter == filter => {
-            batch.vertices.extend(vertices);
-        }
-        Some(_) => {
-            batches.push(current.take().unwrap());
-            *current = Some(TextureBatch {
-                texture,
-                filter,
-                vertices,
-            });
+    let bounds = vertex_bounds(&vertices);
+    if let Some(index) = merge_target(batches, blend, texture, filter, bounds) {
+        let batch = &mut batches[index];
+        batch.vertices.extend(vertices);
+        batch.bounds = union_bounds(batch.bounds, bounds);
+        return;
+    }
+    batches.push(TextureBatch {
+        texture,
+        filter,
+        blend,
+        vertices,
+        bounds,
+    });
+}
+
+// index of the batch a draw with these settings and bounds may join, or None to start a new one
+fn merge_target(
+    batches: &[TextureBatch],
+    blend: BlendMode,
+    texture: TextureKey,
+    filter: TextureFilter,
+    bounds: [f32; 4],
+) -> Option<usize> {
+    for (index, batch) in batches.iter().enumerate().rev().take(BATCH_LOOKBACK) {
+        if batch.blend == blend && batch.texture == texture && batch.filter == filter {
+            return Some(index);
         }
-        None => {
-            *current = Some(TextureBatch {
-                texture,
-                filter,
-                vertices,
-            });
+        if bounds_overlap(batch.bounds, bounds) {
+            return None;
         }
     }
+    None
+}
+
+fn vertex_bounds(vertices: &[GpuVertex]) -> [f32; 4] {
+    vertices.iter().fold(
+        [f32::MAX, f32::MAX, f32::MIN, f32::MIN],
+        |[min_x, min_y, max_x, max_y], vertex| {
+            [
+                min_x.min(vertex.position[0]),
+                min_y.min(vertex.position[1]),
+                max_x.max(vertex.position[0]),
+                max_y.max(vertex.position[1]),
+            ]
+        },
+    )
+}
+
+fn union_bounds(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [
+        a[0].min(b[0]),
+        a[1].min(b[1]),
+        a[2].max(b[2]),
+        a[3].max(b[3]),
+    ]
+}
+
+fn bounds_overlap(a: [f32; 4], b: [f32; 4]) -> bool {
+    a[0] < b[2] && b[0] < a[2] && a[1] < b[3] && b[1] < a[3]
 }
 
 fn world_point(x: f32, y: f32, pivot_x: f32, pivot_y: f32, rotation: f32) -> Vec2 {
@@ -1177,3 +1580,102 @@ fn image_uvs(image: &ImageHandle, source: Option<Rect>) -> Result<[[f32; 2]; 4],
     let v1 = (source.y + source.h) / img_h.max(1) as f32;
     Ok([[u0, v0], [u1, v0], [u1, v1], [u0, v1]])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 10x10 quad at x, y on a 100x100 target
+    fn draw(batches: &mut Vec<TextureBatch>, blend: BlendMode, texture: u64, x: f32, y: f32) {
+        let corners = [
+            Vec2 { x, y },
+            Vec2 { x: x + 10.0, y },
+            Vec2 {
+                x: x + 10.0,
+                y: y + 10.0,
+            },
+            Vec2 { x, y: y + 10.0 },
+        ];
+        let vertices = quad_vertices(100, 100, corners, [[0.0, 0.0]; 4], Color::WHITE);
+        push_vertices(
+            batches,
+            blend,
+            TextureKey(texture),
+            TextureFilter::Nearest,
+            vertices,
+        );
+    }
+
+    // (texture, blend, quad count) per batch, in submission order
+    fn batch_summary(batches: &[TextureBatch]) -> Vec<(u64, BlendMode, usize)> {
+        batches
+            .iter()
+            .map(|batch| (batch.texture.0, batch.blend, batch.vertices.len() / 6))
+            .collect()
+    }
+
+    #[test]
+    fn adjacent_compatible_draws_share_a_batch() {
+        let mut batches = Vec::new();
+        draw(&mut batches, BlendMode::Alpha, 1, 0.0, 0.0);
+        draw(&mut batches, BlendMode::Alpha, 1, 5.0, 5.0);
+        assert_eq!(batch_summary(&batches), vec![(1, BlendMode::Alpha, 2)]);
+
+        // disjoint draws may hop back over a batch with another texture
+        draw(&mut batches, BlendMode::Alpha, 2, 50.0, 0.0);
+        draw(&mut batches, BlendMode::Alpha, 1, 0.0, 50.0);
+        assert_eq!(
+            batch_summary(&batches),
+            vec![(1, BlendMode::Alpha, 3), (2, BlendMode::Alpha, 1)]
+        );
+    }
+
+    #[test]
+    fn overlapping_draws_keep_their_order() {
+        let mut batches = Vec::new();
+        draw(&mut batches, BlendMode::Alpha, 1, 0.0, 0.0);
+        draw(&mut batches, BlendMode::Alpha, 2, 5.0, 5.0);
+        draw(&mut batches, BlendMode::Alpha, 1, 8.0, 8.0);
+        assert_eq!(
+            batch_summary(&batches),
+            vec![
+                (1, BlendMode::Alpha, 1),
+                (2, BlendMode::Alpha, 1),
+                (1, BlendMode::Alpha, 1),
+            ]
+        );
+
+        let mut batches = Vec::new();
+        draw(&mut batches, BlendMode::Alpha, 1, 0.0, 0.0);
+        draw(&mut batches, BlendMode::Additive, 1, 5.0, 5.0);
+        draw(&mut batches, BlendMode::Alpha, 1, 8.0, 8.0);
+        assert_eq!(
+            batch_summary(&batches),
+            vec![
+                (1, BlendMode::Alpha, 1),
+                (1, BlendMode::Additive, 1),
+                (1, BlendMode::Alpha, 1),
+            ]
+        );
+        // an additive draw over the first quad may not skip back past the alpha quad above it
+        let bounds = batches[0].bounds;
+        let target = merge_target(
+            &batches,
+            BlendMode::Additive,
+            TextureKey(1),
+            TextureFilter::Nearest,
+            bounds,
+        );
+        assert_eq!(target, None);
+    }
+
+    #[test]
+    fn keep_passes_start_with_a_clear() {
+        assert_eq!(choose_frame_pass(true, true, true), FramePass::Clear);
+        // first frame with clearing off, or the first after the swapchain was recreated
+        assert_eq!(choose_frame_pass(false, true, false), FramePass::StartKeep);
+        assert_eq!(choose_frame_pass(false, true, true), FramePass::Keep);
+        // swapchains that can't be copied into keep clearing
+        assert_eq!(choose_frame_pass(false, false, true), FramePass::Clear);
+    }
+}
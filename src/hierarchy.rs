@@ -8,6 +8,8 @@ pub struct Entity {
     pub parent: Option<EntityId>,
     pub id: EntityId,
     pub luau_key: RegistryKey,
+    // set once the entity's own `start` hook has had its chance to run
+    pub started: bool,
 }
 
 pub struct Component {
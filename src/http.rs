@@ -12,7 +12,7 @@ mod native {
     use std::sync::{Arc, OnceLock};
     use std::time::Duration;
 
-    use crate::lua_error::{describe_lua_error, protect_lua_call};
+    use crate::lua_error::{protect_lua_call, report_lua_error};
 
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     enum HttpScheme {
@@ -372,10 +372,7 @@ mod native {
                         protect_lua_call("running http callback", || callback.call::<()>(payload));
                     lua.remove_registry_value(callback_key)?;
                     if let Err(error) = call_result {
-                        eprintln!(
-                            "\x1b[31mLua Error in http callback:\x1b[0m\n{}",
-                            describe_lua_error(&error)
-                        );
+                        report_lua_error(lua, "http callback", &error);
                     }
                 }
                 Ok(())
@@ -0,0 +1,190 @@
+use mlua::{Lua, Table, Value};
+use serde_json::{Map, Number};
+use std::collections::HashSet;
+
+pub(crate) fn lua_to_json(value: Value) -> mlua::Result<serde_json::Value> {
+    to_json(value, &mut HashSet::new())
+}
+
+fn to_json(value: Value, visited: &mut HashSet<usize>) -> mlua::Result<serde_json::Value> {
+    match value {
+        Value::Nil => Ok(serde_json::Value::Null),
+        Value::Boolean(value) => Ok(serde_json::Value::Bool(value)),
+        Value::Integer(value) => Ok(serde_json::Value::from(value)),
+        Value::Number(value) => number_to_json(value),
+        Value::String(value) => Ok(serde_json::Value::String(value.to_str()?.to_string())),
+        Value::Table(table) => table_to_json(table, visited),
+        other => Err(mlua::Error::external(format!(
+            "cannot encode a value of type '{}' as JSON",
+            other.type_name()
+        ))),
+    }
+}
+
+fn number_to_json(value: f64) -> mlua::Result<serde_json::Value> {
+    // Luau numbers are all doubles; whole ones are written without a trailing ".0"
+    if value.fract() == 0.0 && value.abs() < 9_007_199_254_740_992.0 {
+        return Ok(serde_json::Value::from(value as i64));
+    }
+    Number::from_f64(value)
+        .map(serde_json::Value::Number)
+        .ok_or_else(|| mlua::Error::external(format!("cannot encode the number {value} as JSON")))
+}
+
+fn key_to_string(key: Value) -> mlua::Result<String> {
+    match key {
+        Value::String(key) => Ok(key.to_str()?.to_string()),
+        Value::Integer(key) => Ok(key.to_string()),
+        Value::Number(key) => Ok(number_to_json(key)?.to_string()),
+        other => Err(mlua::Error::external(format!(
+            "cannot use a key of type '{}' in a JSON object",
+            other.type_name()
+        ))),
+    }
+}
+
+fn table_to_json(table: Table, visited: &mut HashSet<usize>) -> mlua::Result<serde_json::Value> {
+    let pointer = table.to_pointer() as usize;
+    if !visited.insert(pointer) {
+        return Err(mlua::Error::external("cannot encode cyclic tables as JSON"));
+    }
+
+    let mut entries = Vec::<(Value, Value)>::new();
+    for pair in table.pairs::<Value, Value>() {
+        entries.push(pair?);
+    }
+
+    // a table whose keys are exactly 1..n is an array; everything else (empty tables
+    // included) becomes an object
+    let len = table.raw_len();
+    let is_array = len > 0
+        && entries.len() == len
+        && entries.iter().all(|(key, _)| match key {
+            Value::Integer(index) => *index >= 1 && (*index as usize) <= len,
+            Value::Number(index) => {
+                index.fract() == 0.0 && *index >= 1.0 && (*index as usize) <= len
+            }
+            _ => false,
+        });
+
+    let encoded = if is_array {
+        let mut values = Vec::with_capacity(len);
+        for index in 1..=len {
+            values.push(to_json(table.raw_get::<Value>(index)?, visited)?);
+        }
+        serde_json::Value::Array(values)
+    } else {
+        let mut object = Map::new();
+        for (key, value) in entries {
+            object.insert(key_to_string(key)?, to_json(value, visited)?);
+        }
+        serde_json::Value::Object(object)
+    };
+
+    visited.remove(&pointer);
+    Ok(encoded)
+}
+
+pub(crate) fn json_to_lua(lua: &Lua, value: serde_json::Value) -> mlua::Result<Value> {
+    match value {
+        serde_json::Value::Null => Ok(Value::Nil),
+        serde_json::Value::Bool(value) => Ok(Value::Boolean(value)),
+        serde_json::Value::Number(value) => Ok(Value::Number(value.as_f64().unwrap_or(0.0))),
+        serde_json::Value::String(value) => Ok(Value::String(lua.create_string(&value)?)),
+        serde_json::Value::Array(values) => {
+            let table = lua.create_table_with_capacity(values.len(), 0)?;
+            // raw_set keeps the positions of nulls instead of shifting later elements down
+            for (index, value) in values.into_iter().enumerate() {
+                table.raw_set(index + 1, json_to_lua(lua, value)?)?;
+            }
+            Ok(Value::Table(table))
+        }
+        serde_json::Value::Object(entries) => {
+            let table = lua.create_table_with_capacity(0, entries.len())?;
+            for (key, value) in entries {
+                table.raw_set(key, json_to_lua(lua, value)?)?;
+            }
+            Ok(Value::Table(table))
+        }
+    }
+}
+
+pub(crate) fn add_json_module(lua: &Lua) -> mlua::Result<()> {
+    let module = lua.create_table()?;
+
+    module.set(
+        "parse",
+        lua.create_function(|lua, text: String| {
+            let value: serde_json::Value = serde_json::from_str(&text)
+                .map_err(|error| mlua::Error::external(format!("json.parse: {error}")))?;
+            json_to_lua(lua, value)
+        })?,
+    )?;
+
+    module.set(
+        "stringify",
+        lua.create_function(|_lua, (value, pretty): (Value, Option<bool>)| {
+            let value = lua_to_json(value)?;
+            let text = if pretty.unwrap_or(false) {
+                serde_json::to_string_pretty(&value)
+            } else {
+                serde_json::to_string(&value)
+            };
+            text.map_err(mlua::Error::external)
+        })?,
+    )?;
+
+    lua.globals().set("json", module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_round_trip_through_json() -> mlua::Result<()> {
+        let lua = Lua::new();
+        add_json_module(&lua)?;
+
+        let (text, level, second, name, empty): (String, f64, String, String, String) = lua
+            .load(
+                r#"
+                local text = json.stringify({ level = 3, items = { "sword", "shield" }, player = { name = "ada" } })
+                local decoded = json.parse(text)
+                return text, decoded.level, decoded.items[2], decoded.player.name, json.stringify({})
+                "#,
+            )
+            .eval()?;
+        assert_eq!(
+            text,
+            r#"{"items":["sword","shield"],"level":3,"player":{"name":"ada"}}"#
+        );
+        assert_eq!(
+            (level, second.as_str(), name.as_str()),
+            (3.0, "shield", "ada")
+        );
+        assert_eq!(empty, "{}");
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_values_and_bad_input_are_errors() -> mlua::Result<()> {
+        let lua = Lua::new();
+        add_json_module(&lua)?;
+
+        let error = lua
+            .load("json.stringify({ callback = function() end })")
+            .exec()
+            .unwrap_err();
+        assert!(error.to_string().contains("type 'function'"), "{error}");
+        assert!(
+            lua.load("local t = {}; t.self = t; json.stringify(t)")
+                .exec()
+                .is_err()
+        );
+        assert!(lua.load("json.stringify(0/0)").exec().is_err());
+        assert!(lua.load("json.parse('{ not json')").exec().is_err());
+        Ok(())
+    }
+}
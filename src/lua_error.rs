@@ -1,5 +1,10 @@
+use mlua::Lua;
 use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 fn panic_payload_to_string(payload: &(dyn Any + Send)) -> String {
     if let Some(message) = payload.downcast_ref::<&str>() {
@@ -71,6 +76,148 @@ pub fn describe_lua_error(error: &mlua::Error) -> String {
     message
 }
 
+// `chunk:line` from a Luau message line such as `/game/lib/util:12: attempt to index nil`
+fn parse_error_location(line: &str) -> Option<String> {
+    let line = line.trim();
+    let mut search_from = 0;
+    while let Some(offset) = line[search_from..].find(':') {
+        let colon = search_from + offset;
+        let rest = &line[colon + 1..];
+        let digits = rest.chars().take_while(|ch| ch.is_ascii_digit()).count();
+        if colon > 0 && digits > 0 && rest[digits..].starts_with(':') {
+            return Some(format!("{}:{}", &line[..colon], &rest[..digits]));
+        }
+        search_from = colon + 1;
+    }
+    None
+}
+
+// script and line the error was raised from, when the message names one
+pub fn lua_error_location(error: &mlua::Error) -> Option<String> {
+    let (message, _) = flatten_lua_error(error);
+    message.lines().find_map(parse_error_location)
+}
+
+// identical errors inside this window are counted instead of printed
+const ERROR_REPEAT_WINDOW: Duration = Duration::from_secs(2);
+
+struct RepeatedError {
+    last_printed: Instant,
+    suppressed: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct ErrorDebouncer {
+    seen: HashMap<String, RepeatedError>,
+}
+
+impl ErrorDebouncer {
+    // Some(n) means print now, n being how many copies were swallowed since the last print
+    pub(crate) fn check(&mut self, message: &str, now: Instant) -> Option<u32> {
+        // errors that stopped happening are forgotten so the map doesn't grow forever; ones with
+        // swallowed copies wait for `flush` to report them
+        self.seen.retain(|_, entry| {
+            entry.suppressed > 0
+                || now.saturating_duration_since(entry.last_printed) < ERROR_REPEAT_WINDOW * 4
+        });
+        match self.seen.get_mut(message) {
+            Some(entry)
+                if now.saturating_duration_since(entry.last_printed) < ERROR_REPEAT_WINDOW =>
+            {
+                entry.suppressed += 1;
+                None
+            }
+            Some(entry) => {
+                let suppressed = entry.suppressed;
+                entry.last_printed = now;
+                entry.suppressed = 0;
+                Some(suppressed)
+            }
+            None => {
+                self.seen.insert(
+                    message.to_string(),
+                    RepeatedError {
+                        last_printed: now,
+                        suppressed: 0,
+                    },
+                );
+                Some(0)
+            }
+        }
+    }
+
+    // errors whose window ran out with copies still swallowed, so the count isn't lost when the
+    // error stops recurring
+    pub(crate) fn flush(&mut self, now: Instant) -> Vec<(String, u32)> {
+        let mut expired = Vec::new();
+        for (message, entry) in &mut self.seen {
+            if entry.suppressed > 0
+                && now.saturating_duration_since(entry.last_printed) >= ERROR_REPEAT_WINDOW
+            {
+                expired.push((message.clone(), entry.suppressed));
+                entry.last_printed = now;
+                entry.suppressed = 0;
+            }
+        }
+        expired.sort();
+        expired
+    }
+}
+
+// one per Runtime (and hosted server), kept in its Lua app data so every report can reach it
+pub(crate) type SharedErrorDebouncer = Rc<RefCell<ErrorDebouncer>>;
+
+pub(crate) fn new_shared_error_debouncer(lua: &Lua) -> SharedErrorDebouncer {
+    let debouncer = SharedErrorDebouncer::default();
+    lua.set_app_data(debouncer.clone());
+    debouncer
+}
+
+fn lua_error_debouncer(lua: &Lua) -> SharedErrorDebouncer {
+    if let Some(debouncer) = lua.app_data_ref::<SharedErrorDebouncer>() {
+        return debouncer.clone();
+    }
+    new_shared_error_debouncer(lua)
+}
+
+pub fn format_lua_error_report(context: &str, error: &mlua::Error) -> String {
+    let description = describe_lua_error(error);
+    match lua_error_location(error) {
+        Some(location) => {
+            format!("\x1b[31mLua Error in {context}\x1b[0m at {location}\n{description}")
+        }
+        None => format!("\x1b[31mLua Error in {context}:\x1b[0m\n{description}"),
+    }
+}
+
+fn print_report(report: &str, repeated: u32) {
+    if repeated == 0 {
+        eprintln!("{report}");
+    } else {
+        eprintln!("{report}\n(repeated {repeated} more times since the last report)");
+    }
+}
+
+pub fn report_lua_error(lua: &Lua, context: &str, error: &mlua::Error) {
+    let report = format_lua_error_report(context, error);
+    let debouncer = lua_error_debouncer(lua);
+    let now = Instant::now();
+    // a broken update would otherwise print the same error every frame
+    let suppressed = debouncer.borrow_mut().check(&report, now);
+    if let Some(repeated) = suppressed {
+        print_report(&report, repeated);
+    }
+    flush_lua_error_reports(&debouncer, now);
+}
+
+// prints the "(repeated N)" summary of errors that went quiet; the runtime calls it every frame
+pub(crate) fn flush_lua_error_reports(debouncer: &SharedErrorDebouncer, now: Instant) {
+    let expired = debouncer.borrow_mut().flush(now);
+    for (report, repeated) in expired {
+        print_report(&report, repeated);
+    }
+}
+
 pub fn describe_panic(payload: &(dyn Any + Send)) -> String {
     panic_payload_to_string(payload)
 }
@@ -89,3 +236,91 @@ where
         ))),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lua_error_location_names_script_and_line() -> mlua::Result<()> {
+        let lua = Lua::new();
+        let error = lua
+            .load("local value = nil\nreturn value.field")
+            .set_name("@/game/lib/util")
+            .exec()
+            .expect_err("indexing nil should fail");
+        assert_eq!(
+            lua_error_location(&error).as_deref(),
+            Some("/game/lib/util:2")
+        );
+
+        let wrapped = protect_lua_call("running component update callback (Mover)", || {
+            Err(error.clone())
+        })
+        .expect_err("the callback error should pass through");
+        assert_eq!(
+            lua_error_location(&wrapped).as_deref(),
+            Some("/game/lib/util:2")
+        );
+
+        let plain = mlua::Error::external("no location here");
+        assert_eq!(lua_error_location(&plain), None);
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_lua_errors_are_debounced() {
+        let mut debouncer = ErrorDebouncer::default();
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        assert_eq!(debouncer.check("boom", at(0)), Some(0));
+        assert_eq!(debouncer.check("boom", at(16)), None);
+        assert_eq!(debouncer.check("boom", at(33)), None);
+        assert_eq!(debouncer.check("other", at(33)), Some(0));
+        assert_eq!(debouncer.check("boom", at(2500)), Some(2));
+        assert_eq!(debouncer.check("boom", at(2516)), None);
+    }
+
+    #[test]
+    fn flush_reports_errors_that_stopped_repeating() {
+        let mut debouncer = ErrorDebouncer::default();
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        assert_eq!(debouncer.check("boom", at(0)), Some(0));
+        assert_eq!(debouncer.check("boom", at(16)), None);
+        assert_eq!(debouncer.check("boom", at(33)), None);
+        assert_eq!(debouncer.check("quiet", at(33)), Some(0));
+        // still inside the window
+        assert!(debouncer.flush(at(1000)).is_empty());
+        assert_eq!(debouncer.flush(at(2100)), vec![("boom".to_string(), 2)]);
+        // already summarized, and "quiet" never repeated
+        assert!(debouncer.flush(at(20_000)).is_empty());
+        // a long-quiet error isn't dropped before its summary is out
+        assert_eq!(debouncer.check("late", at(20_000)), Some(0));
+        assert_eq!(debouncer.check("late", at(20_010)), None);
+        assert_eq!(debouncer.check("other", at(40_000)), Some(0));
+        assert_eq!(debouncer.flush(at(40_000)), vec![("late".to_string(), 1)]);
+    }
+
+    #[test]
+    fn each_lua_state_debounces_on_its_own() {
+        let first = Lua::new();
+        let second = Lua::new();
+        let shared = new_shared_error_debouncer(&first);
+        let error = mlua::Error::external("boom");
+
+        report_lua_error(&first, "system update", &error);
+        report_lua_error(&first, "system update", &error);
+        report_lua_error(&second, "system update", &error);
+        assert!(Rc::ptr_eq(&shared, &lua_error_debouncer(&first)));
+        assert!(!Rc::ptr_eq(&shared, &lua_error_debouncer(&second)));
+        let report = format_lua_error_report("system update", &error);
+        assert_eq!(shared.borrow().seen[&report].suppressed, 1);
+        assert_eq!(
+            lua_error_debouncer(&second).borrow().seen[&report].suppressed,
+            0
+        );
+    }
+}
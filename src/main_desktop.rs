@@ -1,17 +1,25 @@
 mod assets;
 mod audio_system;
+mod camera;
 mod commands;
 mod core;
+mod draw;
+mod events;
 mod fs_module;
 mod gpu_renderer;
 pub mod hierarchy;
 mod http;
+mod json;
 mod lua_error;
 mod platform;
 mod prefabs;
+mod random;
 mod renderer;
 mod servers;
 mod shader;
+mod spatial_hash;
+mod storage;
+mod tween;
 mod user_input;
 pub mod window;
 
@@ -22,25 +30,29 @@ use std::fs::File;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::sync::mpsc::{Receiver, Sender, channel};
 use std::time::{Duration, Instant};
 
+use gilrs::{Axis, Button, Gilrs};
 use image::imageops::FilterType;
 use mlua::Compiler;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 #[cfg(windows)]
 use std::process::Command;
 use winit::dpi::LogicalSize;
 use winit::event::{
-    ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+    ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, Touch, TouchPhase,
+    VirtualKeyCode, WindowEvent,
 };
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::{CursorGrabMode, Icon, WindowBuilder};
+use winit::window::{CursorGrabMode, Fullscreen, Icon, Window, WindowBuilder};
 use zip::CompressionMethod;
 use zip::write::SimpleFileOptions;
 
 use crate::gpu_renderer::VulkanPresenter;
-use crate::platform::SharedPlatformState;
+use crate::platform::{GamepadState, ScreenshotRequest, SharedPlatformState};
 
 const EMBED_TRAILER_MAGIC: &[u8; 16] = b"NEOLOVE_EMBED_V1";
 const PAYLOAD_MAGIC: &[u8; 8] = b"NLPKGv1\0";
@@ -50,12 +62,30 @@ const TEMPLATE_NEOLOVE_ENGINE_API: &str =
     include_str!("project_template/neolove_engine_api.d.luau");
 const DEFAULT_WINDOW_WIDTH: f32 = 1280.0;
 const DEFAULT_WINDOW_HEIGHT: f32 = 720.0;
+const DEFAULT_MSAA_SAMPLES: u32 = 4;
+const MAX_MSAA_SAMPLES: u32 = 16;
 
 #[derive(Default, Clone)]
 struct ProjectSettings {
     package_name: Option<String>,
     window_title: Option<String>,
     window_icon: Option<String>,
+    window_width: Option<f32>,
+    window_height: Option<f32>,
+    window_vsync: Option<bool>,
+    window_filter: Option<String>,
+    window_msaa: Option<u32>,
+    window_high_dpi: Option<bool>,
+}
+
+struct WindowOptions {
+    title: String,
+    width: f32,
+    height: f32,
+    icon: Option<Icon>,
+    vsync: bool,
+    msaa: u32,
+    high_dpi: bool,
 }
 
 fn resolve_from_cwd(user_path: &str) -> std::io::Result<PathBuf> {
@@ -80,6 +110,53 @@ fn user_home_dir() -> Option<PathBuf> {
     }
 }
 
+fn user_data_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    {
+        env::var_os("APPDATA")
+            .map(PathBuf::from)
+            .or_else(|| user_home_dir().map(|home| home.join("AppData").join("Roaming")))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        user_home_dir().map(|home| home.join("Library").join("Application Support"))
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| user_home_dir().map(|home| home.join(".local").join("share")))
+    }
+}
+
+// one save file per project, keyed by [package].name (or the folder name) so renaming the
+// project folder of a named game keeps its saves
+fn project_storage_path(project_root: &Path, settings: &ProjectSettings) -> Option<PathBuf> {
+    let name = settings.package_name.clone().or_else(|| {
+        project_root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    })?;
+    let id: String = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Some(
+        user_data_dir()?
+            .join("neolove")
+            .join("storage")
+            .join(id)
+            .join("storage.json"),
+    )
+}
+
 #[cfg(not(windows))]
 fn upsert_marked_path_line(file_path: &Path, line: &str, marker: &str) -> std::io::Result<bool> {
     let existing = match fs::read_to_string(file_path) {
@@ -222,6 +299,17 @@ fn parse_quoted(input: &str) -> Option<String> {
     Some(value[1..value.len() - 1].to_string())
 }
 
+fn parse_window_dimension(input: &str) -> Option<f32> {
+    let value = input.trim().parse::<f32>().ok()?;
+    (value.is_finite() && value >= 1.0).then_some(value)
+}
+
+// 1 turns multisampling off; higher counts smooth shape and sprite edges at a fill-rate cost
+fn parse_msaa_samples(input: &str) -> Option<u32> {
+    let value = input.trim().parse::<u32>().ok()?;
+    (value.is_power_of_two() && value <= MAX_MSAA_SAMPLES).then_some(value)
+}
+
 fn parse_project_settings(project_root: &Path) -> ProjectSettings {
     let mut settings = ProjectSettings::default();
     let file_path = project_root.join("neolove.toml");
@@ -244,6 +332,27 @@ fn parse_project_settings(project_root: &Path) -> ProjectSettings {
             continue;
         };
         let key = key_raw.trim().to_ascii_lowercase();
+        if section == "window" && (key == "width" || key == "height") {
+            let dimension = parse_window_dimension(value_raw);
+            if key == "width" {
+                settings.window_width = dimension;
+            } else {
+                settings.window_height = dimension;
+            }
+            continue;
+        }
+        if section == "window" && key == "vsync" {
+            settings.window_vsync = value_raw.trim().parse::<bool>().ok();
+            continue;
+        }
+        if section == "window" && key == "high_dpi" {
+            settings.window_high_dpi = value_raw.trim().parse::<bool>().ok();
+            continue;
+        }
+        if section == "window" && key == "msaa" {
+            settings.window_msaa = parse_msaa_samples(value_raw);
+            continue;
+        }
         let Some(value) = parse_quoted(value_raw) else {
             continue;
         };
@@ -252,6 +361,7 @@ fn parse_project_settings(project_root: &Path) -> ProjectSettings {
             "package" if key == "name" => settings.package_name = Some(value),
             "window" if key == "title" => settings.window_title = Some(value),
             "window" if key == "icon" => settings.window_icon = Some(value),
+            "window" if key == "filter" => settings.window_filter = Some(value),
             _ => {}
         }
     }
@@ -267,7 +377,7 @@ fn try_load_window_icon(project_root: &Path, icon_path: &str) -> Option<Icon> {
     Icon::from_rgba(resized.into_raw(), 64, 64).ok()
 }
 
-fn window_options_for_project(project_root: &Path) -> (String, Option<Icon>) {
+fn window_options_for_project(project_root: &Path) -> WindowOptions {
     let settings = parse_project_settings(project_root);
     let title = settings
         .window_title
@@ -279,7 +389,39 @@ fn window_options_for_project(project_root: &Path) -> (String, Option<Icon>) {
         .as_ref()
         .and_then(|path| try_load_window_icon(project_root, path));
 
-    (title, icon)
+    WindowOptions {
+        title,
+        width: settings.window_width.unwrap_or(DEFAULT_WINDOW_WIDTH),
+        height: settings.window_height.unwrap_or(DEFAULT_WINDOW_HEIGHT),
+        icon,
+        vsync: settings.window_vsync.unwrap_or(false),
+        msaa: settings.window_msaa.unwrap_or(DEFAULT_MSAA_SAMPLES),
+        high_dpi: settings.window_high_dpi.unwrap_or(false),
+    }
+}
+
+// framebuffer pixels per game unit: with `[window] high_dpi` games work in logical points,
+// otherwise in physical pixels
+fn content_scale(window: &Window, high_dpi: bool) -> f64 {
+    if high_dpi { window.scale_factor() } else { 1.0 }
+}
+
+fn game_window_size(window: &Window, high_dpi: bool) -> (f32, f32) {
+    let size = window.inner_size();
+    let scale = content_scale(window, high_dpi);
+    (
+        (size.width as f64 / scale) as f32,
+        (size.height as f64 / scale) as f32,
+    )
+}
+
+fn sync_window_scale(runtime: &window::Runtime, window: &Window, high_dpi: bool) {
+    let (width, height) = game_window_size(window, high_dpi);
+    runtime.set_platform_window_state(width, height);
+    runtime.set_platform_display_scale(
+        window.scale_factor() as f32,
+        content_scale(window, high_dpi) as f32,
+    );
 }
 
 fn should_skip_in_build(path: &Path) -> bool {
@@ -612,7 +754,7 @@ fn project_output_stem(project_root: &Path) -> String {
     sanitize_executable_name(&name_seed)
 }
 
-fn build_executable(project_root: &Path) -> Result<PathBuf, String> {
+fn build_executable(project_root: &Path, output_dir: Option<&Path>) -> Result<PathBuf, String> {
     let output_stem = project_output_stem(project_root);
 
     #[cfg(windows)]
@@ -637,10 +779,12 @@ fn build_executable(project_root: &Path) -> Result<PathBuf, String> {
         )
     })?;
 
-    let output_dir = project_root.join("dist");
+    let output_dir = output_dir
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| project_root.join("dist"));
     fs::create_dir_all(&output_dir).map_err(|e| {
         format!(
-            "failed to create dist directory {}: {e}",
+            "failed to create output directory {}: {e}",
             output_dir.display()
         )
     })?;
@@ -1214,6 +1358,15 @@ fn virtual_key_name(key: VirtualKeyCode) -> Option<&'static str> {
     })
 }
 
+fn touch_phase(phase: TouchPhase) -> platform::TouchPhase {
+    match phase {
+        TouchPhase::Started => platform::TouchPhase::Started,
+        TouchPhase::Moved => platform::TouchPhase::Moved,
+        TouchPhase::Ended => platform::TouchPhase::Ended,
+        TouchPhase::Cancelled => platform::TouchPhase::Cancelled,
+    }
+}
+
 fn mouse_button_name(button: MouseButton) -> &'static str {
     match button {
         MouseButton::Left => "left",
@@ -1223,6 +1376,62 @@ fn mouse_button_name(button: MouseButton) -> &'static str {
     }
 }
 
+const GAMEPAD_BUTTONS: &[(Button, &str)] = &[
+    (Button::South, "a"),
+    (Button::East, "b"),
+    (Button::West, "x"),
+    (Button::North, "y"),
+    (Button::Start, "start"),
+    (Button::Select, "back"),
+    (Button::Mode, "guide"),
+    (Button::LeftTrigger, "leftshoulder"),
+    (Button::RightTrigger, "rightshoulder"),
+    (Button::LeftTrigger2, "lefttrigger"),
+    (Button::RightTrigger2, "righttrigger"),
+    (Button::LeftThumb, "leftstick"),
+    (Button::RightThumb, "rightstick"),
+    (Button::DPadUp, "dpadup"),
+    (Button::DPadDown, "dpaddown"),
+    (Button::DPadLeft, "dpadleft"),
+    (Button::DPadRight, "dpadright"),
+];
+
+fn gamepad_snapshot(gilrs: &Gilrs) -> Vec<GamepadState> {
+    gilrs
+        .gamepads()
+        .map(|(_, gamepad)| {
+            let mut state = GamepadState::default();
+            for (button, name) in GAMEPAD_BUTTONS {
+                if gamepad.is_pressed(*button) {
+                    state.buttons_down.insert(name.to_string());
+                }
+            }
+            // gilrs reports stick Y as up-positive; flip it to match screen space
+            let axes = [
+                ("leftx", gamepad.value(Axis::LeftStickX)),
+                ("lefty", -gamepad.value(Axis::LeftStickY)),
+                ("rightx", gamepad.value(Axis::RightStickX)),
+                ("righty", -gamepad.value(Axis::RightStickY)),
+            ];
+            for (name, value) in axes {
+                state.axes.insert(name.to_string(), value);
+            }
+            // analog triggers are exposed as buttons with a value
+            for (button, name) in [
+                (Button::LeftTrigger2, "lefttrigger"),
+                (Button::RightTrigger2, "righttrigger"),
+            ] {
+                let value = gamepad
+                    .button_data(button)
+                    .map(|data| data.value())
+                    .unwrap_or(0.0);
+                state.axes.insert(name.to_string(), value);
+            }
+            state
+        })
+        .collect()
+}
+
 fn normalize_mouse_wheel_delta(delta: MouseScrollDelta) -> (f32, f32) {
     const PIXELS_PER_LINE: f32 = 40.0;
 
@@ -1283,73 +1492,141 @@ fn catch_desktop_panic<T>(context: &str, f: impl FnOnce() -> T) -> Result<T, Str
         .map_err(|payload| describe_desktop_panic(context, payload.as_ref()))
 }
 
-fn run_project_window(project_root: PathBuf) -> Result<(), String> {
+fn start_runtime(project_root: &Path, width: f32, height: f32) -> Result<window::Runtime, String> {
+    let mut runtime = window::Runtime::new(project_root.to_path_buf());
+    runtime.set_platform_window_state(width, height);
+    // re-read on every start so hot reload picks up a changed filter
+    let settings = parse_project_settings(project_root);
+    match settings.window_filter.as_deref() {
+        Some("linear") => runtime.set_default_nearest_neighbor_scaling(false),
+        Some("nearest") => runtime.set_default_nearest_neighbor_scaling(true),
+        _ => {}
+    }
+    runtime.set_storage_path(project_storage_path(project_root, &settings));
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| runtime.start())) {
+        Ok(Ok(())) => Ok(runtime),
+        Ok(Err(error)) => Err(format!(
+            "failed to start runtime:\n{}",
+            lua_error::describe_lua_error(&error)
+        )),
+        Err(payload) => Err(format!(
+            "runtime panicked during startup\nPanic: {}",
+            lua_error::describe_panic(payload.as_ref())
+        )),
+    }
+}
+
+fn watch_project_scripts(
+    project_root: &Path,
+    changes: Sender<()>,
+) -> Result<RecommendedWatcher, String> {
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        if !matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) {
+            return;
+        }
+        if event
+            .paths
+            .iter()
+            .any(|path| path.extension() == Some(OsStr::new("luau")))
+        {
+            let _ = changes.send(());
+        }
+    })
+    .map_err(|error| format!("failed to start file watcher: {error}"))?;
+    watcher
+        .watch(project_root, RecursiveMode::Recursive)
+        .map_err(|error| format!("failed to watch {}: {error}", project_root.display()))?;
+    Ok(watcher)
+}
+
+fn run_project_window(project_root: PathBuf, watch: bool) -> Result<(), String> {
     env::set_current_dir(&project_root).map_err(|error| {
         format!(
             "failed to set current directory to {}: {error}",
             project_root.display()
         )
     })?;
-    let (title, icon) = window_options_for_project(&project_root);
-    let mut runtime = window::Runtime::new(project_root);
-    runtime.set_platform_window_state(DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT);
-    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| runtime.start())) {
-        Ok(Ok(())) => {}
-        Ok(Err(error)) => {
-            return Err(format!(
-                "failed to start runtime:\n{}",
-                lua_error::describe_lua_error(&error)
-            ));
-        }
-        Err(payload) => {
-            return Err(format!(
-                "runtime panicked during startup\nPanic: {}",
-                lua_error::describe_panic(payload.as_ref())
-            ));
-        }
-    }
+    let window_options = window_options_for_project(&project_root);
+    let high_dpi = window_options.high_dpi;
+    let mut runtime = start_runtime(&project_root, window_options.width, window_options.height)?;
+
+    let (reload_sender, reload_requests): (Sender<()>, Receiver<()>) = channel();
+    let watcher = if watch {
+        let watcher = watch_project_scripts(&project_root, reload_sender)?;
+        println!("Watching {} for script changes.", project_root.display());
+        Some(watcher)
+    } else {
+        None
+    };
 
     let event_loop =
         catch_desktop_panic("failed to initialize the window event loop", EventLoop::new)?;
     let mut builder = WindowBuilder::new()
-        .with_title(title)
+        .with_title(window_options.title)
         .with_inner_size(LogicalSize::new(
-            DEFAULT_WINDOW_WIDTH as f64,
-            DEFAULT_WINDOW_HEIGHT as f64,
+            window_options.width as f64,
+            window_options.height as f64,
         ));
-    if let Some(icon) = icon {
+    if let Some(icon) = window_options.icon {
         builder = builder.with_window_icon(Some(icon));
     }
     let window = builder
         .build(&event_loop)
         .map(std::sync::Arc::new)
         .map_err(|error| format!("failed to create window: {error}"))?;
-    let size = window.inner_size();
-    runtime.set_platform_window_state(size.width as f32, size.height as f32);
-
-    let platform_state = runtime.platform_state();
-    let render_state = runtime.render_state();
-    let (mut presenter, _surface) = catch_desktop_panic(
-        "failed while initializing the Vulkan presenter",
-        || VulkanPresenter::new(&event_loop, window.clone()),
-    )?
-    .map_err(|error| format!("failed to initialize Vulkan: {error}"))?;
+    sync_window_scale(&runtime, &window, high_dpi);
+
+    let mut platform_state = runtime.platform_state();
+    let mut render_state = runtime.render_state();
+    let (mut presenter, _surface) =
+        catch_desktop_panic("failed while initializing the Vulkan presenter", || {
+            VulkanPresenter::new(
+                &event_loop,
+                window.clone(),
+                window_options.vsync,
+                window_options.msaa,
+            )
+        })?
+        .map_err(|error| format!("failed to initialize Vulkan: {error}"))?;
+
+    let mut gilrs = match Gilrs::new() {
+        Ok(gilrs) => Some(gilrs),
+        Err(error) => {
+            eprintln!("gamepad input unavailable: {error}");
+            None
+        }
+    };
 
     let mut last_update = Instant::now();
     let mut cursor_grab_warning_logged = false;
+    // app.screenshot requests waiting for the next frame the presenter renders
+    let mut pending_screenshots: Vec<ScreenshotRequest> = Vec::new();
     event_loop.run(move |event, _target, control_flow| {
+        // owned by the loop so the watcher lives as long as the window
+        let _ = &watcher;
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
             *control_flow = ControlFlow::Poll;
 
             match event {
                 Event::WindowEvent { event, .. } => match event {
                     WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                    WindowEvent::Resized(size) => {
-                        runtime.set_platform_window_state(size.width as f32, size.height as f32);
+                    WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
+                        sync_window_scale(&runtime, &window, high_dpi);
                         presenter.request_swapchain_recreate();
                     }
+                    WindowEvent::Focused(focused) => runtime.set_platform_focused(focused),
                     WindowEvent::CursorMoved { position, .. } => {
-                        runtime.set_platform_mouse_state(position.x as f32, position.y as f32);
+                        let scale = content_scale(&window, high_dpi);
+                        runtime.set_platform_mouse_state(
+                            (position.x / scale) as f32,
+                            (position.y / scale) as f32,
+                        );
                     }
                     WindowEvent::MouseInput { state, button, .. } => {
                         if let Err(error) = with_platform_state(
@@ -1358,15 +1635,8 @@ fn run_project_window(project_root: PathBuf) -> Result<(), String> {
                             |platform| {
                                 let name = mouse_button_name(button).to_string();
                                 match state {
-                                    ElementState::Pressed => {
-                                        if platform.input_mut().mouse_down.insert(name.clone()) {
-                                            platform.input_mut().mouse_pressed.insert(name);
-                                        }
-                                    }
-                                    ElementState::Released => {
-                                        platform.input_mut().mouse_down.remove(name.as_str());
-                                        platform.input_mut().mouse_released.insert(name);
-                                    }
+                                    ElementState::Pressed => platform.press_mouse_button(name),
+                                    ElementState::Released => platform.release_mouse_button(name),
                                 }
                             },
                         ) {
@@ -1386,13 +1656,35 @@ fn run_project_window(project_root: PathBuf) -> Result<(), String> {
                             exit_runtime_failure(control_flow, "Fatal Runtime Error:", &error);
                         }
                     }
+                    WindowEvent::Touch(Touch {
+                        id,
+                        location,
+                        phase,
+                        ..
+                    }) => {
+                        let scale = content_scale(&window, high_dpi);
+                        if let Err(error) = with_platform_state(
+                            &platform_state,
+                            "updating touch state",
+                            |platform| {
+                                platform.update_touch(
+                                    id,
+                                    (location.x / scale) as f32,
+                                    (location.y / scale) as f32,
+                                    touch_phase(phase),
+                                );
+                            },
+                        ) {
+                            exit_runtime_failure(control_flow, "Fatal Runtime Error:", &error);
+                        }
+                    }
                     WindowEvent::ReceivedCharacter(ch) => {
                         if !ch.is_control() {
                             if let Err(error) = with_platform_state(
                                 &platform_state,
                                 "recording text input",
                                 |platform| {
-                                    platform.input_mut().char_pressed = Some(ch.to_string());
+                                    platform.push_text_input(&ch.to_string());
                                 },
                             ) {
                                 exit_runtime_failure(control_flow, "Fatal Runtime Error:", &error);
@@ -1415,16 +1707,8 @@ fn run_project_window(project_root: PathBuf) -> Result<(), String> {
                                 |platform| {
                                     let name = name.to_string();
                                     match state {
-                                        ElementState::Pressed => {
-                                            if platform.input_mut().keys_down.insert(name.clone()) {
-                                                platform.input_mut().keys_pressed.insert(name.clone());
-                                            }
-                                            platform.input_mut().last_key_pressed = Some(name);
-                                        }
-                                        ElementState::Released => {
-                                            platform.input_mut().keys_down.remove(name.as_str());
-                                            platform.input_mut().keys_released.insert(name);
-                                        }
+                                        ElementState::Pressed => platform.press_key(name),
+                                        ElementState::Released => platform.release_key(name),
                                     }
                                 },
                             ) {
@@ -1435,10 +1719,41 @@ fn run_project_window(project_root: PathBuf) -> Result<(), String> {
                     _ => {}
                 },
                 Event::MainEventsCleared => {
+                    // several writes from one save collapse into a single reload
+                    if reload_requests.try_iter().count() > 0 {
+                        let (width, height) = game_window_size(&window, high_dpi);
+                        match start_runtime(&project_root, width, height) {
+                            Ok(reloaded) => {
+                                runtime = reloaded;
+                                sync_window_scale(&runtime, &window, high_dpi);
+                                platform_state = runtime.platform_state();
+                                render_state = runtime.render_state();
+                                println!("Reloaded {}", project_root.display());
+                            }
+                            Err(error) => report_runtime_failure(
+                                "Reload Failed (keeping previous version):",
+                                &error,
+                            ),
+                        }
+                    }
+
                     let update_start = Instant::now();
                     let dt = update_start.duration_since(last_update).as_secs_f32();
                     last_update = update_start;
 
+                    if let Some(gilrs) = gilrs.as_mut() {
+                        while gilrs.next_event().is_some() {}
+                        let gamepads = gamepad_snapshot(gilrs);
+                        if let Err(error) = with_platform_state(
+                            &platform_state,
+                            "updating gamepad state",
+                            |platform| platform.input_mut().gamepads = gamepads,
+                        ) {
+                            exit_runtime_failure(control_flow, "Fatal Runtime Error:", &error);
+                            return;
+                        }
+                    }
+
                     match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| runtime.update(dt)))
                     {
                         Ok(Ok(())) => {}
@@ -1459,8 +1774,8 @@ fn run_project_window(project_root: PathBuf) -> Result<(), String> {
                         }
                     }
 
-                    if runtime.exit_requested() {
-                        *control_flow = ControlFlow::Exit;
+                    if let Some(code) = runtime.exit_code() {
+                        *control_flow = ControlFlow::ExitWithCode(code);
                         return;
                     }
 
@@ -1484,6 +1799,24 @@ fn run_project_window(project_root: PathBuf) -> Result<(), String> {
                                 cursor_grab_warning_logged = false;
                             }
                             window.set_cursor_visible(!mouse_locked);
+
+                            let requests = platform.take_window_requests();
+                            if let Some(title) = requests.title {
+                                window.set_title(&title);
+                            }
+                            if let Some((width, height)) = requests.size {
+                                window
+                                    .set_inner_size(LogicalSize::new(width as f64, height as f64));
+                            }
+                            if let Some(fullscreen) = requests.fullscreen {
+                                window.set_fullscreen(
+                                    fullscreen.then_some(Fullscreen::Borderless(None)),
+                                );
+                            }
+                            if let Some(vsync) = requests.vsync {
+                                presenter.set_vsync(vsync);
+                            }
+                            pending_screenshots.extend(platform.take_screenshot_requests());
                             platform.begin_frame();
                         },
                     ) {
@@ -1503,14 +1836,31 @@ fn run_project_window(project_root: PathBuf) -> Result<(), String> {
                 }
                 Event::RedrawRequested(_) => {
                     let size = window.inner_size();
-                    if let Err(error) =
-                        presenter.render(&platform_state, &render_state, size.width, size.height)
-                    {
-                        exit_runtime_failure(
-                            control_flow,
-                            "Fatal Render Error:",
-                            &format!("Vulkan presenter failed: {error}"),
-                        );
+                    let capture = !pending_screenshots.is_empty();
+                    match presenter.render(
+                        &platform_state,
+                        &render_state,
+                        size.width,
+                        size.height,
+                        capture,
+                    ) {
+                        Ok(Some(frame)) => {
+                            let requests = std::mem::take(&mut pending_screenshots);
+                            window::save_screenshots(&platform_state, requests, Ok(&frame));
+                        }
+                        Ok(None) => {}
+                        Err(error) if capture => {
+                            // a failed readback shouldn't take the game down with it
+                            let requests = std::mem::take(&mut pending_screenshots);
+                            window::save_screenshots(&platform_state, requests, Err(error));
+                        }
+                        Err(error) => {
+                            exit_runtime_failure(
+                                control_flow,
+                                "Fatal Render Error:",
+                                &format!("Vulkan presenter failed: {error}"),
+                            );
+                        }
                     }
                 }
                 _ => {}
@@ -1547,6 +1897,8 @@ version = \"0.1.0\"
 [window]
 title = \"{}\"
 icon = \"assets/icon.png\"
+width = 1280
+height = 720
 
 [dependencies]
 ",
@@ -1615,8 +1967,8 @@ fn print_usage() {
     println!("NeoLOVE CLI");
     println!("Usage:");
     println!("  neolove new <project-name>");
-    println!("  neolove run [project-dir]");
-    println!("  neolove build [project-dir] [--webasm]");
+    println!("  neolove run [project-dir] [--watch]");
+    println!("  neolove build [project-dir] [--webasm | --out <dir>]");
     println!("  neolove api [project-dir]");
     println!("  neolove setup-path");
     println!("  neolove --help");
@@ -1674,7 +2026,7 @@ fn run_cli() -> Result<(), String> {
         if args.len() == 1 {
             let project_root = extract_embedded_project(&payload)
                 .map_err(|error| format!("failed to extract embedded project: {error}"))?;
-            return run_project_window(project_root);
+            return run_project_window(project_root, false);
         }
     }
 
@@ -1724,16 +2076,38 @@ fn run_cli() -> Result<(), String> {
             println!("To build the webasm package, run `neolove build --webasm`");
         }
         "run" => {
-            let project_root = resolve_target_project_root(args.get(2).map(String::as_str))?;
+            let mut project_arg: Option<&str> = None;
+            let mut watch = false;
+            for arg in &args[2..] {
+                if arg == "--watch" {
+                    watch = true;
+                } else if arg.starts_with('-') {
+                    return Err(format!("run failed: unrecognized option: {arg}"));
+                } else if project_arg.is_none() {
+                    project_arg = Some(arg);
+                } else {
+                    return Err("run failed: expected at most one project directory".to_string());
+                }
+            }
+
+            let project_root = resolve_target_project_root(project_arg)?;
             validate_project_root(&project_root).map_err(|error| format!("run failed: {error}"))?;
-            run_project_window(project_root).map_err(|error| format!("run failed: {error}"))?;
+            run_project_window(project_root, watch)
+                .map_err(|error| format!("run failed: {error}"))?;
         }
         "build" => {
             let mut project_arg: Option<&str> = None;
             let mut webasm = false;
-            for arg in &args[2..] {
+            let mut out_arg: Option<&str> = None;
+            let mut remaining = args[2..].iter();
+            while let Some(arg) = remaining.next() {
                 if arg == "--webasm" {
                     webasm = true;
+                } else if arg == "--out" {
+                    let Some(dir) = remaining.next() else {
+                        return Err("build failed: --out expects a directory".to_string());
+                    };
+                    out_arg = Some(dir);
                 } else if arg.starts_with('-') {
                     return Err(format!("build failed: unrecognized option: {arg}"));
                 } else if project_arg.is_none() {
@@ -1746,6 +2120,16 @@ fn run_cli() -> Result<(), String> {
             let project_root = resolve_target_project_root(project_arg)?;
             validate_project_root(&project_root)
                 .map_err(|error| format!("build failed: {error}"))?;
+            if webasm && out_arg.is_some() {
+                return Err("build failed: --out is not supported with --webasm".to_string());
+            }
+            let output_dir = out_arg
+                .map(|dir| {
+                    resolve_from_cwd(dir).map_err(|error| {
+                        format!("build failed: failed to resolve output directory '{dir}': {error}")
+                    })
+                })
+                .transpose()?;
 
             if webasm {
                 let (bundle_output, zip_output) =
@@ -1753,7 +2137,7 @@ fn run_cli() -> Result<(), String> {
                 println!("Built webasm bundle: {}", bundle_output.display());
                 println!("Built itch.io package: {}", zip_output.display());
             } else {
-                let output = build_executable(&project_root)
+                let output = build_executable(&project_root, output_dir.as_deref())
                     .map_err(|error| format!("build failed: {error}"))?;
                 println!("Built executable: {}", output.display());
             }
@@ -1,16 +1,24 @@
 mod assets;
 mod audio_system;
+mod camera;
 mod commands;
 mod core;
+mod draw;
+mod events;
 mod fs_module;
 pub mod hierarchy;
 mod http;
+mod json;
 mod lua_error;
 mod platform;
 mod prefabs;
+mod random;
 mod renderer;
 mod servers;
 mod shader;
+mod spatial_hash;
+mod storage;
+mod tween;
 mod user_input;
 pub mod window;
 
@@ -18,7 +26,7 @@ use std::env;
 use std::ffi::{c_char, c_void};
 use std::path::PathBuf;
 
-use crate::platform::SharedPlatformState;
+use crate::platform::{SharedPlatformState, TouchPhase};
 use crate::renderer::SoftwareRenderer;
 
 unsafe extern "C" {
@@ -34,6 +42,8 @@ unsafe extern "C" {
     fn neolove_web_now_seconds() -> f64;
     fn neolove_web_canvas_width() -> i32;
     fn neolove_web_canvas_height() -> i32;
+    fn neolove_web_device_pixel_ratio() -> f64;
+    fn neolove_web_has_focus() -> i32;
     fn neolove_web_mouse_x() -> f64;
     fn neolove_web_mouse_y() -> f64;
     fn neolove_web_mouse_button_state(index: i32, kind: i32) -> i32;
@@ -41,7 +51,8 @@ unsafe extern "C" {
     fn neolove_web_wheel_y() -> f64;
     fn neolove_web_key_state(name: *const c_char, kind: i32) -> i32;
     fn neolove_web_take_last_key(buffer: *mut c_char, capacity: i32) -> i32;
-    fn neolove_web_take_char(buffer: *mut c_char, capacity: i32) -> i32;
+    fn neolove_web_take_text(buffer: *mut c_char, capacity: i32) -> i32;
+    fn neolove_web_take_touch(out: *mut f64) -> i32;
     fn neolove_web_begin_frame();
     fn neolove_web_present_rgba(pixels: *const u8, width: i32, height: i32);
     fn neolove_web_report_status(message: *const c_char);
@@ -182,6 +193,11 @@ impl WebApp {
         let height = unsafe { neolove_web_canvas_height() }.max(1) as u32;
         self.runtime
             .set_platform_window_state(width as f32, height as f32);
+        // the canvas is sized in physical pixels, so game units match them
+        self.runtime
+            .set_platform_display_scale(unsafe { neolove_web_device_pixel_ratio() } as f32, 1.0);
+        self.runtime
+            .set_platform_focused(unsafe { neolove_web_has_focus() } != 0);
         self.runtime.set_platform_mouse_state(
             unsafe { neolove_web_mouse_x() } as f32,
             unsafe { neolove_web_mouse_y() } as f32,
@@ -208,9 +224,9 @@ impl WebApp {
             dt += self.frame_interval;
             self.frame_interval = 0.0;
         }
-        let clamped_dt = dt.clamp(0.0, 0.25) as f32;
+        let dt = dt as f32;
 
-        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.runtime.update(clamped_dt)))
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.runtime.update(dt)))
             .map_err(|payload| {
                 format!(
                     "runtime panicked during frame update: {}",
@@ -293,8 +309,20 @@ impl WebApp {
             platform.input_mut().last_key_pressed = Some(last_key);
         }
 
-        if let Some(ch) = take_bridge_string(neolove_web_take_char)? {
-            platform.input_mut().char_pressed = Some(ch);
+        if let Some(text) = take_bridge_string(neolove_web_take_text)? {
+            platform.push_text_input(&text);
+        }
+
+        let mut touch = [0.0f64; 3];
+        loop {
+            let phase = match unsafe { neolove_web_take_touch(touch.as_mut_ptr()) } {
+                0 => TouchPhase::Started,
+                1 => TouchPhase::Moved,
+                3 => TouchPhase::Ended,
+                4 => TouchPhase::Cancelled,
+                _ => break,
+            };
+            platform.update_touch(touch[0] as u64, touch[1] as f32, touch[2] as f32, phase);
         }
 
         Ok(())
@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -31,6 +32,60 @@ pub(crate) struct WindowState {
     pub height: f32,
 }
 
+// framebuffer pixels per logical point (`dpi`) and per game unit (`content`); the two only
+// match when `[window] high_dpi` lets games work in logical points
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct DisplayScale {
+    pub dpi: f32,
+    pub content: f32,
+}
+
+impl Default for DisplayScale {
+    fn default() -> Self {
+        Self {
+            dpi: 1.0,
+            content: 1.0,
+        }
+    }
+}
+
+// snapshot of one connected controller, refreshed by the platform backend every frame
+#[derive(Clone, Debug, Default)]
+pub(crate) struct GamepadState {
+    pub buttons_down: HashSet<String>,
+    pub axes: HashMap<String, f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TouchPhase {
+    Started,
+    Moved,
+    // still down, without moving since the previous frame
+    Stationary,
+    Ended,
+    Cancelled,
+}
+
+impl TouchPhase {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Started => "started",
+            Self::Moved => "moved",
+            Self::Stationary => "stationary",
+            Self::Ended => "ended",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TouchPoint {
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub phase: TouchPhase,
+}
+
 #[derive(Clone, Debug, Default)]
 pub(crate) struct InputState {
     pub keys_down: HashSet<String>,
@@ -39,22 +94,40 @@ pub(crate) struct InputState {
     pub mouse_down: HashSet<String>,
     pub mouse_pressed: HashSet<String>,
     pub mouse_released: HashSet<String>,
+    // presses claimed with input.consumeMousePress; hidden from isMousePressed for the frame
+    pub mouse_consumed: HashSet<String>,
     pub wheel_x: f32,
     pub wheel_y: f32,
     pub last_key_pressed: Option<String>,
     pub char_pressed: Option<String>,
+    // every character typed this frame, in order
+    pub text_input: String,
     pub mouse_locked: bool,
+    pub gamepads: Vec<GamepadState>,
+    // in the order the fingers went down; ended touches stay for the frame they lifted in
+    pub touches: Vec<TouchPoint>,
+}
+
+// window changes requested from Lua, applied and cleared by the platform backend
+#[derive(Clone, Debug, Default)]
+pub(crate) struct WindowRequests {
+    pub title: Option<String>,
+    pub size: Option<(f32, f32)>,
+    pub fullscreen: Option<bool>,
+    pub vsync: Option<bool>,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct FrameState {
     pub clear_color: Color,
+    pub clear_enabled: bool,
 }
 
 impl Default for FrameState {
     fn default() -> Self {
         Self {
             clear_color: Color::WHITE,
+            clear_enabled: true,
         }
     }
 }
@@ -63,8 +136,25 @@ impl Default for FrameState {
 pub(crate) struct PlatformState {
     mouse: MouseState,
     window: WindowState,
+    display_scale: DisplayScale,
     input: InputState,
     frame: FrameState,
+    window_requests: WindowRequests,
+    // false until the first cursor position arrives, so that one isn't reported as motion
+    mouse_tracked: bool,
+    // stored inverted so the derived default is a focused window
+    unfocused: bool,
+    // app.screenshot calls waiting for the next presented frame
+    screenshot_requests: Vec<ScreenshotRequest>,
+    // finished screenshots by request id, with the error for the ones that failed
+    screenshot_results: Vec<(u64, Result<(), String>)>,
+    next_screenshot_id: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ScreenshotRequest {
+    pub id: u64,
+    pub path: PathBuf,
 }
 
 impl PlatformState {
@@ -73,8 +163,12 @@ impl PlatformState {
     }
 
     pub(crate) fn set_mouse_position(&mut self, x: f32, y: f32) {
-        self.mouse.delta_x = x - self.mouse.x;
-        self.mouse.delta_y = y - self.mouse.y;
+        // several moves can arrive in one frame; the delta sums them until begin_frame
+        if self.mouse_tracked {
+            self.mouse.delta_x += x - self.mouse.x;
+            self.mouse.delta_y += y - self.mouse.y;
+        }
+        self.mouse_tracked = true;
         self.mouse.x = x;
         self.mouse.y = y;
     }
@@ -92,6 +186,51 @@ impl PlatformState {
         self.window = window;
     }
 
+    pub(crate) fn display_scale(&self) -> DisplayScale {
+        self.display_scale
+    }
+
+    pub(crate) fn set_display_scale(&mut self, display_scale: DisplayScale) {
+        self.display_scale = display_scale;
+    }
+
+    pub(crate) fn is_focused(&self) -> bool {
+        !self.unfocused
+    }
+
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        self.unfocused = !focused;
+    }
+
+    pub(crate) fn window_requests_mut(&mut self) -> &mut WindowRequests {
+        &mut self.window_requests
+    }
+
+    pub(crate) fn take_window_requests(&mut self) -> WindowRequests {
+        std::mem::take(&mut self.window_requests)
+    }
+
+    // returns the id the result is reported under
+    pub(crate) fn request_screenshot(&mut self, path: PathBuf) -> u64 {
+        let id = self.next_screenshot_id;
+        self.next_screenshot_id += 1;
+        self.screenshot_requests
+            .push(ScreenshotRequest { id, path });
+        id
+    }
+
+    pub(crate) fn take_screenshot_requests(&mut self) -> Vec<ScreenshotRequest> {
+        std::mem::take(&mut self.screenshot_requests)
+    }
+
+    pub(crate) fn finish_screenshot(&mut self, id: u64, result: Result<(), String>) {
+        self.screenshot_results.push((id, result));
+    }
+
+    pub(crate) fn take_screenshot_results(&mut self) -> Vec<(u64, Result<(), String>)> {
+        std::mem::take(&mut self.screenshot_results)
+    }
+
     pub(crate) fn input(&self) -> &InputState {
         &self.input
     }
@@ -100,6 +239,62 @@ impl PlatformState {
         &mut self.input
     }
 
+    pub(crate) fn press_key(&mut self, name: String) {
+        if self.input.keys_down.insert(name.clone()) {
+            self.input.keys_pressed.insert(name.clone());
+        }
+        self.input.last_key_pressed = Some(name);
+    }
+
+    pub(crate) fn push_text_input(&mut self, text: &str) {
+        self.input.text_input.push_str(text);
+        if let Some(last) = text.chars().last() {
+            self.input.char_pressed = Some(last.to_string());
+        }
+    }
+
+    pub(crate) fn release_key(&mut self, name: String) {
+        self.input.keys_down.remove(name.as_str());
+        self.input.keys_released.insert(name);
+    }
+
+    pub(crate) fn press_mouse_button(&mut self, name: String) {
+        if self.input.mouse_down.insert(name.clone()) {
+            self.input.mouse_pressed.insert(name);
+        }
+    }
+
+    pub(crate) fn release_mouse_button(&mut self, name: String) {
+        self.input.mouse_down.remove(name.as_str());
+        self.input.mouse_released.insert(name);
+    }
+
+    pub(crate) fn update_touch(&mut self, id: u64, x: f32, y: f32, phase: TouchPhase) {
+        let touches = &mut self.input.touches;
+        let Some(touch) = touches.iter_mut().find(|touch| touch.id == id) else {
+            // a move or end for an unknown id means its start was missed; treat it as new
+            touches.push(TouchPoint {
+                id,
+                x,
+                y,
+                phase: match phase {
+                    TouchPhase::Moved | TouchPhase::Stationary => TouchPhase::Started,
+                    other => other,
+                },
+            });
+            return;
+        };
+        touch.x = x;
+        touch.y = y;
+        // a touch that starts and moves within one frame still reports "started" first
+        touch.phase = match (touch.phase, phase) {
+            (TouchPhase::Started, TouchPhase::Moved | TouchPhase::Stationary) => {
+                TouchPhase::Started
+            }
+            (_, phase) => phase,
+        };
+    }
+
     pub(crate) fn clear_color(&self) -> Color {
         self.frame.clear_color
     }
@@ -108,15 +303,31 @@ impl PlatformState {
         self.frame.clear_color = color;
     }
 
+    pub(crate) fn clear_enabled(&self) -> bool {
+        self.frame.clear_enabled
+    }
+
+    pub(crate) fn set_clear_enabled(&mut self, enabled: bool) {
+        self.frame.clear_enabled = enabled;
+    }
+
     pub(crate) fn begin_frame(&mut self) {
         self.input.keys_pressed.clear();
         self.input.keys_released.clear();
         self.input.mouse_pressed.clear();
         self.input.mouse_released.clear();
+        self.input.mouse_consumed.clear();
         self.input.wheel_x = 0.0;
         self.input.wheel_y = 0.0;
         self.input.last_key_pressed = None;
         self.input.char_pressed = None;
+        self.input.text_input.clear();
+        self.input
+            .touches
+            .retain(|touch| !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled));
+        for touch in &mut self.input.touches {
+            touch.phase = TouchPhase::Stationary;
+        }
         self.reset_mouse_delta();
     }
 }
@@ -269,6 +269,28 @@ fn create_entity_shells_recursive(
     Ok(entity)
 }
 
+// copies run `awake` like freshly added components, then get the copied fields back so the
+// defaults awake assigns don't wipe the state being duplicated
+fn awake_copied_component(entity: &Table, component: &Table) -> mlua::Result<()> {
+    let Ok(awake) = component.get::<Function>("awake") else {
+        return Ok(());
+    };
+    let copied = component
+        .pairs::<Value, Value>()
+        .collect::<mlua::Result<Vec<_>>>()?;
+    let component_name = component
+        .get::<String>("__neolove_component")
+        .unwrap_or_else(|_| "prefab component".to_string());
+    protect_lua_call(
+        &format!("running component awake callback ({component_name})"),
+        || awake.call::<()>((entity.clone(), component.clone())),
+    )?;
+    for (key, value) in copied {
+        component.raw_set(key, value)?;
+    }
+    Ok(())
+}
+
 fn apply_entity_state_recursive(
     lua: &Lua,
     source: &Table,
@@ -302,6 +324,7 @@ fn apply_entity_state_recursive(
             let component = clone_table_value(lua, &component?, state)?;
             component.raw_set("entity", entity.clone())?;
             crate::window::attach_component_methods(lua, &component)?;
+            awake_copied_component(&entity, &component)?;
             components.push(component)?;
         }
     }
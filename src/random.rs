@@ -0,0 +1,153 @@
+use mlua::Lua;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// SplitMix64: tiny and fully specified here, so a seed gives the same sequence on every
+// platform and engine version (replays and procedural levels depend on that)
+#[derive(Clone, Debug)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or(0);
+        Self::new(nanos)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in [0, 1)
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // uniform in [min, max], both inclusive
+    pub(crate) fn next_int(&mut self, min: i64, max: i64) -> i64 {
+        let span = max.wrapping_sub(min) as u64 as u128 + 1;
+        let offset = (self.next_u64() as u128 * span) >> 64;
+        min.wrapping_add(offset as i64)
+    }
+}
+
+pub(crate) type SharedRng = Rc<RefCell<Rng>>;
+
+pub(crate) fn new_shared_rng() -> SharedRng {
+    Rc::new(RefCell::new(Rng::from_time()))
+}
+
+pub(crate) fn add_random_module(lua: &Lua, rng: SharedRng) -> mlua::Result<()> {
+    let module = lua.create_table()?;
+
+    {
+        let rng = rng.clone();
+        module.set(
+            "seed",
+            lua.create_function(move |_lua, seed: i64| {
+                *rng.borrow_mut() = Rng::new(seed as u64);
+                Ok(())
+            })?,
+        )?;
+    }
+
+    {
+        let rng = rng.clone();
+        module.set(
+            "int",
+            lua.create_function(move |_lua, (min, max): (i64, i64)| {
+                if min > max {
+                    return Err(mlua::Error::external(format!(
+                        "random.int expects min <= max, got {min} and {max}"
+                    )));
+                }
+                Ok(rng.borrow_mut().next_int(min, max))
+            })?,
+        )?;
+    }
+
+    {
+        let rng = rng.clone();
+        module.set(
+            "float",
+            lua.create_function(move |_lua, ()| Ok(rng.borrow_mut().next_f64()))?,
+        )?;
+    }
+
+    {
+        let rng = rng.clone();
+        module.set(
+            "range",
+            lua.create_function(move |_lua, (a, b): (f64, f64)| {
+                if !a.is_finite() || !b.is_finite() {
+                    return Err(mlua::Error::external("random.range expects finite numbers"));
+                }
+                Ok(a + (b - a) * rng.borrow_mut().next_f64())
+            })?,
+        )?;
+    }
+
+    lua.globals().set("random", module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeding_restarts_the_same_sequence() -> mlua::Result<()> {
+        let lua = Lua::new();
+        add_random_module(&lua, new_shared_rng())?;
+
+        let (first, second): (String, String) = lua
+            .load(
+                r#"
+                local function roll()
+                    local values = {}
+                    for _ = 1, 20 do
+                        table.insert(values, random.int(1, 6))
+                        table.insert(values, random.range(-5, 5))
+                    end
+                    return table.concat(values, ",")
+                end
+                random.seed(42)
+                local first = roll()
+                random.seed(42)
+                return first, roll()
+                "#,
+            )
+            .eval()?;
+        assert_eq!(first, second);
+
+        assert!(lua.load("random.int(3, 1)").exec().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn values_stay_inside_their_ranges() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let value = rng.next_int(-2, 2);
+            assert!((-2..=2).contains(&value));
+            let float = rng.next_f64();
+            assert!((0.0..1.0).contains(&float));
+        }
+        assert_eq!(rng.next_int(i64::MIN, i64::MIN), i64::MIN);
+        // the full i64 range must not overflow the span
+        rng.next_int(i64::MIN, i64::MAX);
+    }
+}
@@ -5,7 +5,7 @@ use crate::platform::{Color, SharedPlatformState};
 use fontdue::Font;
 use image::{ImageBuffer, Rgba, RgbaImage};
 use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
 
 #[derive(Clone, Copy, Debug, Default)]
 pub(crate) struct Vec2 {
@@ -27,6 +27,31 @@ pub(crate) enum TextureFilter {
     Linear,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) enum BlendMode {
+    #[default]
+    Alpha,
+    // adds the source color weighted by its alpha; for glows and particles
+    Additive,
+    // overwrites the destination pixels, alpha included
+    None,
+    // alpha blending for colors already multiplied by their alpha; premultiplied images
+    // use it automatically in place of Alpha
+    Premultiplied,
+}
+
+impl BlendMode {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "alpha" => Some(Self::Alpha),
+            "additive" | "add" => Some(Self::Additive),
+            "none" | "replace" => Some(Self::None),
+            "premultiplied" => Some(Self::Premultiplied),
+            _ => None,
+        }
+    }
+}
+
 const DEFAULT_FONT_CACHE_KEY: &str = "__neolove_default_font__";
 const DEFAULT_FONT_BYTES: &[u8] =
     include_bytes!("../samples/new_features_test/assets/fonts/ProggyClean.ttf");
@@ -95,6 +120,8 @@ pub(crate) struct TextMetrics {
     pub height: f32,
     pub used_scale: f32,
     pub line_count: usize,
+    // distance from the top of the bounds to the first line's baseline
+    pub baseline: f32,
 }
 
 #[derive(Clone, Debug)]
@@ -129,12 +156,22 @@ pub(crate) enum DrawCommand {
         filter: TextureFilter,
     },
     Text(TextRenderRequest),
+    // applies to every later command until the next SetBlend; each frame starts at Alpha
+    SetBlend(BlendMode),
 }
 
 #[derive(Default)]
 pub(crate) struct RenderState {
     commands: Vec<DrawCommand>,
     overlay_commands: Vec<DrawCommand>,
+    target: Option<RenderTarget>,
+}
+
+// set by app.setRenderTarget: everything queued meanwhile is drawn into `image` instead
+pub(crate) struct RenderTarget {
+    pub image: ImageHandle,
+    pub clear: bool,
+    pub commands: Vec<DrawCommand>,
 }
 
 pub(crate) type SharedRenderState = Arc<Mutex<RenderState>>;
@@ -154,11 +191,48 @@ pub(crate) struct RasterizedTextSprite {
 
 impl RenderState {
     pub(crate) fn queue(&mut self, command: DrawCommand) {
-        self.commands.push(command);
+        match self.target.as_mut() {
+            Some(target) => target.commands.push(command),
+            None => self.commands.push(command),
+        }
+    }
+
+    // returns the previous target so the caller can draw it once the lock is released
+    pub(crate) fn set_target(&mut self, image: ImageHandle, clear: bool) -> Option<RenderTarget> {
+        self.target.replace(RenderTarget {
+            image,
+            clear,
+            commands: Vec::new(),
+        })
+    }
+
+    pub(crate) fn take_target(&mut self) -> Option<RenderTarget> {
+        self.target.take()
+    }
+
+    pub(crate) fn command_count(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub(crate) fn commands_since_mut(&mut self, start: usize) -> &mut [DrawCommand] {
+        let start = start.min(self.commands.len());
+        &mut self.commands[start..]
+    }
+
+    pub(crate) fn overlay_command_count(&self) -> usize {
+        self.overlay_commands.len()
+    }
+
+    pub(crate) fn overlay_commands_since_mut(&mut self, start: usize) -> &mut [DrawCommand] {
+        let start = start.min(self.overlay_commands.len());
+        &mut self.overlay_commands[start..]
     }
 
     pub(crate) fn extend_overlay(&mut self, commands: Vec<DrawCommand>) {
-        self.overlay_commands.extend(commands);
+        match self.target.as_mut() {
+            Some(target) => target.commands.extend(commands),
+            None => self.overlay_commands.extend(commands),
+        }
     }
 
     pub(crate) fn drain(&mut self) -> Vec<DrawCommand> {
@@ -168,11 +242,31 @@ impl RenderState {
     }
 }
 
-fn font_cache() -> &'static Mutex<HashMap<String, Arc<Font>>> {
-    static CACHE: OnceLock<Mutex<HashMap<String, Arc<Font>>>> = OnceLock::new();
+// fonts the renderer read itself stay for the whole run; fonts from assets.loadFont only while
+// a handle still holds them
+enum CachedFont {
+    Owned(Arc<Font>),
+    Registered(Weak<Font>),
+}
+
+impl CachedFont {
+    fn get(&self) -> Option<Arc<Font>> {
+        match self {
+            Self::Owned(font) => Some(font.clone()),
+            Self::Registered(font) => font.upgrade(),
+        }
+    }
+}
+
+fn font_cache() -> &'static Mutex<HashMap<String, CachedFont>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, CachedFont>>> = OnceLock::new();
     CACHE.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
+fn cached_font(key: &str) -> Option<Arc<Font>> {
+    font_cache().lock().ok()?.get(key)?.get()
+}
+
 fn font_warning_cache() -> &'static Mutex<HashSet<String>> {
     static CACHE: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
     CACHE.get_or_init(|| Mutex::new(HashSet::new()))
@@ -186,6 +280,18 @@ fn warn_font_once(key: &str, message: impl FnOnce() -> String) {
     }
 }
 
+// lets fonts parsed elsewhere (assets.loadFont) be reused by path without re-reading them
+pub(crate) fn register_font(path: String, font: &Arc<Font>) {
+    if let Ok(mut cache) = font_cache().lock() {
+        // evict fonts whose handles were all dropped
+        cache.retain(|_, cached| match cached {
+            CachedFont::Owned(_) => true,
+            CachedFont::Registered(font) => font.strong_count() > 0,
+        });
+        cache.insert(path, CachedFont::Registered(Arc::downgrade(font)));
+    }
+}
+
 fn load_font(source: &FontHandle) -> Option<Arc<Font>> {
     let cache_key = match source {
         FontHandle::Default => DEFAULT_FONT_CACHE_KEY.to_string(),
@@ -193,10 +299,8 @@ fn load_font(source: &FontHandle) -> Option<Arc<Font>> {
         FontHandle::Path(_) => DEFAULT_FONT_CACHE_KEY.to_string(),
     };
 
-    if let Ok(cache) = font_cache().lock() {
-        if let Some(font) = cache.get(&cache_key) {
-            return Some(font.clone());
-        }
+    if let Some(font) = cached_font(&cache_key) {
+        return Some(font);
     }
 
     let font = match source {
@@ -238,11 +342,28 @@ fn load_font(source: &FontHandle) -> Option<Arc<Font>> {
         }
     };
     if let Ok(mut cache) = font_cache().lock() {
-        cache.insert(cache_key, font.clone());
+        cache.insert(cache_key, CachedFont::Owned(font.clone()));
     }
     Some(font)
 }
 
+// rasterizes a finished render target into its image in software, so it works the same on
+// every backend and the result can be drawn by Image2D like any other image
+pub(crate) fn draw_render_target(target: RenderTarget) -> Result<(), String> {
+    let base = if target.clear {
+        let (width, height) = target.image.dimensions().map_err(|e| e.to_string())?;
+        RgbaImage::new(width, height)
+    } else {
+        target.image.clone_rgba_image().map_err(|e| e.to_string())?
+    };
+    let mut renderer = SoftwareRenderer::from_image(base);
+    renderer.draw_commands(target.commands)?;
+    target
+        .image
+        .replace_pixels(renderer.into_image())
+        .map_err(|e| e.to_string())
+}
+
 pub(crate) fn drain_commands(render_state: &SharedRenderState) -> Result<Vec<DrawCommand>, String> {
     render_state
         .lock()
@@ -607,6 +728,7 @@ fn prepare_text_layout(request: &TextRenderRequest) -> Option<PreparedTextLayout
             .unwrap_or(block_height),
         used_scale,
         line_count: lines.len(),
+        baseline: start_y - request.bounds.y + line_metrics.ascent,
     };
     if request.stretch_width > 0.0 && request.stretch_height > 0.0 {
         metrics.width = request.stretch_width;
@@ -706,6 +828,29 @@ fn blend(dest: &mut [u8], src: Color) {
         .clamp(0.0, 255.0) as u8;
 }
 
+// matches the GPU additive pipeline: rgb += src.rgb * src.a, destination alpha is kept
+fn blend_additive(dest: &mut [u8], src: Color) {
+    let src_a = src.a as f32 / 255.0;
+    dest[0] = (dest[0] as f32 + src.r as f32 * src_a).round().min(255.0) as u8;
+    dest[1] = (dest[1] as f32 + src.g as f32 * src_a).round().min(255.0) as u8;
+    dest[2] = (dest[2] as f32 + src.b as f32 * src_a).round().min(255.0) as u8;
+}
+
+// matches the GPU premultiplied pipeline: rgb = src.rgb + dest.rgb * (1 - src.a)
+fn blend_premultiplied(dest: &mut [u8], src: Color) {
+    let inv = 1.0 - src.a as f32 / 255.0;
+    dest[0] = (src.r as f32 + dest[0] as f32 * inv).round().min(255.0) as u8;
+    dest[1] = (src.g as f32 + dest[1] as f32 * inv).round().min(255.0) as u8;
+    dest[2] = (src.b as f32 + dest[2] as f32 * inv).round().min(255.0) as u8;
+    dest[3] = (src.a as f32 + dest[3] as f32 * inv).round().min(255.0) as u8;
+}
+
+// premultiplied pixels need a premultiplied tint too, or a faded tint would only fade alpha
+pub(crate) fn premultiply_color(color: Color) -> Color {
+    let scale = |channel: u8| ((channel as u16 * color.a as u16 + 127) / 255) as u8;
+    Color::rgba(scale(color.r), scale(color.g), scale(color.b), color.a)
+}
+
 fn rotate_local(x: f32, y: f32, rotation: f32) -> (f32, f32) {
     let cos_r = rotation.cos();
     let sin_r = rotation.sin();
@@ -841,6 +986,9 @@ pub(crate) fn command_intersects_viewport(command: &DrawCommand, width: u32, hei
                 request.rotation,
             ))
         }
+        DrawCommand::SetBlend(_) => {
+            return true;
+        }
     };
 
     rect_intersects_viewport(bounds, width, height)
@@ -850,6 +998,7 @@ pub(crate) struct SoftwareRenderer {
     width: u32,
     height: u32,
     pixels: Vec<u8>,
+    blend_mode: BlendMode,
 }
 
 impl SoftwareRenderer {
@@ -858,6 +1007,7 @@ impl SoftwareRenderer {
             width: width.max(1),
             height: height.max(1),
             pixels: vec![0; width.max(1) as usize * height.max(1) as usize * 4],
+            blend_mode: BlendMode::Alpha,
         }
     }
 
@@ -872,26 +1022,50 @@ impl SoftwareRenderer {
         &self.pixels
     }
 
+    pub(crate) fn from_image(image: RgbaImage) -> Self {
+        let (width, height) = image.dimensions();
+        let mut renderer = Self::new(width, height);
+        if width > 0 && height > 0 {
+            renderer.pixels = image.into_raw();
+        }
+        renderer
+    }
+
+    pub(crate) fn into_image(self) -> RgbaImage {
+        RgbaImage::from_raw(self.width, self.height, self.pixels)
+            .unwrap_or_else(|| RgbaImage::new(1, 1))
+    }
+
     pub(crate) fn render(
         &mut self,
         platform: &SharedPlatformState,
         render_state: &SharedRenderState,
     ) -> Result<(), String> {
-        let clear = platform
-            .lock()
-            .map_err(|_| "platform lock poisoned".to_string())?
-            .clear_color();
-        for pixel in self.pixels.chunks_exact_mut(4) {
-            pixel[0] = clear.r;
-            pixel[1] = clear.g;
-            pixel[2] = clear.b;
-            pixel[3] = clear.a;
+        let (clear, clear_enabled) = {
+            let platform = platform
+                .lock()
+                .map_err(|_| "platform lock poisoned".to_string())?;
+            (platform.clear_color(), platform.clear_enabled())
+        };
+        // with clearing off the previous frame stays underneath
+        if clear_enabled {
+            for pixel in self.pixels.chunks_exact_mut(4) {
+                pixel[0] = clear.r;
+                pixel[1] = clear.g;
+                pixel[2] = clear.b;
+                pixel[3] = clear.a;
+            }
         }
 
         let commands = render_state
             .lock()
             .map_err(|_| "render state lock poisoned".to_string())?
             .drain();
+        self.draw_commands(commands)
+    }
+
+    pub(crate) fn draw_commands(&mut self, commands: Vec<DrawCommand>) -> Result<(), String> {
+        self.blend_mode = BlendMode::Alpha;
         for command in commands {
             if !command_intersects_viewport(&command, self.width, self.height) {
                 continue;
@@ -935,8 +1109,12 @@ impl SoftwareRenderer {
                 pivot,
                 tint,
                 filter,
-            } => self.draw_image(image, dest, source, rotation, pivot, tint, filter)?,
+            } => {
+                let filter = image.filter_override().unwrap_or(filter);
+                self.draw_image(image, dest, source, rotation, pivot, tint, filter)?
+            }
             DrawCommand::Text(request) => self.draw_text(&request)?,
+            DrawCommand::SetBlend(mode) => self.blend_mode = mode,
         }
         Ok(())
     }
@@ -1010,6 +1188,12 @@ impl SoftwareRenderer {
         filter: TextureFilter,
     ) -> Result<(), String> {
         let (img_w, img_h) = image.dimensions().map_err(|e| e.to_string())?;
+        let premultiplied = image.is_premultiplied() && self.blend_mode == BlendMode::Alpha;
+        let tint = if premultiplied {
+            premultiply_color(tint)
+        } else {
+            tint
+        };
         let source = source.unwrap_or(Rect {
             x: 0.0,
             y: 0.0,
@@ -1047,30 +1231,33 @@ impl SoftwareRenderer {
             .ceil()
             .min(self.height as f32 - 1.0) as i32;
 
-        image
-            .with_image(|source_image| {
-                for py in min_y..=max_y {
-                    for px in min_x..=max_x {
-                        let local_x = px as f32 + 0.5 - pivot.x;
-                        let local_y = py as f32 + 0.5 - pivot.y;
-                        let (rx, ry) = inverse_rotate(local_x, local_y, rotation);
-                        let image_x = rx + pivot.x;
-                        let image_y = ry + pivot.y;
-                        let u = (image_x - dest.x) / dest.w;
-                        let v = (image_y - dest.y) / dest.h;
-                        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
-                            continue;
-                        }
-                        let src_x = source.x + source.w * u;
-                        let src_y = source.y + source.h * v;
-                        let sample = sample_rgba(source_image, src_x, src_y, filter);
-                        let color = modulate(sample, tint);
-                        self.put_pixel(px as u32, py as u32, color);
+        let blend_mode = self.blend_mode;
+        if premultiplied {
+            self.blend_mode = BlendMode::Premultiplied;
+        }
+        let drawn = image.with_image(|source_image| {
+            for py in min_y..=max_y {
+                for px in min_x..=max_x {
+                    let local_x = px as f32 + 0.5 - pivot.x;
+                    let local_y = py as f32 + 0.5 - pivot.y;
+                    let (rx, ry) = inverse_rotate(local_x, local_y, rotation);
+                    let image_x = rx + pivot.x;
+                    let image_y = ry + pivot.y;
+                    let u = (image_x - dest.x) / dest.w;
+                    let v = (image_y - dest.y) / dest.h;
+                    if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+                        continue;
                     }
+                    let src_x = source.x + source.w * u;
+                    let src_y = source.y + source.h * v;
+                    let sample = sample_rgba(source_image, src_x, src_y, filter);
+                    let color = modulate(sample, tint);
+                    self.put_pixel(px as u32, py as u32, color);
                 }
-            })
-            .map_err(|e| e.to_string())?;
-        Ok(())
+            }
+        });
+        self.blend_mode = blend_mode;
+        drawn.map_err(|e| e.to_string())
     }
 
     fn draw_text(&mut self, request: &TextRenderRequest) -> Result<(), String> {
@@ -1094,7 +1281,13 @@ impl SoftwareRenderer {
             return;
         }
         let index = ((y * self.width + x) * 4) as usize;
-        blend(&mut self.pixels[index..index + 4], color);
+        let dest = &mut self.pixels[index..index + 4];
+        match self.blend_mode {
+            BlendMode::Alpha => blend(dest, color),
+            BlendMode::Additive => blend_additive(dest, color),
+            BlendMode::None => dest.copy_from_slice(&[color.r, color.g, color.b, color.a]),
+            BlendMode::Premultiplied => blend_premultiplied(dest, color),
+        }
     }
 }
 
@@ -1109,8 +1302,10 @@ fn sample_rgba(image: &RgbaImage, x: f32, y: f32, filter: TextureFilter) -> Colo
             Color::rgba(r, g, b, a)
         }
         TextureFilter::Linear => {
-            let x0 = x.floor();
-            let y0 = y.floor();
+            let x0 = x.floor().clamp(0.0, image.width().saturating_sub(1) as f32);
+            let y0 = y
+                .floor()
+                .clamp(0.0, image.height().saturating_sub(1) as f32);
             let x1 = (x0 + 1.0).min(image.width().saturating_sub(1) as f32);
             let y1 = (y0 + 1.0).min(image.height().saturating_sub(1) as f32);
             let tx = (x - x0).clamp(0.0, 1.0);
@@ -1182,4 +1377,134 @@ mod tests {
             600
         ));
     }
+
+    #[test]
+    fn dropped_registered_fonts_are_evicted() {
+        let parse = || {
+            Arc::new(
+                Font::from_bytes(DEFAULT_FONT_BYTES, fontdue::FontSettings::default()).unwrap(),
+            )
+        };
+        let font = parse();
+        register_font("evicted_font_test/a.ttf".to_string(), &font);
+        assert!(cached_font("evicted_font_test/a.ttf").is_some());
+
+        drop(font);
+        assert!(cached_font("evicted_font_test/a.ttf").is_none());
+        let other = parse();
+        register_font("evicted_font_test/b.ttf".to_string(), &other);
+        assert!(
+            !font_cache()
+                .lock()
+                .unwrap()
+                .contains_key("evicted_font_test/a.ttf")
+        );
+        assert!(cached_font("evicted_font_test/b.ttf").is_some());
+    }
+
+    #[test]
+    fn blend_modes_change_how_pixels_combine() {
+        // wider than the 1x1 target so the pixel center stays off the rect's diagonal
+        let rect = |color: Color| DrawCommand::Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 3.0,
+            h: 1.0,
+            rotation: 0.0,
+            offset: Vec2::default(),
+            color,
+        };
+        let half_red = Color::rgba(255, 0, 0, 128);
+        let mut renderer =
+            SoftwareRenderer::from_image(RgbaImage::from_pixel(1, 1, Rgba([0, 100, 0, 255])));
+        renderer
+            .draw_commands(vec![
+                DrawCommand::SetBlend(BlendMode::Additive),
+                rect(half_red),
+            ])
+            .unwrap();
+        assert_eq!(renderer.pixels(), &[128, 100, 0, 255]);
+
+        renderer
+            .draw_commands(vec![DrawCommand::SetBlend(BlendMode::None), rect(half_red)])
+            .unwrap();
+        assert_eq!(renderer.pixels(), &[255, 0, 0, 128]);
+
+        // every batch of commands starts over with alpha blending
+        renderer.draw_commands(vec![rect(half_red)]).unwrap();
+        assert_eq!(renderer.pixels()[3], 192);
+        assert_eq!(BlendMode::parse(" Additive "), Some(BlendMode::Additive));
+        assert_eq!(BlendMode::parse("multiply"), None);
+    }
+
+    #[test]
+    fn premultiplied_images_blend_like_straight_alpha() -> mlua::Result<()> {
+        let draw = |image: ImageHandle| {
+            let mut renderer =
+                SoftwareRenderer::from_image(RgbaImage::from_pixel(1, 1, Rgba([0, 100, 0, 255])));
+            renderer
+                .draw_commands(vec![DrawCommand::Image {
+                    image,
+                    dest: Rect {
+                        x: 0.0,
+                        y: 0.0,
+                        w: 3.0,
+                        h: 1.0,
+                    },
+                    source: None,
+                    rotation: 0.0,
+                    pivot: Vec2::default(),
+                    tint: Color::WHITE,
+                    filter: TextureFilter::Nearest,
+                }])
+                .unwrap();
+            renderer.pixels().to_vec()
+        };
+        let half_red =
+            || ImageHandle::from_rgba_image(RgbaImage::from_pixel(1, 1, Rgba([255, 0, 0, 128])));
+
+        let straight = draw(half_red());
+        let premultiplied = half_red();
+        premultiplied.premultiply_alpha()?;
+        // a second call must not darken the pixels again
+        premultiplied.premultiply_alpha()?;
+        assert_eq!(premultiplied.sample_rgba(0, 0)?, [128, 0, 0, 128]);
+        assert_eq!(draw(premultiplied), straight);
+        Ok(())
+    }
+
+    #[test]
+    fn negative_source_extent_mirrors_image() {
+        let mut pixels = RgbaImage::new(2, 1);
+        pixels.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        pixels.put_pixel(1, 0, Rgba([0, 0, 255, 255]));
+        let image = ImageHandle::from_rgba_image(pixels);
+
+        let platform = crate::platform::new_shared_platform_state();
+        let render_state = new_shared_render_state();
+        render_state.lock().unwrap().queue(DrawCommand::Image {
+            image,
+            dest: Rect {
+                x: 0.0,
+                y: 0.0,
+                w: 2.0,
+                h: 1.0,
+            },
+            source: Some(Rect {
+                x: 2.0,
+                y: 0.0,
+                w: -2.0,
+                h: 1.0,
+            }),
+            rotation: 0.0,
+            pivot: Vec2::default(),
+            tint: Color::WHITE,
+            filter: TextureFilter::Nearest,
+        });
+
+        let mut renderer = SoftwareRenderer::new(2, 1);
+        renderer.render(&platform, &render_state).unwrap();
+        assert_eq!(&renderer.pixels()[0..4], &[0, 0, 255, 255]);
+        assert_eq!(&renderer.pixels()[4..8], &[255, 0, 0, 255]);
+    }
 }
@@ -21,7 +21,10 @@ mod native {
     use std::time::{Duration, Instant};
     use uuid::Uuid;
 
-    use crate::lua_error::{describe_lua_error, protect_lua_call};
+    use crate::lua_error::{
+        describe_lua_error, flush_lua_error_reports, format_lua_error_report,
+        new_shared_error_debouncer, protect_lua_call, report_lua_error,
+    };
 
     const MAX_HTTP_HEADER_SIZE: usize = 64 * 1024;
     const MAX_HTTP_BODY_SIZE: usize = 16 * 1024 * 1024;
@@ -1135,10 +1138,7 @@ mod native {
         if let Err(error) = protect_lua_call("polling server-side HTTP callbacks", || {
             poll.call::<()>(())
         }) {
-            eprintln!(
-                "\x1b[31mLua Error:\x1b[0m Failed to poll server-side HTTP callbacks\n{}",
-                describe_lua_error(&error)
-            );
+            report_lua_error(lua, "server-side HTTP callback polling", &error);
         }
     }
 
@@ -1227,6 +1227,7 @@ mod native {
 
             let require = lua.create_require_function(TextRequirer::new())?;
             lua.globals().set("require", require)?;
+            let error_debouncer = new_shared_error_debouncer(&lua);
 
             crate::fs_module::add_fs_module(&lua, env_root.clone())?;
             crate::http::add_http_module(&lua)?;
@@ -1247,6 +1248,7 @@ mod native {
 
             loop {
                 poll_http_callbacks(&lua);
+                flush_lua_error_reports(&error_debouncer, Instant::now());
 
                 if shared.stop_flag.load(Ordering::SeqCst) {
                     break;
@@ -1264,10 +1266,7 @@ mod native {
                                 "running server callback",
                                 || callback.call::<()>((event.client_key.clone(), payload.clone())),
                             ) {
-                                eprintln!(
-                                    "\x1b[31mLua Error in server callback:\x1b[0m\n{}",
-                                    describe_lua_error(&error)
-                                );
+                                report_lua_error(&lua, "server callback", &error);
                             }
                         }
                     }
@@ -1282,7 +1281,7 @@ mod native {
         if let Err(error) = runtime {
             let display = describe_lua_error(&error);
             let _ = startup_sender.send(Err(display.clone()));
-            eprintln!("\x1b[31mLua Error in hosted server:\x1b[0m\n{display}");
+            eprintln!("{}", format_lua_error_report("hosted server", &error));
         }
 
         stop_hosted_server(&shared, "server stopped");
@@ -2112,10 +2111,7 @@ mod native {
                                     "running server client callback",
                                     || callback.call::<()>(payload.clone()),
                                 ) {
-                                    eprintln!(
-                                        "\x1b[31mLua Error in server client callback:\x1b[0m\n{}",
-                                        describe_lua_error(&error)
-                                    );
+                                    report_lua_error(lua, "server client callback", &error);
                                 }
                             }
                         }
@@ -0,0 +1,157 @@
+use crate::hierarchy::EntityId;
+use std::collections::HashMap;
+
+// world units per grid cell; typical sprites and colliders touch one to four cells
+const CELL_SIZE: f32 = 128.0;
+
+// entities spanning more cells than this (backgrounds, level-sized triggers) stay out of the
+// grid and are tested against every query instead
+const MAX_CELLS_PER_ENTRY: i64 = 64;
+
+// x, y, width, height in world units, as returned by the entity AABB helpers
+pub(crate) type Aabb = (f32, f32, f32, f32);
+
+pub(crate) fn aabbs_overlap(a: Aabb, b: Aabb) -> bool {
+    let (x1, y1, w1, h1) = a;
+    let (x2, y2, w2, h2) = b;
+    x1 < x2 + w2 && x1 + w1 > x2 && y1 < y2 + h2 && y1 + h1 > y2
+}
+
+// uniform grid over entity AABBs; cleared and refilled whenever the positions it holds go stale
+pub(crate) struct SpatialHash {
+    entries: Vec<(EntityId, Aabb)>,
+    // indices into `entries`
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    oversized: Vec<usize>,
+    stale: bool,
+}
+
+impl Default for SpatialHash {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+            stale: true,
+        }
+    }
+}
+
+impl SpatialHash {
+    pub(crate) fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    pub(crate) fn mark_stale(&mut self) {
+        self.stale = true;
+    }
+
+    // empties the grid for a rebuild; it counts as fresh until marked stale again
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.cells.clear();
+        self.oversized.clear();
+        self.stale = false;
+    }
+
+    pub(crate) fn insert(&mut self, id: EntityId, aabb: Aabb) {
+        let (x, y, w, h) = aabb;
+        // an empty or broken box can't overlap anything
+        if !(x.is_finite() && y.is_finite() && w.is_finite() && h.is_finite())
+            || w <= 0.0
+            || h <= 0.0
+        {
+            return;
+        }
+
+        let index = self.entries.len();
+        self.entries.push((id, aabb));
+        let (min_x, min_y, max_x, max_y) = cell_range(aabb);
+        if cell_count(min_x, min_y, max_x, max_y) > MAX_CELLS_PER_ENTRY {
+            self.oversized.push(index);
+            return;
+        }
+        for cell_y in min_y..=max_y {
+            for cell_x in min_x..=max_x {
+                self.cells.entry((cell_x, cell_y)).or_default().push(index);
+            }
+        }
+    }
+
+    // ids of every entry overlapping `region`, ascending
+    pub(crate) fn query(&self, region: Aabb) -> Vec<EntityId> {
+        let (min_x, min_y, max_x, max_y) = cell_range(region);
+        let region_cells = cell_count(min_x, min_y, max_x, max_y);
+
+        let mut candidates: Vec<usize> = if region_cells > self.entries.len() as i64 {
+            // visiting the cells would cost more than checking everything
+            (0..self.entries.len()).collect()
+        } else {
+            let mut candidates = self.oversized.clone();
+            for cell_y in min_y..=max_y {
+                for cell_x in min_x..=max_x {
+                    if let Some(indices) = self.cells.get(&(cell_x, cell_y)) {
+                        candidates.extend_from_slice(indices);
+                    }
+                }
+            }
+            // entries spanning several cells were picked up once per cell
+            candidates.sort_unstable();
+            candidates.dedup();
+            candidates
+        };
+
+        candidates.retain(|&index| aabbs_overlap(self.entries[index].1, region));
+        let mut ids: Vec<EntityId> = candidates
+            .into_iter()
+            .map(|index| self.entries[index].0)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+fn cell_range(aabb: Aabb) -> (i64, i64, i64, i64) {
+    let (x, y, w, h) = aabb;
+    // float to int casts saturate, so far-off or infinite boxes stay in range
+    let cell = |value: f32| (value / CELL_SIZE).floor() as i64;
+    (cell(x), cell(y), cell(x + w), cell(y + h))
+}
+
+fn cell_count(min_x: i64, min_y: i64, max_x: i64, max_y: i64) -> i64 {
+    let columns = max_x.saturating_sub(min_x).saturating_add(1);
+    let rows = max_y.saturating_sub(min_y).saturating_add(1);
+    columns.saturating_mul(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_return_overlapping_ids_in_order() {
+        let mut hash = SpatialHash::default();
+        assert!(hash.is_stale());
+        hash.clear();
+        assert!(!hash.is_stale());
+
+        hash.insert(3, (10.0, 10.0, 20.0, 20.0));
+        // straddles four cells but must come back once
+        hash.insert(1, (120.0, 120.0, 16.0, 16.0));
+        hash.insert(2, (1000.0, 1000.0, 10.0, 10.0));
+        // too big for the grid
+        hash.insert(4, (-5000.0, -5000.0, 10000.0, 10000.0));
+        hash.insert(5, (0.0, 0.0, 0.0, 10.0));
+
+        assert_eq!(hash.query((0.0, 0.0, 130.0, 130.0)), vec![1, 3, 4]);
+        assert_eq!(hash.query((125.0, 125.0, 1.0, 1.0)), vec![1, 4]);
+        assert_eq!(hash.query((30.0, 30.0, 50.0, 50.0)), vec![4]);
+        // larger than the grid is worth walking
+        assert_eq!(hash.query((-1.0e9, -1.0e9, 2.0e9, 2.0e9)), vec![1, 2, 3, 4]);
+
+        hash.mark_stale();
+        assert!(hash.is_stale());
+        hash.clear();
+        assert!(hash.query((0.0, 0.0, 130.0, 130.0)).is_empty());
+    }
+}
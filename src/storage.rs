@@ -0,0 +1,178 @@
+use crate::json::{json_to_lua, lua_to_json};
+use mlua::{Lua, Value};
+use serde_json::Map;
+use std::cell::RefCell;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+// values are kept as JSON so whatever `set` accepts is guaranteed to save
+struct Storage {
+    // None where the platform has no persistent location; data then lives for the session only
+    path: Option<PathBuf>,
+    data: Map<String, serde_json::Value>,
+}
+
+impl Storage {
+    fn save(&self) -> mlua::Result<bool> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| {
+                mlua::Error::external(format!(
+                    "failed to create storage directory {}: {error}",
+                    parent.display()
+                ))
+            })?;
+        }
+        let text = serde_json::to_string_pretty(&self.data).map_err(mlua::Error::external)?;
+        // write next to the target and rename, so a crash mid-save keeps the old file intact
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, text)
+            .and_then(|()| fs::rename(&temp, path))
+            .map_err(|error| {
+                mlua::Error::external(format!(
+                    "failed to save storage to {}: {error}",
+                    path.display()
+                ))
+            })?;
+        Ok(true)
+    }
+
+    fn load(&mut self) -> mlua::Result<bool> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(error) => {
+                return Err(mlua::Error::external(format!(
+                    "failed to read storage from {}: {error}",
+                    path.display()
+                )));
+            }
+        };
+        self.data = serde_json::from_str(&text).map_err(|error| {
+            mlua::Error::external(format!(
+                "storage file {} is not a JSON object: {error}",
+                path.display()
+            ))
+        })?;
+        Ok(true)
+    }
+}
+
+pub(crate) fn add_storage_module(lua: &Lua, path: Option<PathBuf>) -> mlua::Result<()> {
+    let storage = Rc::new(RefCell::new(Storage {
+        path,
+        data: Map::new(),
+    }));
+    let module = lua.create_table()?;
+
+    {
+        let storage = storage.clone();
+        module.set(
+            "set",
+            lua.create_function(move |_lua, (key, value): (String, Value)| {
+                let value = lua_to_json(value).map_err(|error| {
+                    mlua::Error::external(format!("storage.set('{key}'): {error}"))
+                })?;
+                let mut storage = storage.borrow_mut();
+                if value.is_null() {
+                    storage.data.remove(&key);
+                } else {
+                    storage.data.insert(key, value);
+                }
+                Ok(())
+            })?,
+        )?;
+    }
+
+    {
+        let storage = storage.clone();
+        module.set(
+            "get",
+            lua.create_function(move |lua, (key, default): (String, Value)| {
+                // a fresh copy each call: edit the returned table, then `set` it back
+                match storage.borrow().data.get(&key) {
+                    Some(value) => json_to_lua(lua, value.clone()),
+                    None => Ok(default),
+                }
+            })?,
+        )?;
+    }
+
+    {
+        let storage = storage.clone();
+        module.set(
+            "save",
+            lua.create_function(move |_lua, ()| storage.borrow().save())?,
+        )?;
+    }
+
+    {
+        let storage = storage.clone();
+        module.set(
+            "load",
+            lua.create_function(move |_lua, ()| storage.borrow_mut().load())?,
+        )?;
+    }
+
+    lua.globals().set("storage", module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn saved_values_survive_a_new_session() -> mlua::Result<()> {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("neolove_storage_{unique}"));
+        let path = dir.join("storage.json");
+
+        let lua = Lua::new();
+        add_storage_module(&lua, Some(path.clone()))?;
+        let (loaded, saved): (bool, bool) = lua
+            .load(
+                r#"
+                local loaded = storage.load()
+                storage.set("highScore", 1200)
+                storage.set("settings", { volume = 0.5, keys = { "a", "d" } })
+                storage.set("removed", true)
+                storage.set("removed", nil)
+                return loaded, storage.save()
+                "#,
+            )
+            .eval()?;
+        assert_eq!((loaded, saved), (false, true));
+        assert!(lua.load("storage.set('bad', print)").exec().is_err());
+
+        let lua = Lua::new();
+        add_storage_module(&lua, Some(path))?;
+        let (loaded, score, key, removed): (bool, f64, String, String) = lua
+            .load(
+                r#"
+                local loaded = storage.load()
+                return loaded, storage.get("highScore"), storage.get("settings").keys[2],
+                    storage.get("removed", "default")
+                "#,
+            )
+            .eval()?;
+        assert_eq!(
+            (loaded, score, key.as_str(), removed.as_str()),
+            (true, 1200.0, "d", "default")
+        );
+
+        let _ = fs::remove_dir_all(dir);
+        Ok(())
+    }
+}
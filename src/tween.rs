@@ -0,0 +1,127 @@
+use mlua::Lua;
+use std::f64::consts::PI;
+
+// the standard easing curves (easings.net); each maps progress 0..1 to 0..1
+const EASINGS: &[(&str, fn(f64) -> f64)] = &[
+    ("linear", |t| t),
+    ("easeInQuad", |t| t * t),
+    ("easeOutQuad", |t| 1.0 - (1.0 - t) * (1.0 - t)),
+    ("easeInOutQuad", |t| {
+        if t < 0.5 {
+            2.0 * t * t
+        } else {
+            1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+        }
+    }),
+    ("easeInCubic", |t| t * t * t),
+    ("easeOutCubic", |t| 1.0 - (1.0 - t).powi(3)),
+    ("easeInOutCubic", ease_in_out_cubic),
+    ("easeInOut", ease_in_out_cubic),
+    ("easeInSine", |t| 1.0 - (t * PI / 2.0).cos()),
+    ("easeOutSine", |t| (t * PI / 2.0).sin()),
+    ("easeInOutSine", |t| -((PI * t).cos() - 1.0) / 2.0),
+    ("easeInBack", |t| {
+        const C1: f64 = 1.70158;
+        (C1 + 1.0) * t * t * t - C1 * t * t
+    }),
+    ("easeOutBack", |t| {
+        const C1: f64 = 1.70158;
+        1.0 + (C1 + 1.0) * (t - 1.0).powi(3) + C1 * (t - 1.0).powi(2)
+    }),
+    ("easeOutElastic", |t| {
+        if t <= 0.0 || t >= 1.0 {
+            t
+        } else {
+            2f64.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+        }
+    }),
+    ("easeOutBounce", ease_out_bounce),
+    ("easeInBounce", |t| 1.0 - ease_out_bounce(1.0 - t)),
+];
+
+fn ease_in_out_cubic(t: f64) -> f64 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+fn ease_out_bounce(t: f64) -> f64 {
+    const N1: f64 = 7.5625;
+    const D1: f64 = 2.75;
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+pub(crate) fn add_tween_module(lua: &Lua) -> mlua::Result<()> {
+    let module = lua.create_table()?;
+
+    module.set(
+        "lerp",
+        lua.create_function(|_lua, (a, b, t): (f64, f64, f64)| Ok(a + (b - a) * t))?,
+    )?;
+
+    module.set(
+        "inverseLerp",
+        lua.create_function(|_lua, (a, b, value): (f64, f64, f64)| {
+            Ok(if a == b { 0.0 } else { (value - a) / (b - a) })
+        })?,
+    )?;
+
+    module.set(
+        "clamp01",
+        lua.create_function(|_lua, t: f64| Ok(t.clamp(0.0, 1.0)))?,
+    )?;
+
+    // progress outside 0..1 is clamped so a timer that overshoots still lands on the end value
+    for &(name, ease) in EASINGS {
+        module.set(
+            name,
+            lua.create_function(move |_lua, t: f64| Ok(ease(t.clamp(0.0, 1.0))))?,
+        )?;
+    }
+
+    lua.globals().set("tween", module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for &(name, ease) in EASINGS {
+            assert!(ease(0.0).abs() < 1e-9, "{name}(0) = {}", ease(0.0));
+            assert!((ease(1.0) - 1.0).abs() < 1e-9, "{name}(1) = {}", ease(1.0));
+        }
+    }
+
+    #[test]
+    fn lua_helpers_lerp_and_clamp_progress() -> mlua::Result<()> {
+        let lua = Lua::new();
+        add_tween_module(&lua)?;
+
+        let (lerped, inverse, quad, clamped): (f64, f64, f64, f64) = lua
+            .load(
+                r#"
+                return tween.lerp(10, 20, 0.25), tween.inverseLerp(10, 20, 15),
+                    tween.easeInQuad(0.5), tween.easeOutQuad(2)
+                "#,
+            )
+            .eval()?;
+        assert_eq!((lerped, inverse, quad, clamped), (12.5, 0.5, 0.25, 1.0));
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
-use crate::platform::SharedPlatformState;
-use mlua::Lua;
+use crate::camera::SharedCameraState;
+use crate::platform::{GamepadState, SharedPlatformState, TouchPhase};
+use mlua::{Lua, Table, Value};
 
 pub(crate) fn normalize_name(name: &str) -> String {
     name.chars()
@@ -8,7 +9,117 @@ pub(crate) fn normalize_name(name: &str) -> String {
         .collect()
 }
 
-pub(crate) fn add_user_input_module(lua: &Lua, platform: SharedPlatformState) -> mlua::Result<()> {
+pub(crate) fn parse_gamepad_button(name: &str) -> Option<&'static str> {
+    let button = match normalize_name(name).as_str() {
+        "a" | "south" | "cross" => "a",
+        "b" | "east" | "circle" => "b",
+        "x" | "west" | "square" => "x",
+        "y" | "north" | "triangle" => "y",
+        "start" | "options" | "menu" => "start",
+        "back" | "select" | "share" | "view" => "back",
+        "guide" | "home" | "mode" => "guide",
+        "leftshoulder" | "lb" | "l1" => "leftshoulder",
+        "rightshoulder" | "rb" | "r1" => "rightshoulder",
+        "lefttrigger" | "lt" | "l2" => "lefttrigger",
+        "righttrigger" | "rt" | "r2" => "righttrigger",
+        "leftstick" | "ls" | "l3" => "leftstick",
+        "rightstick" | "rs" | "r3" => "rightstick",
+        "dpadup" | "up" => "dpadup",
+        "dpaddown" | "down" => "dpaddown",
+        "dpadleft" | "left" => "dpadleft",
+        "dpadright" | "right" => "dpadright",
+        _ => return None,
+    };
+    Some(button)
+}
+
+pub(crate) fn parse_gamepad_axis(name: &str) -> Option<&'static str> {
+    let axis = match normalize_name(name).as_str() {
+        "leftx" | "lx" => "leftx",
+        "lefty" | "ly" => "lefty",
+        "rightx" | "rx" => "rightx",
+        "righty" | "ry" => "righty",
+        "lefttrigger" | "lt" | "l2" => "lefttrigger",
+        "righttrigger" | "rt" | "r2" => "righttrigger",
+        _ => return None,
+    };
+    Some(axis)
+}
+
+#[cfg(not(target_os = "emscripten"))]
+thread_local! {
+    // kept alive for the whole session: on X11 the copied text disappears with its owner
+    static CLIPBOARD: std::cell::RefCell<Option<arboard::Clipboard>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(not(target_os = "emscripten"))]
+fn with_clipboard<R>(f: impl FnOnce(&mut arboard::Clipboard) -> Option<R>) -> Option<R> {
+    CLIPBOARD.with(|cell| {
+        let mut clipboard = cell.borrow_mut();
+        if clipboard.is_none() {
+            *clipboard = arboard::Clipboard::new().ok();
+        }
+        f(clipboard.as_mut()?)
+    })
+}
+
+#[cfg(not(target_os = "emscripten"))]
+fn read_clipboard() -> Option<String> {
+    with_clipboard(|clipboard| clipboard.get_text().ok()).filter(|text| !text.is_empty())
+}
+
+#[cfg(not(target_os = "emscripten"))]
+fn write_clipboard(text: String) -> bool {
+    with_clipboard(|clipboard| clipboard.set_text(text).ok()).is_some()
+}
+
+// browsers only hand out the clipboard asynchronously from user gestures
+#[cfg(target_os = "emscripten")]
+fn read_clipboard() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "emscripten")]
+fn write_clipboard(_text: String) -> bool {
+    false
+}
+
+fn parse_key_list(keys: &Table) -> mlua::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for (index, key) in keys.sequence_values::<Value>().enumerate() {
+        let name = match key? {
+            Value::String(name) => normalize_name(&name.to_str()?),
+            other => {
+                return Err(mlua::Error::external(format!(
+                    "key {} must be a string, got {}",
+                    index + 1,
+                    other.type_name()
+                )));
+            }
+        };
+        if name.is_empty() {
+            return Err(mlua::Error::external(format!(
+                "key {} is not a valid key name",
+                index + 1
+            )));
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
+
+// Lua-facing gamepad indices start at 1
+fn gamepad_at(gamepads: &[GamepadState], index: i64) -> Option<&GamepadState> {
+    let index = usize::try_from(index.checked_sub(1)?).ok()?;
+    gamepads.get(index)
+}
+
+pub(crate) fn add_user_input_module(
+    lua: &Lua,
+    platform: SharedPlatformState,
+    camera: SharedCameraState,
+) -> mlua::Result<()> {
     let input = lua.create_table()?;
 
     {
@@ -25,6 +136,37 @@ pub(crate) fn add_user_input_module(lua: &Lua, platform: SharedPlatformState) ->
         )?;
     }
 
+    {
+        let platform = platform.clone();
+        input.set(
+            "isAnyKeyDown",
+            lua.create_function(move |_lua, keys: Table| {
+                let keys = parse_key_list(&keys)?;
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                let keys_down = &platform.input().keys_down;
+                Ok(keys.iter().any(|key| keys_down.contains(key)))
+            })?,
+        )?;
+    }
+
+    {
+        let platform = platform.clone();
+        input.set(
+            "areKeysDown",
+            lua.create_function(move |_lua, keys: Table| {
+                let keys = parse_key_list(&keys)?;
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                let keys_down = &platform.input().keys_down;
+                // an empty chord is never "held"
+                Ok(!keys.is_empty() && keys.iter().all(|key| keys_down.contains(key)))
+            })?,
+        )?;
+    }
+
     {
         let platform = platform.clone();
         input.set(
@@ -53,6 +195,36 @@ pub(crate) fn add_user_input_module(lua: &Lua, platform: SharedPlatformState) ->
         )?;
     }
 
+    // the key names the platform layers report for each side of a modifier
+    for (name, sides) in [
+        ("isShiftDown", ["leftshift", "rightshift"]),
+        ("isCtrlDown", ["leftcontrol", "rightcontrol"]),
+        ("isAltDown", ["leftalt", "rightalt"]),
+        ("isSuperDown", ["leftsuper", "rightsuper"]),
+    ] {
+        let platform = platform.clone();
+        input.set(
+            name,
+            lua.create_function(move |_lua, ()| {
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                let keys_down = &platform.input().keys_down;
+                Ok(sides.iter().any(|key| keys_down.contains(*key)))
+            })?,
+        )?;
+    }
+
+    input.set(
+        "getClipboard",
+        lua.create_function(move |_lua, ()| Ok(read_clipboard()))?,
+    )?;
+
+    input.set(
+        "setClipboard",
+        lua.create_function(move |_lua, text: String| Ok(write_clipboard(text)))?,
+    )?;
+
     {
         let platform = platform.clone();
         input.set(
@@ -76,7 +248,27 @@ pub(crate) fn add_user_input_module(lua: &Lua, platform: SharedPlatformState) ->
                 let platform = platform
                     .lock()
                     .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
-                Ok(platform.input().mouse_pressed.contains(&button))
+                let input = platform.input();
+                Ok(
+                    input.mouse_pressed.contains(&button)
+                        && !input.mouse_consumed.contains(&button),
+                )
+            })?,
+        )?;
+    }
+
+    {
+        let platform = platform.clone();
+        input.set(
+            "consumeMousePress",
+            lua.create_function(move |_lua, button: Option<String>| {
+                let button = normalize_name(button.as_deref().unwrap_or("left"));
+                let mut platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                let input = platform.input_mut();
+                // true only for the caller that actually takes the press
+                Ok(input.mouse_pressed.contains(&button) && input.mouse_consumed.insert(button))
             })?,
         )?;
     }
@@ -147,6 +339,38 @@ pub(crate) fn add_user_input_module(lua: &Lua, platform: SharedPlatformState) ->
         )?;
     }
 
+    {
+        let platform = platform.clone();
+        input.set(
+            "getMousePosition",
+            lua.create_function(move |_lua, ()| {
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                let mouse = platform.mouse();
+                Ok((mouse.x, mouse.y))
+            })?,
+        )?;
+    }
+
+    {
+        let platform = platform.clone();
+        let camera = camera.clone();
+        input.set(
+            "getMouseWorldPosition",
+            lua.create_function(move |_lua, ()| {
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                let mouse = platform.mouse();
+                let world = camera
+                    .borrow()
+                    .screen_to_world(platform.window(), mouse.x, mouse.y);
+                Ok((world.x, world.y))
+            })?,
+        )?;
+    }
+
     {
         let platform = platform.clone();
         input.set(
@@ -214,6 +438,113 @@ pub(crate) fn add_user_input_module(lua: &Lua, platform: SharedPlatformState) ->
         )?;
     }
 
+    {
+        let platform = platform.clone();
+        input.set(
+            "getTextInput",
+            lua.create_function(move |_lua, ()| {
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                Ok(platform.input().text_input.clone())
+            })?,
+        )?;
+    }
+
+    {
+        let platform = platform.clone();
+        input.set(
+            "getTouches",
+            lua.create_function(move |lua, ()| {
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                let touches = lua.create_table()?;
+                for touch in &platform.input().touches {
+                    let entry = lua.create_table()?;
+                    entry.set("id", touch.id)?;
+                    entry.set("x", touch.x)?;
+                    entry.set("y", touch.y)?;
+                    entry.set("phase", touch.phase.as_str())?;
+                    touches.push(entry)?;
+                }
+                Ok(touches)
+            })?,
+        )?;
+    }
+
+    {
+        let platform = platform.clone();
+        input.set(
+            "touchCount",
+            lua.create_function(move |_lua, ()| {
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                Ok(platform
+                    .input()
+                    .touches
+                    .iter()
+                    .filter(|touch| {
+                        !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled)
+                    })
+                    .count())
+            })?,
+        )?;
+    }
+
+    {
+        let platform = platform.clone();
+        input.set(
+            "gamepadCount",
+            lua.create_function(move |_lua, ()| {
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                Ok(platform.input().gamepads.len())
+            })?,
+        )?;
+    }
+
+    {
+        let platform = platform.clone();
+        input.set(
+            "isGamepadButtonDown",
+            lua.create_function(move |_lua, (index, button): (i64, String)| {
+                let Some(button) = parse_gamepad_button(&button) else {
+                    return Err(mlua::Error::external(format!(
+                        "unknown gamepad button '{button}'"
+                    )));
+                };
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                Ok(gamepad_at(&platform.input().gamepads, index)
+                    .is_some_and(|gamepad| gamepad.buttons_down.contains(button)))
+            })?,
+        )?;
+    }
+
+    {
+        let platform = platform.clone();
+        input.set(
+            "getGamepadAxis",
+            lua.create_function(move |_lua, (index, axis): (i64, String)| {
+                let Some(axis) = parse_gamepad_axis(&axis) else {
+                    return Err(mlua::Error::external(format!(
+                        "unknown gamepad axis '{axis}'"
+                    )));
+                };
+                let platform = platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                Ok(gamepad_at(&platform.input().gamepads, index)
+                    .and_then(|gamepad| gamepad.axes.get(axis).copied())
+                    .unwrap_or(0.0))
+            })?,
+        )?;
+    }
+
     lua.globals().set("input", input.clone())?;
     lua.globals().set("userInput", input)?;
     Ok(())
@@ -22,7 +22,8 @@ EM_JS(void, neolove_js_bootstrap, (), {
     wheelX: 0,
     wheelY: 0,
     lastKey: "",
-    charPressed: "",
+    textInput: "",
+    touchEvents: [],
     ctx: null,
     imageData: null,
     statusEl: null,
@@ -204,6 +205,26 @@ EM_JS(void, neolove_js_bootstrap, (), {
       updateMouse(event);
     });
 
+    // phase codes follow platform::TouchPhase: 0 started, 1 moved, 3 ended, 4 cancelled.
+    // passive, so taps still produce the browser's emulated mouse clicks
+    const queueTouches = (event, phase) => {
+      const rect = canvas.getBoundingClientRect();
+      const logicalWidth = rect.width || 1;
+      const logicalHeight = rect.height || 1;
+      for (const touch of event.changedTouches) {
+        state.touchEvents.push({
+          id: touch.identifier,
+          x: ((touch.clientX - rect.left) * state.canvasWidth) / logicalWidth,
+          y: ((touch.clientY - rect.top) * state.canvasHeight) / logicalHeight,
+          phase
+        });
+      }
+    };
+    canvas.addEventListener("touchstart", (event) => queueTouches(event, 0), { passive: true });
+    canvas.addEventListener("touchmove", (event) => queueTouches(event, 1), { passive: true });
+    canvas.addEventListener("touchend", (event) => queueTouches(event, 3), { passive: true });
+    canvas.addEventListener("touchcancel", (event) => queueTouches(event, 4), { passive: true });
+
     canvas.addEventListener("wheel", (event) => {
       state.wheelX += event.deltaX / 40.0;
       state.wheelY += event.deltaY / -40.0;
@@ -227,7 +248,7 @@ EM_JS(void, neolove_js_bootstrap, (), {
         !event.altKey &&
         !event.metaKey
       ) {
-        state.charPressed = event.key;
+        state.textInput += event.key;
       }
       event.preventDefault();
     });
@@ -264,6 +285,14 @@ EM_JS(int, neolove_js_canvas_height, (), {
   return Module.neoloveState ? Module.neoloveState.canvasHeight : 1;
 });
 
+EM_JS(double, neolove_js_device_pixel_ratio, (), {
+  return window.devicePixelRatio || 1;
+});
+
+EM_JS(int, neolove_js_has_focus, (), {
+  return document.hasFocus() ? 1 : 0;
+});
+
 EM_JS(double, neolove_js_mouse_x, (), {
   return Module.neoloveState ? Module.neoloveState.mouseX : 0.0;
 });
@@ -333,19 +362,39 @@ EM_JS(int, neolove_js_take_last_key, (char* buffer, int capacity), {
   return required - 1;
 });
 
-EM_JS(int, neolove_js_take_char, (char* buffer, int capacity), {
+// writes id, x, y of the oldest queued touch event to `out` and returns its phase, or -1
+EM_JS(int, neolove_js_take_touch, (double* out), {
+  const state = Module.neoloveState;
+  if (!state || !state.touchEvents.length) {
+    return -1;
+  }
+  const touch = state.touchEvents.shift();
+  const view = HEAPF64.subarray(out >> 3, (out >> 3) + 3);
+  view[0] = touch.id;
+  view[1] = touch.x;
+  view[2] = touch.y;
+  return touch.phase;
+});
+
+EM_JS(int, neolove_js_take_text, (char* buffer, int capacity), {
   const state = Module.neoloveState;
-  if (!state || !state.charPressed || capacity <= 0) {
+  if (!state || !state.textInput || capacity <= 0) {
     return 0;
   }
-  const value = state.charPressed;
-  state.charPressed = "";
-  const required = lengthBytesUTF8(value) + 1;
-  if (required > capacity) {
-    return -required;
+  // hand out whole characters that fit; anything left over is taken next frame
+  let value = "";
+  for (const ch of state.textInput) {
+    if (lengthBytesUTF8(value + ch) + 1 > capacity) {
+      break;
+    }
+    value += ch;
+  }
+  if (!value) {
+    return -(lengthBytesUTF8(state.textInput) + 1);
   }
+  state.textInput = state.textInput.slice(value.length);
   stringToUTF8(value, buffer, capacity);
-  return required - 1;
+  return lengthBytesUTF8(value);
 });
 
 EM_JS(void, neolove_js_begin_frame, (), {
@@ -360,7 +409,6 @@ EM_JS(void, neolove_js_begin_frame, (), {
   state.wheelX = 0;
   state.wheelY = 0;
   state.lastKey = "";
-  state.charPressed = "";
 });
 
 EM_JS(void, neolove_js_present_rgba, (const uint8_t* pixels, int width, int height), {
@@ -418,6 +466,8 @@ EM_JS(int, neolove_js_audio_play, (
   int sample_rate,
   int channels,
   int looped,
+  float loop_start,
+  float loop_end,
   float volume
 ), {
   const state = Module.neoloveState;
@@ -456,6 +506,10 @@ EM_JS(int, neolove_js_audio_play, (
     const source = context.createBufferSource();
     source.buffer = audioBuffer;
     source.loop = !!looped;
+    if (looped && loop_end > loop_start) {
+      source.loopStart = loop_start;
+      source.loopEnd = loop_end;
+    }
     source.connect(gain);
 
     const entry = {
@@ -527,6 +581,31 @@ EM_JS(int, neolove_js_audio_set_volume, (int sound_id, float volume), {
   }
 });
 
+EM_JS(int, neolove_js_audio_stop_all, (void), {
+  const state = Module.neoloveState;
+  if (!state || !state.audio) {
+    return 1;
+  }
+  try {
+    Module.neoloveClearAudioError();
+    for (const soundId of Array.from(state.audio.active.keys())) {
+      Module.neoloveStopAudioInstance(soundId);
+    }
+    return 1;
+  } catch (error) {
+    Module.neoloveSetAudioError(error);
+    return 0;
+  }
+});
+
+EM_JS(int, neolove_js_audio_is_playing, (int sound_id), {
+  const state = Module.neoloveState;
+  if (!state || !state.audio) {
+    return 0;
+  }
+  return state.audio.active.has(sound_id) ? 1 : 0;
+});
+
 EM_JS(int, neolove_js_take_audio_error, (char* buffer, int capacity), {
   const state = Module.neoloveState;
   const value = state && state.audio ? state.audio.lastError : "";
@@ -558,6 +637,14 @@ int neolove_web_canvas_height(void) {
   return neolove_js_canvas_height();
 }
 
+double neolove_web_device_pixel_ratio(void) {
+  return neolove_js_device_pixel_ratio();
+}
+
+int neolove_web_has_focus(void) {
+  return neolove_js_has_focus();
+}
+
 double neolove_web_mouse_x(void) {
   return neolove_js_mouse_x();
 }
@@ -586,8 +673,12 @@ int neolove_web_take_last_key(char* buffer, int capacity) {
   return neolove_js_take_last_key(buffer, capacity);
 }
 
-int neolove_web_take_char(char* buffer, int capacity) {
-  return neolove_js_take_char(buffer, capacity);
+int neolove_web_take_text(char* buffer, int capacity) {
+  return neolove_js_take_text(buffer, capacity);
+}
+
+int neolove_web_take_touch(double* out) {
+  return neolove_js_take_touch(out);
 }
 
 void neolove_web_begin_frame(void) {
@@ -617,6 +708,8 @@ int neolove_web_audio_play(
     int sample_rate,
     int channels,
     int looped,
+    float loop_start,
+    float loop_end,
     float volume) {
   return neolove_js_audio_play(
       sound_id,
@@ -625,6 +718,8 @@ int neolove_web_audio_play(
       sample_rate,
       channels,
       looped,
+      loop_start,
+      loop_end,
       volume);
 }
 
@@ -636,6 +731,14 @@ int neolove_web_audio_set_volume(int sound_id, float volume) {
   return neolove_js_audio_set_volume(sound_id, volume);
 }
 
+int neolove_web_audio_stop_all(void) {
+  return neolove_js_audio_stop_all();
+}
+
+int neolove_web_audio_is_playing(int sound_id) {
+  return neolove_js_audio_is_playing(sound_id);
+}
+
 int neolove_web_take_audio_error(char* buffer, int capacity) {
   return neolove_js_take_audio_error(buffer, capacity);
 }
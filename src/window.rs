@@ -1,4 +1,4 @@
-use mlua::{Compiler, Function, Lua, RegistryKey, Table, TextRequirer, Value};
+use mlua::{Compiler, Function, IntoLuaMulti, Lua, RegistryKey, Table, TextRequirer, Value};
 use rapier2d::prelude::{
     nalgebra, point, vector, CCDSolver, ColliderBuilder, ColliderHandle, ColliderSet,
     DefaultBroadPhase, ImpulseJointHandle, ImpulseJointSet, IntegrationParameters, IslandManager,
@@ -6,34 +6,114 @@ use rapier2d::prelude::{
     RigidBodySet, RopeJointBuilder,
 };
 use std::cell::{Cell, RefCell};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
+use std::time::Instant;
 
+use crate::camera::{new_shared_camera_state, SharedCameraState};
 use crate::hierarchy;
-use crate::lua_error::{describe_lua_error, protect_lua_call};
+use crate::lua_error::{
+    describe_lua_error, new_shared_error_debouncer, protect_lua_call, report_lua_error,
+    SharedErrorDebouncer,
+};
 use crate::platform::{
-    new_shared_platform_state, Color as PlatformColor, SharedPlatformState, WindowState,
+    new_shared_platform_state, Color as PlatformColor, DisplayScale, ScreenshotRequest,
+    SharedPlatformState, WindowState,
+};
+use crate::renderer::{
+    new_shared_render_state, BlendMode, DrawCommand, FontHandle, Rect, SharedRenderState,
+    TextAlignX, TextAlignY, TextRenderRequest, TextScaleMode, TextWrapMode, Vec2,
 };
-use crate::renderer::{new_shared_render_state, SharedRenderState};
+use crate::spatial_hash::{aabbs_overlap, SpatialHash};
 
 pub struct Runtime {
     entities: Rc<RefCell<HashMap<hierarchy::EntityId, hierarchy::Entity>>>,
     entity_listeners: Rc<RefCell<HashMap<u64, EntityListener>>>,
     next_entity_listener_id: Rc<RefCell<u64>>,
     systems: Rc<RefCell<Vec<RegistryKey>>>,
+    system_context: Option<RegistryKey>,
+    scene: Rc<RefCell<SceneState>>,
+    // backs the `events` global
+    event_handlers: crate::events::SharedEventHandlers,
     environment: PathBuf,
     lua: Lua,
     entity_max: usize,
     max_fps: Rc<RefCell<Option<f32>>>,
     show_fps: Rc<RefCell<bool>>,
-    exit_requested: Rc<RefCell<bool>>,
+    debug_overlay: Rc<RefCell<bool>>,
+    // average over the last FRAME_RATE_SAMPLES real (unscaled) frame times
+    frame_rate: Rc<RefCell<f32>>,
+    recent_dts: VecDeque<f32>,
+    // backs the `time` global: clock origin and the dt of the current frame
+    started_at: Instant,
+    frame_dt: Rc<RefCell<f32>>,
+    fixed_timestep: Rc<RefCell<FixedTimestep>>,
+    paused: Rc<RefCell<bool>>,
+    time_scale: Rc<RefCell<f32>>,
+    // longest real frame time fed to gameplay, see app.setMaxDelta
+    max_delta: Rc<RefCell<f32>>,
+    // shared with report_lua_error through the Lua app data
+    error_debouncer: SharedErrorDebouncer,
+    // process exit code requested by app.quit / die
+    exit_requested: Rc<RefCell<Option<i32>>>,
+    // initial app.nearestNeighborScaling, from `[window] filter` in neolove.toml
+    default_nearest_scaling: bool,
+    // file behind the `storage` global; None keeps saves in memory for the session
+    storage_path: Option<PathBuf>,
+    // app.onResize / app.onFocusChanged handlers and the window state they last saw
+    resize_callback: Rc<RefCell<Option<RegistryKey>>>,
+    focus_callback: Rc<RefCell<Option<RegistryKey>>>,
+    last_window_size: Option<(f32, f32)>,
+    last_focused: bool,
+    // app.screenshot callbacks by request id, until the platform reports the result
+    screenshot_callbacks: Rc<RefCell<HashMap<u64, RegistryKey>>>,
     physics_world: Option<PhysicsWorld>,
     physics_signature: u64,
+    asset_manager: Option<crate::assets::SharedAssetManager>,
     platform: SharedPlatformState,
     render_state: SharedRenderState,
+    camera: SharedCameraState,
+    // backs the `random` global
+    rng: crate::random::SharedRng,
+    // backs transform.queryRegion; refilled from entity AABBs on the first query of each step
+    spatial_hash: Rc<RefCell<SpatialHash>>,
+}
+
+// frames averaged by app.getFps and the fps overlay, about a second at 60 fps
+const FRAME_RATE_SAMPLES: usize = 60;
+
+// a stall (window drag, breakpoint, slow load) advances gameplay by at most this many seconds
+const DEFAULT_MAX_DELTA: f32 = 0.1;
+
+// caps the catch-up work one slow frame can trigger in fixed timestep mode
+const MAX_FIXED_STEPS_PER_FRAME: u32 = 8;
+
+// set by app.setFixedTimestep: `step` seconds per simulation step, None for one step per frame
+#[derive(Clone, Copy, Debug, Default)]
+struct FixedTimestep {
+    step: Option<f32>,
+    // real time not yet consumed by a step
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    // how far the frame is between the last step and the next one, for interpolating drawing
+    fn alpha(&self) -> f32 {
+        match self.step {
+            Some(step) => (self.accumulator / step).clamp(0.0, 1.0),
+            None => 1.0,
+        }
+    }
+}
+
+// set by ecs.loadScene; `loads` lets the system loop notice a swap made by one of its systems
+#[derive(Debug)]
+struct SceneState {
+    name: String,
+    loads: u64,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -92,6 +172,31 @@ struct EntityListener {
     connected: Rc<Cell<bool>>,
 }
 
+// writes each screenshot from the presented frame, or fails them all with the readback error,
+// and reports the results back to the runtime
+pub(crate) fn save_screenshots(
+    platform: &SharedPlatformState,
+    requests: Vec<ScreenshotRequest>,
+    frame: Result<&image::RgbaImage, String>,
+) {
+    let Ok(mut platform) = platform.lock() else {
+        return;
+    };
+    for request in requests {
+        let result = match &frame {
+            Ok(frame) => crate::assets::write_png(&request.path, (*frame).clone())
+                .map_err(|error| error.to_string()),
+            Err(error) => Err(error.clone()),
+        };
+        platform.finish_screenshot(request.id, result);
+    }
+}
+
+// the first request wins, so a later die() can't overwrite the code passed to app.quit
+fn request_exit(exit_requested: &Rc<RefCell<Option<i32>>>, code: i32) {
+    exit_requested.borrow_mut().get_or_insert(code);
+}
+
 fn color4_table(lua: &Lua, r: u8, g: u8, b: u8, a: u8) -> mlua::Result<Table> {
     let t = lua.create_table()?;
     t.set("r", r)?;
@@ -247,6 +352,58 @@ fn describe_component_name(component: &Table, entity: Option<&Table>) -> String
     "anonymous component".to_string()
 }
 
+fn component_blend_mode(component: &Table) -> mlua::Result<BlendMode> {
+    match component.get::<Option<String>>("blend")? {
+        Some(name) => BlendMode::parse(&name).ok_or_else(|| {
+            mlua::Error::external(format!(
+                "blend must be \"alpha\", \"additive\", \"none\" or \"premultiplied\", got \"{name}\""
+            ))
+        }),
+        None => Ok(BlendMode::Alpha),
+    }
+}
+
+fn component_matches_name(lua: &Lua, component: &Table, name: &str) -> bool {
+    if component
+        .get::<String>("name")
+        .is_ok_and(|value| value == name)
+    {
+        return true;
+    }
+    let Ok(kind) = component.get::<String>("__neolove_component") else {
+        return false;
+    };
+    if kind == name {
+        return true;
+    }
+
+    // aliases like TextLabel share the TextBox prototype, so compare the registered tables
+    let Ok(core) = lua.globals().get::<Table>("core") else {
+        return false;
+    };
+    match (core.get::<Table>(name), core.get::<Table>(kind)) {
+        (Ok(wanted), Ok(actual)) => wanted == actual,
+        _ => false,
+    }
+}
+
+fn run_component_destroy(entity: &Table, component: &Table) -> mlua::Result<()> {
+    if let Ok(destroy) = component.get::<Function>("destroy") {
+        let component_name = describe_component_name(component, Some(entity));
+        protect_lua_call(
+            &format!("running component destroy callback ({component_name})"),
+            || destroy.call::<()>((entity, component)),
+        )?;
+    } else if let Ok(on_destroy) = component.get::<Function>("onDestroy") {
+        let component_name = describe_component_name(component, Some(entity));
+        protect_lua_call(
+            &format!("running component onDestroy callback ({component_name})"),
+            || on_destroy.call::<()>((entity, component)),
+        )?;
+    }
+    Ok(())
+}
+
 pub(crate) fn attach_entity_methods(lua: &Lua, entity: &Table) -> mlua::Result<()> {
     let listen = lua.create_function(
         move |lua, (entity, event_name, callback): (Table, String, Function)| {
@@ -296,7 +453,9 @@ pub(crate) fn attach_entity_methods(lua: &Lua, entity: &Table) -> mlua::Result<(
         duplicate_entity.call::<Table>((entity, parent))
     })?;
     entity.set("duplicate", duplicate.clone())?;
-    entity.set("Duplicate", duplicate)?;
+    entity.set("Duplicate", duplicate.clone())?;
+    entity.set("clone", duplicate.clone())?;
+    entity.set("Clone", duplicate)?;
 
     let find_first_child = lua.create_function(move |lua, (entity, name): (Table, String)| {
         let ecs: Table = lua.globals().get("ecs")?;
@@ -306,6 +465,30 @@ pub(crate) fn attach_entity_methods(lua: &Lua, entity: &Table) -> mlua::Result<(
     entity.set("findFirstChild", find_first_child.clone())?;
     entity.set("FindFirstChild", find_first_child)?;
 
+    let find_all_children = lua.create_function(move |lua, (entity, name): (Table, String)| {
+        let ecs: Table = lua.globals().get("ecs")?;
+        let find_all_children: Function = ecs.get("findAllChildren")?;
+        find_all_children.call::<Table>((entity, name))
+    })?;
+    entity.set("findAllChildren", find_all_children.clone())?;
+    entity.set("FindAllChildren", find_all_children)?;
+
+    let find_descendant = lua.create_function(move |lua, (entity, name): (Table, String)| {
+        let ecs: Table = lua.globals().get("ecs")?;
+        let find_descendant: Function = ecs.get("findDescendant")?;
+        find_descendant.call::<Option<Table>>((entity, name))
+    })?;
+    entity.set("findDescendant", find_descendant.clone())?;
+    entity.set("FindDescendant", find_descendant)?;
+
+    let get_component = lua.create_function(move |lua, (entity, name): (Table, String)| {
+        let ecs: Table = lua.globals().get("ecs")?;
+        let get_component: Function = ecs.get("getComponent")?;
+        get_component.call::<Option<Table>>((entity, name))
+    })?;
+    entity.set("getComponent", get_component.clone())?;
+    entity.set("GetComponent", get_component)?;
+
     let get_world_position = lua.create_function(move |lua, entity: Table| {
         let transform: Table = lua.globals().get("transform")?;
         let get_world_position: Function = transform.get("getWorldPosition")?;
@@ -368,6 +551,7 @@ pub(crate) fn create_entity_table(
     table.set("anchor_y", 0.0)?;
     table.set("pivot_x", Value::Nil)?;
     table.set("pivot_y", Value::Nil)?;
+    table.set("enabled", true)?;
     table.set("components", lua.create_table()?)?;
     if let Some(par) = parent {
         table.set("parent", &par)?;
@@ -427,6 +611,98 @@ fn resolve_existing_softrequire_path(root: &Path, input: &str) -> Result<Option<
     Ok(Some(canonical))
 }
 
+// directory of the script calling `require`, taken from its "@path" chunk name
+fn calling_script_dir(lua: &Lua) -> Option<PathBuf> {
+    let source = lua.inspect_stack(1, |debug| {
+        debug.source().source.map(|source| source.into_owned())
+    })??;
+    let path = PathBuf::from(source.strip_prefix('@')?);
+    if !path.is_absolute() {
+        return None;
+    }
+    path.parent().map(Path::to_path_buf)
+}
+
+// Luau-style lookup: relative to the requiring script first; bare names (no ./ or ../)
+// fall back to the project root
+fn resolve_require_path(
+    root: &Path,
+    caller_dir: Option<&Path>,
+    name: &str,
+) -> Result<PathBuf, String> {
+    let explicit = name.starts_with("./") || name.starts_with("../");
+    let mut bases = Vec::new();
+    if let Some(dir) = caller_dir {
+        bases.push(dir.to_path_buf());
+    }
+    if (!explicit || caller_dir.is_none()) && caller_dir != Some(root) {
+        bases.push(root.to_path_buf());
+    }
+    for base in &bases {
+        let candidate = base.join(name);
+        if let Some(path) = resolve_existing_softrequire_path(root, &candidate.to_string_lossy())? {
+            return Ok(path);
+        }
+    }
+    let searched = bases
+        .iter()
+        .map(|base| base.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(format!("module '{name}' not found (searched {searched})"))
+}
+
+// `name = "path"` entries from the [dependencies] section of neolove.toml
+fn parse_project_dependencies(root: &Path) -> Result<Vec<(String, String)>, String> {
+    let Ok(contents) = fs::read_to_string(root.join("neolove.toml")) else {
+        return Ok(Vec::new());
+    };
+
+    let mut dependencies = Vec::new();
+    let mut section = String::new();
+    for (index, raw_line) in contents.lines().enumerate() {
+        let line = raw_line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].trim().to_ascii_lowercase();
+            continue;
+        }
+        if section != "dependencies" {
+            continue;
+        }
+
+        let parsed = line.split_once('=').and_then(|(name, path)| {
+            let name = name.trim().trim_matches('"').trim();
+            let path = path.trim();
+            let path = path.strip_prefix('"')?.strip_suffix('"')?;
+            (!name.is_empty() && !path.is_empty()).then(|| (name.to_string(), path.to_string()))
+        });
+        let Some(entry) = parsed else {
+            return Err(format!(
+                "neolove.toml line {}: dependencies must look like name = \"path\"",
+                index + 1
+            ));
+        };
+        dependencies.push(entry);
+    }
+    Ok(dependencies)
+}
+
+fn resolve_project_dependencies(root: &Path) -> Result<HashMap<String, PathBuf>, String> {
+    let mut resolved = HashMap::new();
+    for (name, path) in parse_project_dependencies(root)? {
+        let module_path = resolve_existing_softrequire_path(root, &path)
+            .map_err(|error| format!("dependency '{name}' is invalid: {error}"))?
+            .ok_or_else(|| {
+                format!("dependency '{name}' points to '{path}', which does not exist")
+            })?;
+        resolved.insert(name, module_path);
+    }
+    Ok(resolved)
+}
+
 fn softrequire_source_cache_key(source: &str) -> String {
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
     source.hash(&mut hasher);
@@ -725,6 +1001,30 @@ pub fn get_global_scale(entity: &Table) -> mlua::Result<f32> {
     Ok(scale.max(0.0))
 }
 
+// an entity only runs while it and every ancestor are enabled; a missing flag counts as enabled
+pub fn is_entity_enabled(entity: &Table) -> bool {
+    let mut current_entity = entity.clone();
+
+    loop {
+        if current_entity.get::<Option<bool>>("enabled") == Ok(Some(false)) {
+            return false;
+        }
+
+        if let Ok(Some(parent)) = current_entity.get::<Option<Table>>("parent") {
+            current_entity = parent;
+        } else {
+            return true;
+        }
+    }
+}
+
+// (x, y, w, h) of the entity's unrotated bounds in world space
+fn get_world_aabb(entity: &Table) -> mlua::Result<(f32, f32, f32, f32)> {
+    let (x, y) = get_global_position(entity)?;
+    let (w, h) = get_global_size(entity)?;
+    Ok((x, y, w, h))
+}
+
 pub fn get_global_size(entity: &Table) -> mlua::Result<(f32, f32)> {
     let w: f32 = entity.get("size_x")?;
     let h: f32 = entity.get("size_y")?;
@@ -1115,23 +1415,53 @@ fn write_id_set_to_table(lua: &Lua, ids: &HashSet<usize>) -> mlua::Result<Table>
 
 impl Runtime {
     pub fn new(env: PathBuf) -> Runtime {
+        let lua = Lua::new();
+        let error_debouncer = new_shared_error_debouncer(&lua);
         Runtime {
             entities: Rc::new(RefCell::new(HashMap::new())),
             entity_listeners: Rc::new(RefCell::new(HashMap::new())),
             next_entity_listener_id: Rc::new(RefCell::new(1)),
             systems: Rc::new(RefCell::new(Vec::new())),
+            system_context: None,
+            // main.luau builds the first scene
+            scene: Rc::new(RefCell::new(SceneState {
+                name: "main".to_string(),
+                loads: 0,
+            })),
+            event_handlers: crate::events::new_shared_event_handlers(),
             environment: env,
-            lua: Lua::new(),
+            lua,
+            error_debouncer,
             entity_max: 1,
             // default to uncapped; users can opt into a cap via app.setMaxFps
             max_fps: Rc::new(RefCell::new(None)),
             // default to showing fps counter in debug runs
             show_fps: Rc::new(RefCell::new(true)),
-            exit_requested: Rc::new(RefCell::new(false)),
+            debug_overlay: Rc::new(RefCell::new(false)),
+            frame_rate: Rc::new(RefCell::new(0.0)),
+            recent_dts: VecDeque::with_capacity(FRAME_RATE_SAMPLES),
+            started_at: Instant::now(),
+            frame_dt: Rc::new(RefCell::new(0.0)),
+            fixed_timestep: Rc::new(RefCell::new(FixedTimestep::default())),
+            paused: Rc::new(RefCell::new(false)),
+            time_scale: Rc::new(RefCell::new(1.0)),
+            max_delta: Rc::new(RefCell::new(DEFAULT_MAX_DELTA)),
+            exit_requested: Rc::new(RefCell::new(None)),
+            default_nearest_scaling: true,
+            storage_path: None,
+            resize_callback: Rc::new(RefCell::new(None)),
+            focus_callback: Rc::new(RefCell::new(None)),
+            last_window_size: None,
+            last_focused: true,
+            screenshot_callbacks: Rc::new(RefCell::new(HashMap::new())),
             physics_world: None,
             physics_signature: 0,
+            asset_manager: None,
             platform: new_shared_platform_state(),
             render_state: new_shared_render_state(),
+            camera: new_shared_camera_state(),
+            rng: crate::random::new_shared_rng(),
+            spatial_hash: Rc::new(RefCell::new(SpatialHash::default())),
         }
     }
 
@@ -1143,27 +1473,80 @@ impl Runtime {
         self.render_state.clone()
     }
 
+    pub fn set_default_nearest_neighbor_scaling(&mut self, enabled: bool) {
+        self.default_nearest_scaling = enabled;
+    }
+
+    pub fn set_storage_path(&mut self, path: Option<PathBuf>) {
+        self.storage_path = path;
+    }
+
     pub fn set_platform_window_state(&self, width: f32, height: f32) {
         if let Ok(mut platform) = self.platform.lock() {
             platform.set_window(WindowState { width, height });
         }
     }
 
+    pub fn set_platform_display_scale(&self, dpi: f32, content: f32) {
+        if let Ok(mut platform) = self.platform.lock() {
+            platform.set_display_scale(DisplayScale { dpi, content });
+        }
+    }
+
+    pub fn set_platform_focused(&self, focused: bool) {
+        if let Ok(mut platform) = self.platform.lock() {
+            platform.set_focused(focused);
+        }
+    }
+
     pub fn set_platform_mouse_state(&self, x: f32, y: f32) {
         if let Ok(mut platform) = self.platform.lock() {
             platform.set_mouse_position(x, y);
         }
     }
 
+    pub fn press_key(&self, key: &str) {
+        if let Ok(mut platform) = self.platform.lock() {
+            platform.press_key(crate::user_input::normalize_name(key));
+        }
+    }
+
+    pub fn release_key(&self, key: &str) {
+        if let Ok(mut platform) = self.platform.lock() {
+            platform.release_key(crate::user_input::normalize_name(key));
+        }
+    }
+
+    pub fn press_mouse_button(&self, button: &str) {
+        if let Ok(mut platform) = self.platform.lock() {
+            platform.press_mouse_button(crate::user_input::normalize_name(button));
+        }
+    }
+
+    pub fn release_mouse_button(&self, button: &str) {
+        if let Ok(mut platform) = self.platform.lock() {
+            platform.release_mouse_button(crate::user_input::normalize_name(button));
+        }
+    }
+
     pub fn max_fps(&self) -> Option<f32> {
         *self.max_fps.borrow()
     }
 
+    // `update` cuts the gameplay dt to this
+    pub fn max_delta(&self) -> f32 {
+        *self.max_delta.borrow()
+    }
+
     pub fn show_fps(&self) -> bool {
         *self.show_fps.borrow()
     }
 
     pub fn exit_requested(&self) -> bool {
+        self.exit_requested.borrow().is_some()
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
         *self.exit_requested.borrow()
     }
 
@@ -1219,7 +1602,46 @@ impl Runtime {
                 .set_type_info_level(1),
         );
 
-        let require = self.lua.create_require_function(TextRequirer::new())?;
+        let dependency_root = self
+            .environment
+            .canonicalize()
+            .map_err(mlua::Error::external)?;
+        let dependencies =
+            resolve_project_dependencies(&dependency_root).map_err(mlua::Error::external)?;
+        let text_require = self.lua.create_require_function(TextRequirer::new())?;
+        let module_cache = Rc::new(RefCell::new(HashMap::<PathBuf, RegistryKey>::new()));
+        let require = self.lua.create_function(move |lua, name: String| {
+            // "@alias/..." paths keep the standard .luaurc rules
+            if name.starts_with('@') {
+                return text_require.call::<Value>(name);
+            }
+            let path = match dependencies.get(&name) {
+                Some(path) => path.clone(),
+                None => resolve_require_path(
+                    &dependency_root,
+                    calling_script_dir(lua).as_deref(),
+                    &name,
+                )
+                .map_err(mlua::Error::external)?,
+            };
+            if let Some(registry_key) = module_cache.borrow().get(&path) {
+                return lua.registry_value::<Value>(registry_key);
+            }
+
+            let source = fs::read(&path).map_err(|error| {
+                mlua::Error::external(format!(
+                    "failed to read module '{name}' at {}: {error}",
+                    path.display()
+                ))
+            })?;
+            let result: Value = lua
+                .load(source)
+                .set_name(format!("@{}", path.display()))
+                .call(())?;
+            let registry_key = lua.create_registry_value(result.clone())?;
+            module_cache.borrow_mut().insert(path, registry_key);
+            Ok(result)
+        })?;
         self.lua.globals().set("require", require)?;
 
         self.set_mouse_table()?;
@@ -1229,9 +1651,10 @@ impl Runtime {
         {
             let app = self.lua.create_table()?;
             app.set("bg", color4_table(&self.lua, 255, 255, 255, 255)?)?;
-            app.set("nearestNeighborScaling", true)?;
+            app.set("nearestNeighborScaling", self.default_nearest_scaling)?;
             if let Ok(mut platform) = self.platform.lock() {
                 platform.set_clear_color(PlatformColor::WHITE);
+                platform.set_clear_enabled(true);
             }
 
             let max_fps_setter = self.max_fps.clone();
@@ -1266,6 +1689,137 @@ impl Runtime {
                 .create_function(move |_lua, ()| Ok(*show_fps_getter.borrow()))?;
             app.set("getShowFps", get_show_fps)?;
 
+            let debug_overlay_setter = self.debug_overlay.clone();
+            let set_debug_overlay =
+                self.lua
+                    .create_function(move |_lua, enabled: Option<bool>| {
+                        *debug_overlay_setter.borrow_mut() = enabled.unwrap_or(true);
+                        Ok(())
+                    })?;
+            app.set("setDebugOverlay", set_debug_overlay)?;
+
+            let paused_setter = self.paused.clone();
+            let set_paused = self
+                .lua
+                .create_function(move |_lua, paused: Option<bool>| {
+                    *paused_setter.borrow_mut() = paused.unwrap_or(true);
+                    Ok(())
+                })?;
+            app.set("setPaused", set_paused)?;
+
+            let paused_getter = self.paused.clone();
+            let is_paused = self
+                .lua
+                .create_function(move |_lua, ()| Ok(*paused_getter.borrow()))?;
+            app.set("isPaused", is_paused)?;
+
+            let time_scale_setter = self.time_scale.clone();
+            let set_time_scale = self.lua.create_function(move |_lua, factor: f32| {
+                if !factor.is_finite() {
+                    return Err(mlua::Error::external(
+                        "app.setTimeScale expects a finite number",
+                    ));
+                }
+                *time_scale_setter.borrow_mut() = factor.max(0.0);
+                Ok(())
+            })?;
+            app.set("setTimeScale", set_time_scale)?;
+
+            let time_scale_getter = self.time_scale.clone();
+            let get_time_scale = self
+                .lua
+                .create_function(move |_lua, ()| Ok(*time_scale_getter.borrow()))?;
+            app.set("getTimeScale", get_time_scale)?;
+
+            let max_delta_setter = self.max_delta.clone();
+            let set_max_delta = self.lua.create_function(move |_lua, seconds: f32| {
+                if !seconds.is_finite() || seconds <= 0.0 {
+                    return Err(mlua::Error::external(
+                        "app.setMaxDelta expects a positive number of seconds",
+                    ));
+                }
+                *max_delta_setter.borrow_mut() = seconds;
+                Ok(())
+            })?;
+            app.set("setMaxDelta", set_max_delta)?;
+
+            let max_delta_getter = self.max_delta.clone();
+            let get_max_delta = self
+                .lua
+                .create_function(move |_lua, ()| Ok(*max_delta_getter.borrow()))?;
+            app.set("getMaxDelta", get_max_delta)?;
+
+            let frame_rate = self.frame_rate.clone();
+            let get_fps = self
+                .lua
+                .create_function(move |_lua, ()| Ok(*frame_rate.borrow()))?;
+            app.set("getFps", get_fps)?;
+
+            let frame_rate = self.frame_rate.clone();
+            let get_frame_time = self.lua.create_function(move |_lua, ()| {
+                let fps = *frame_rate.borrow();
+                Ok(if fps > 0.0 { 1000.0 / fps } else { 0.0 })
+            })?;
+            app.set("getFrameTime", get_frame_time)?;
+
+            let fixed_timestep = self.fixed_timestep.clone();
+            let set_fixed_timestep =
+                self.lua
+                    .create_function(move |_lua, seconds: Option<f32>| {
+                        let step = match seconds {
+                            None => None,
+                            Some(seconds) if seconds == 0.0 => None,
+                            Some(seconds) if seconds.is_finite() && seconds > 0.0 => Some(seconds),
+                            Some(_) => {
+                                return Err(mlua::Error::external(
+                                    "app.setFixedTimestep expects a positive number or nil",
+                                ))
+                            }
+                        };
+                        *fixed_timestep.borrow_mut() = FixedTimestep {
+                            step,
+                            accumulator: 0.0,
+                        };
+                        Ok(())
+                    })?;
+            app.set("setFixedTimestep", set_fixed_timestep)?;
+
+            let fixed_timestep = self.fixed_timestep.clone();
+            let get_fixed_timestep = self
+                .lua
+                .create_function(move |_lua, ()| Ok(fixed_timestep.borrow().step))?;
+            app.set("getFixedTimestep", get_fixed_timestep)?;
+
+            let fixed_timestep = self.fixed_timestep.clone();
+            let get_interpolation_alpha = self
+                .lua
+                .create_function(move |_lua, ()| Ok(fixed_timestep.borrow().alpha()))?;
+            app.set("getInterpolationAlpha", get_interpolation_alpha)?;
+
+            for (name, slot) in [
+                ("onResize", self.resize_callback.clone()),
+                ("onFocusChanged", self.focus_callback.clone()),
+            ] {
+                let set_callback =
+                    self.lua
+                        .create_function(move |lua, callback: Option<Function>| {
+                            let key = callback
+                                .map(|callback| lua.create_registry_value(callback))
+                                .transpose()?;
+                            if let Some(old) = slot.replace(key) {
+                                lua.remove_registry_value(old)?;
+                            }
+                            Ok(())
+                        })?;
+                app.set(name, set_callback)?;
+            }
+
+            let debug_overlay_getter = self.debug_overlay.clone();
+            let get_debug_overlay = self
+                .lua
+                .create_function(move |_lua, ()| Ok(*debug_overlay_getter.borrow()))?;
+            app.set("getDebugOverlay", get_debug_overlay)?;
+
             let set_nearest_neighbor_scaling =
                 self.lua
                     .create_function(move |lua, enabled: Option<bool>| {
@@ -1281,6 +1835,148 @@ impl Runtime {
             })?;
             app.set("getNearestNeighborScaling", get_nearest_neighbor_scaling)?;
 
+            let title_platform = self.platform.clone();
+            let set_title = self.lua.create_function(move |_lua, title: String| {
+                let mut platform = title_platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                platform.window_requests_mut().title = Some(title);
+                Ok(())
+            })?;
+            app.set("setTitle", set_title)?;
+
+            let size_platform = self.platform.clone();
+            let set_window_size =
+                self.lua
+                    .create_function(move |_lua, (width, height): (f32, f32)| {
+                        // non-positive or non-finite sizes are ignored
+                        if !width.is_finite() || !height.is_finite() || width < 1.0 || height < 1.0
+                        {
+                            return Ok(false);
+                        }
+                        let mut platform = size_platform
+                            .lock()
+                            .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                        platform.window_requests_mut().size = Some((width, height));
+                        Ok(true)
+                    })?;
+            app.set("setWindowSize", set_window_size)?;
+
+            let fullscreen_platform = self.platform.clone();
+            let set_fullscreen = self
+                .lua
+                .create_function(move |_lua, enabled: Option<bool>| {
+                    let mut platform = fullscreen_platform
+                        .lock()
+                        .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                    platform.window_requests_mut().fullscreen = Some(enabled.unwrap_or(true));
+                    Ok(())
+                })?;
+            app.set("setFullscreen", set_fullscreen)?;
+
+            let vsync_platform = self.platform.clone();
+            let set_vsync = self.lua.create_function(move |_lua, enabled: bool| {
+                let mut platform = vsync_platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                platform.window_requests_mut().vsync = Some(enabled);
+                Ok(())
+            })?;
+            app.set("setVsync", set_vsync)?;
+
+            let dpi_platform = self.platform.clone();
+            let get_dpi_scale = self.lua.create_function(move |_lua, ()| {
+                let platform = dpi_platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                Ok(platform.display_scale().dpi)
+            })?;
+            app.set("getDpiScale", get_dpi_scale)?;
+
+            let clear_platform = self.platform.clone();
+            let set_clear_enabled = self.lua.create_function(move |_lua, enabled: bool| {
+                let mut platform = clear_platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                platform.set_clear_enabled(enabled);
+                Ok(())
+            })?;
+            app.set("setClearEnabled", set_clear_enabled)?;
+
+            let clear_platform = self.platform.clone();
+            let is_clear_enabled = self.lua.create_function(move |_lua, ()| {
+                let platform = clear_platform
+                    .lock()
+                    .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+                Ok(platform.clear_enabled())
+            })?;
+            app.set("isClearEnabled", is_clear_enabled)?;
+
+            let quit_requested = self.exit_requested.clone();
+            let quit = self.lua.create_function(move |_lua, code: Option<i32>| {
+                request_exit(&quit_requested, code.unwrap_or(0));
+                Ok(())
+            })?;
+            app.set("quit", quit)?;
+
+            let target_render_state = self.render_state.clone();
+            let set_render_target = self.lua.create_function(
+                move |_lua, (target, clear): (mlua::AnyUserData, Option<bool>)| {
+                    let image = target.borrow::<crate::assets::ImageHandle>()?.clone();
+                    let previous = target_render_state
+                        .lock()
+                        .map_err(|_| mlua::Error::external("render state lock poisoned"))?
+                        .set_target(image, clear.unwrap_or(true));
+                    if let Some(previous) = previous {
+                        crate::renderer::draw_render_target(previous)
+                            .map_err(mlua::Error::external)?;
+                    }
+                    Ok(())
+                },
+            )?;
+            app.set("setRenderTarget", set_render_target)?;
+
+            let reset_render_state = self.render_state.clone();
+            let reset_render_target = self.lua.create_function(move |_lua, ()| {
+                let previous = reset_render_state
+                    .lock()
+                    .map_err(|_| mlua::Error::external("render state lock poisoned"))?
+                    .take_target();
+                if let Some(previous) = previous {
+                    crate::renderer::draw_render_target(previous).map_err(mlua::Error::external)?;
+                }
+                Ok(())
+            })?;
+            app.set("resetRenderTarget", reset_render_target)?;
+
+            let screenshot_platform = self.platform.clone();
+            let screenshot_root = self
+                .environment
+                .canonicalize()
+                .map_err(mlua::Error::external)?;
+            let screenshot_callbacks = self.screenshot_callbacks.clone();
+            let screenshot = self.lua.create_function(
+                move |lua, (path, callback): (String, Option<Function>)| {
+                    if cfg!(target_os = "emscripten") {
+                        return Err(mlua::Error::external(
+                            "app.screenshot is not supported in web builds",
+                        ));
+                    }
+                    let path = crate::assets::resolve_png_export_path(&screenshot_root, &path)?;
+                    let id = screenshot_platform
+                        .lock()
+                        .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+                        .request_screenshot(path);
+                    if let Some(callback) = callback {
+                        screenshot_callbacks
+                            .borrow_mut()
+                            .insert(id, lua.create_registry_value(callback)?);
+                    }
+                    Ok(())
+                },
+            )?;
+            app.set("screenshot", screenshot)?;
+
             self.lua.globals().set("app", app)?;
         }
 
@@ -1361,15 +2057,52 @@ impl Runtime {
             self.lua.globals().set("softrequire", softrequire)?;
         }
 
-        crate::user_input::add_user_input_module(&self.lua, self.platform.clone())?;
+        crate::user_input::add_user_input_module(
+            &self.lua,
+            self.platform.clone(),
+            self.camera.clone(),
+        )?;
+        crate::camera::add_camera_module(&self.lua, self.camera.clone(), self.platform.clone())?;
+        crate::draw::add_draw_module(&self.lua, self.render_state.clone())?;
+        crate::tween::add_tween_module(&self.lua)?;
+        crate::json::add_json_module(&self.lua)?;
+        crate::storage::add_storage_module(&self.lua, self.storage_path.clone())?;
+        crate::random::add_random_module(&self.lua, self.rng.clone())?;
+        crate::events::add_events_module(&self.lua, self.event_handlers.clone())?;
         crate::audio_system::add_audio_module(&self.lua)?;
-        crate::assets::add_assets_module(&self.lua, env_root.clone())?;
+        self.asset_manager = Some(crate::assets::add_assets_module(
+            &self.lua,
+            env_root.clone(),
+        )?);
         crate::fs_module::add_fs_module(&self.lua, env_root.clone())?;
         crate::http::add_http_module(&self.lua)?;
         crate::servers::add_servers_module(&self.lua, env_root.clone())?;
         crate::commands::add_commands_module(&self.lua, env_root.clone())?;
         crate::shader::add_shader_module(&self.lua, env_root.clone())?;
 
+        {
+            let time = self.lua.create_table()?;
+
+            let started_at = self.started_at;
+            let now = self
+                .lua
+                .create_function(move |_lua, ()| Ok(started_at.elapsed().as_secs_f64()))?;
+            time.set("now", now)?;
+
+            let frame_dt = self.frame_dt.clone();
+            let frame = self
+                .lua
+                .create_function(move |_lua, ()| Ok(*frame_dt.borrow()))?;
+            time.set("frame", frame)?;
+
+            let since = self.lua.create_function(move |_lua, start: f64| {
+                Ok(started_at.elapsed().as_secs_f64() - start)
+            })?;
+            time.set("since", since)?;
+
+            self.lua.globals().set("time", time)?;
+        }
+
         let entry_file = env_root.join("main.luau");
 
         let entry_parent = entry_file
@@ -1385,7 +2118,7 @@ impl Runtime {
 
         let exit_requested = self.exit_requested.clone();
         let die = self.lua.create_function(move |_lua, ()| {
-            *exit_requested.borrow_mut() = true;
+            request_exit(&exit_requested, 1);
             Ok(())
         })?;
 
@@ -1444,27 +2177,89 @@ impl Runtime {
                 let rotation = get_global_rotation(&entity)?;
                 Ok(rotation)
             })?;
+            let get_world_transform = self.lua.create_function(move |_lua, entity: Table| {
+                let (x, y, rotation) = get_global_transform(&entity)?;
+                Ok((x, y, rotation))
+            })?;
+            let distance = self
+                .lua
+                .create_function(move |_lua, (a, b): (Table, Table)| {
+                    let (ax, ay) = get_global_position(&a)?;
+                    let (bx, by) = get_global_position(&b)?;
+                    Ok((bx - ax).hypot(by - ay))
+                })?;
+            let angle_between = self
+                .lua
+                .create_function(move |_lua, (a, b): (Table, Table)| {
+                    let (ax, ay) = get_global_position(&a)?;
+                    let (bx, by) = get_global_position(&b)?;
+                    Ok((by - ay).atan2(bx - ax))
+                })?;
+            let circle_overlap = self.lua.create_function(
+                move |_lua, (a, b, radius_a, radius_b): (Table, Table, f32, f32)| {
+                    if !(radius_a >= 0.0 && radius_b >= 0.0) {
+                        return Err(mlua::Error::external(
+                            "transform.circleOverlap expects non-negative radii",
+                        ));
+                    }
+                    let (ax, ay) = get_global_position(&a)?;
+                    let (bx, by) = get_global_position(&b)?;
+                    let reach = radius_a + radius_b;
+                    // compare squared lengths so touching edges count without a sqrt
+                    Ok((bx - ax).powi(2) + (by - ay).powi(2) <= reach * reach)
+                },
+            )?;
+            let point_in_entity =
+                self.lua
+                    .create_function(move |_lua, (x, y, entity): (f32, f32, Table)| {
+                        let (ex, ey, ew, eh) = get_world_aabb(&entity)?;
+                        Ok(x >= ex && x < ex + ew && y >= ey && y < ey + eh)
+                    })?;
+
+            // the topmost hit is the one drawn last: highest z, then the newest entity
+            let pick =
+                self.lua
+                    .create_function(move |_lua, (x, y, candidates): (f32, f32, Table)| {
+                        let mut best: Option<(Table, f64, usize)> = None;
+                        for pair in candidates.pairs::<Value, Table>() {
+                            let (_, candidate) = pair?;
+                            if !is_entity_enabled(&candidate) {
+                                continue;
+                            }
+                            let (ex, ey, ew, eh) = get_world_aabb(&candidate)?;
+                            if !(x >= ex && x < ex + ew && y >= ey && y < ey + eh) {
+                                continue;
+                            }
+                            let z = candidate.get::<f64>("z").unwrap_or(0.0);
+                            let id = candidate.get::<usize>("id").unwrap_or(0);
+                            let on_top = best.as_ref().is_none_or(|(_, best_z, best_id)| {
+                                compare_entity_order(z, id, *best_z, *best_id).is_gt()
+                            });
+                            if on_top {
+                                best = Some((candidate, z, id));
+                            }
+                        }
+                        Ok(best.map(|(entity, _, _)| entity))
+                    })?;
 
             let do_they_overlap = self.lua.create_function(move |_lua, entities: Table| {
-                // go through the entities and see if one overlaps with any of them
-                // if so, then return true
-                // otherwise, false
-
-                for pair1 in entities.pairs::<Value, Table>() {
-                    let (_, entity1) = pair1?;
-                    for pair2 in entities.pairs::<Value, Table>() {
-                        let (_, entity2) = pair2?;
+                // resolve every world AABB once, then check each unordered pair
+                // (the overlap test is symmetric, so (a, b) covers (b, a))
+                let mut bounds = Vec::new();
+                for pair in entities.pairs::<Value, Table>() {
+                    let (_, entity) = pair?;
+                    let aabb = get_world_aabb(&entity)?;
+                    bounds.push((entity, aabb));
+                }
+
+                for (index, (entity1, aabb1)) in bounds.iter().enumerate() {
+                    for (entity2, aabb2) in &bounds[index + 1..] {
+                        // the same entity listed twice never overlaps itself
                         if entity1 == entity2 {
                             continue;
                         }
 
-                        let (x1, y1) = get_global_position(&entity1)?;
-                        let (w1, h1) = get_global_size(&entity1)?;
-
-                        let (x2, y2) = get_global_position(&entity2)?;
-                        let (w2, h2) = get_global_size(&entity2)?;
-
-                        if x1 < x2 + w2 && x1 + w1 > x2 && y1 < y2 + h2 && y1 + h1 > y2 {
+                        if aabbs_overlap(*aabb1, *aabb2) {
                             return Ok(true);
                         }
                     }
@@ -1473,23 +2268,85 @@ impl Runtime {
                 Ok(false)
             })?;
 
-            let raycast_entities = self.entities.clone();
-            let raycast = self.lua.create_function(
-                move |lua,
-                      (origin_x, origin_y, dir_x, dir_y, max_distance, options): (
-                    f32,
-                    f32,
-                    f32,
-                    f32,
-                    Option<f32>,
-                    Option<Table>,
-                )| {
-                    let direction_len_sq = dir_x * dir_x + dir_y * dir_y;
-                    if direction_len_sq <= f32::EPSILON || !direction_len_sq.is_finite() {
-                        return Ok(None::<Table>);
-                    }
-
-                    let direction_len = direction_len_sq.sqrt();
+            let get_overlapping =
+                self.lua
+                    .create_function(move |lua, (entity, candidates): (Table, Table)| {
+                        let aabb = get_world_aabb(&entity)?;
+                        let overlapping = lua.create_table()?;
+                        for pair in candidates.pairs::<Value, Table>() {
+                            let (_, candidate) = pair?;
+                            if candidate == entity {
+                                continue;
+                            }
+                            if aabbs_overlap(aabb, get_world_aabb(&candidate)?) {
+                                overlapping.raw_push(candidate)?;
+                            }
+                        }
+                        Ok(overlapping)
+                    })?;
+
+            let query_entities = self.entities.clone();
+            let query_spatial_hash = self.spatial_hash.clone();
+            let query_region = self.lua.create_function(
+                move |lua, (x, y, width, height): (f32, f32, f32, f32)| {
+                    if !(x.is_finite() && y.is_finite() && width.is_finite() && height.is_finite())
+                        || width < 0.0
+                        || height < 0.0
+                    {
+                        return Err(mlua::Error::external(
+                            "transform.queryRegion expects a finite position and a non-negative size",
+                        ));
+                    }
+
+                    let entities = query_entities.borrow();
+                    let mut spatial_hash = query_spatial_hash.borrow_mut();
+                    if spatial_hash.is_stale() {
+                        spatial_hash.clear();
+                        for (id, entity_data) in entities.iter() {
+                            // the root follows the window size and would match every query
+                            if *id == 0 {
+                                continue;
+                            }
+                            let Ok(entity) = lua.registry_value::<Table>(&entity_data.luau_key)
+                            else {
+                                continue;
+                            };
+                            if !is_entity_enabled(&entity) {
+                                continue;
+                            }
+                            if let Ok(aabb) = get_world_aabb(&entity) {
+                                spatial_hash.insert(*id, aabb);
+                            }
+                        }
+                    }
+
+                    let found = lua.create_table()?;
+                    for id in spatial_hash.query((x, y, width, height)) {
+                        if let Some(entity_data) = entities.get(&id) {
+                            found.raw_push(lua.registry_value::<Table>(&entity_data.luau_key)?)?;
+                        }
+                    }
+                    Ok(found)
+                },
+            )?;
+
+            let raycast_entities = self.entities.clone();
+            let raycast = self.lua.create_function(
+                move |lua,
+                      (origin_x, origin_y, dir_x, dir_y, max_distance, options): (
+                    f32,
+                    f32,
+                    f32,
+                    f32,
+                    Option<f32>,
+                    Option<Table>,
+                )| {
+                    let direction_len_sq = dir_x * dir_x + dir_y * dir_y;
+                    if direction_len_sq <= f32::EPSILON || !direction_len_sq.is_finite() {
+                        return Ok(None::<Table>);
+                    }
+
+                    let direction_len = direction_len_sq.sqrt();
                     let ray_x = dir_x / direction_len;
                     let ray_y = dir_y / direction_len;
                     let max_distance = max_distance
@@ -1581,8 +2438,16 @@ impl Runtime {
 
             transforms.set("getWorldPosition", get_world_position)?;
             transforms.set("getWorldRotation", get_world_rotation)?;
+            transforms.set("getWorldTransform", get_world_transform)?;
+            transforms.set("distance", distance)?;
+            transforms.set("angleBetween", angle_between)?;
+            transforms.set("circleOverlap", circle_overlap)?;
+            transforms.set("pointInEntity", point_in_entity)?;
+            transforms.set("pick", pick)?;
 
             transforms.set("doTheyOverlap", do_they_overlap)?;
+            transforms.set("getOverlapping", get_overlapping)?;
+            transforms.set("queryRegion", query_region)?;
             transforms.set("raycast", raycast)?;
         }
 
@@ -1596,6 +2461,11 @@ impl Runtime {
             })?;
 
             ecs.set("addSystem", add_system)?;
+
+            // shared between every system update so systems don't need to talk through _G
+            let context = self.lua.create_table()?;
+            ecs.set("context", context.clone())?;
+            self.system_context = Some(self.lua.create_registry_value(context)?);
         }
 
         // Entities
@@ -1610,18 +2480,23 @@ impl Runtime {
             let new =
                 self.lua.create_function(
                     move |lua,
-                          (name, _parent, x, y): (
+                          (name, parent, x, y): (
                         String,
                         Option<Table>,
                         Option<f64>,
                         Option<f64>,
                     )| {
+                        // plain tables without an id still get wired up on the Lua side
+                        let parent_id = match &parent {
+                            Some(parent) => parent.get::<Option<usize>>("id")?,
+                            None => None,
+                        };
                         let luau = create_entity_table(
                             lua,
                             &name,
                             x.unwrap_or(0.0),
                             y.unwrap_or(0.0),
-                            _parent,
+                            parent,
                         )?;
 
                         let mut max = entity_max_clone.borrow_mut();
@@ -1632,15 +2507,23 @@ impl Runtime {
 
                         let reg_key = lua.create_registry_value(&luau)?;
 
+                        let mut entities = entities.borrow_mut();
+                        let parent_id = parent_id.filter(|parent_id| {
+                            entities
+                                .get_mut(parent_id)
+                                .map(|parent| parent.children.push(id))
+                                .is_some()
+                        });
                         let entity = hierarchy::Entity {
                             components: Vec::new(),
                             children: Vec::new(),
-                            parent: None,
+                            parent: parent_id,
                             id,
                             luau_key: reg_key,
+                            started: false,
                         };
 
-                        entities.borrow_mut().insert(id, entity);
+                        entities.insert(id, entity);
 
                         Ok(luau)
                     },
@@ -1649,7 +2532,7 @@ impl Runtime {
             ecs.set("newEntity", new)?;
 
             let table_remove_delete = table_remove.clone();
-            let delete = self.lua.create_function(move |_lua, entity: Table| {
+            let delete = self.lua.create_function(move |lua, entity: Table| {
                 // Recursive deletion
                 let mut ids_to_remove = Vec::new();
                 let mut stack = vec![entity.clone()];
@@ -1659,6 +2542,15 @@ impl Runtime {
                         ids_to_remove.push(id);
                     }
 
+                    if let Ok(components) = current.get::<Table>("components") {
+                        for component in components.sequence_values::<Table>().flatten() {
+                            // a failing cleanup hook shouldn't leave half the tree alive
+                            if let Err(e) = run_component_destroy(&current, &component) {
+                                report_lua_error(lua, "component destroy", &e);
+                            }
+                        }
+                    }
+
                     if let Ok(children) = current.get::<Table>("children") {
                         for pair in children.pairs::<Value, Table>() {
                             if let Ok((_, child)) = pair {
@@ -1669,6 +2561,12 @@ impl Runtime {
                 }
 
                 let mut entities = entities_delete.borrow_mut();
+                if let Some(&id) = ids_to_remove.first() {
+                    let parent_id = entities.get(&id).and_then(|entry| entry.parent);
+                    if let Some(parent) = parent_id.and_then(|parent| entities.get_mut(&parent)) {
+                        parent.children.retain(|child| *child != id);
+                    }
+                }
                 for id in &ids_to_remove {
                     entities.remove(id);
                 }
@@ -1692,6 +2590,69 @@ impl Runtime {
 
             ecs.set("deleteEntity", delete)?;
 
+            let entities_reparent = self.entities.clone();
+            let table_remove_reparent = table_remove.clone();
+            let set_parent = self.lua.create_function(
+                move |_lua, (entity, new_parent): (Table, Option<Table>)| {
+                    let id = entity.get::<usize>("id")?;
+                    if id == 0 {
+                        return Err(mlua::Error::external(
+                            "ecs.setParent cannot move the root entity",
+                        ));
+                    }
+
+                    // walking up from the new parent must never reach the entity itself
+                    let mut ancestor = new_parent.clone();
+                    while let Some(current) = ancestor {
+                        if current == entity {
+                            return Err(mlua::Error::external(
+                                "ecs.setParent would make the entity its own ancestor",
+                            ));
+                        }
+                        ancestor = current.get::<Option<Table>>("parent")?;
+                    }
+
+                    let old_parent = entity.get::<Option<Table>>("parent")?;
+                    if let Some(old_parent) = &old_parent {
+                        let children: Table = old_parent.get("children")?;
+                        let len = children.len()?;
+                        for i in 1..=len {
+                            if children.get::<Table>(i)? == entity {
+                                table_remove_reparent.call::<()>((children, i))?;
+                                break;
+                            }
+                        }
+                    }
+
+                    let new_parent_id = match &new_parent {
+                        Some(parent) => {
+                            parent.get::<Table>("children")?.push(&entity)?;
+                            entity.set("parent", parent)?;
+                            Some(parent.get::<usize>("id")?)
+                        }
+                        None => {
+                            entity.set("parent", Value::Nil)?;
+                            None
+                        }
+                    };
+
+                    let mut entities = entities_reparent.borrow_mut();
+                    let old_parent_id = entities.get(&id).and_then(|entry| entry.parent);
+                    if let Some(old_parent) = old_parent_id.and_then(|old| entities.get_mut(&old)) {
+                        old_parent.children.retain(|child| *child != id);
+                    }
+                    if let Some(new_parent) = new_parent_id.and_then(|new| entities.get_mut(&new)) {
+                        new_parent.children.push(id);
+                    }
+                    if let Some(entry) = entities.get_mut(&id) {
+                        entry.parent = new_parent_id;
+                    }
+                    Ok(())
+                },
+            )?;
+
+            ecs.set("setParent", set_parent)?;
+
             let duplicate =
                 self.lua
                     .create_function(move |lua, (target_entity, parent): (Table, Table)| {
@@ -1723,6 +2684,43 @@ impl Runtime {
 
             ecs.set("findFirstChild", find_first_child)?;
 
+            let find_all_children =
+                self.lua
+                    .create_function(move |lua, (parent, name): (Table, String)| {
+                        let matches = lua.create_table()?;
+                        if let Ok(children) = parent.get::<Table>("children") {
+                            for child in children.sequence_values::<Table>().flatten() {
+                                if child.get::<String>("name").is_ok_and(|n| n == name) {
+                                    matches.raw_push(child)?;
+                                }
+                            }
+                        }
+                        Ok(matches)
+                    })?;
+
+            ecs.set("findAllChildren", find_all_children)?;
+
+            // breadth-first, so the shallowest match wins
+            let find_descendant =
+                self.lua
+                    .create_function(move |_lua, (parent, name): (Table, String)| {
+                        let mut queue = VecDeque::from([parent]);
+                        while let Some(current) = queue.pop_front() {
+                            let Ok(children) = current.get::<Table>("children") else {
+                                continue;
+                            };
+                            for child in children.sequence_values::<Table>().flatten() {
+                                if child.get::<String>("name").is_ok_and(|n| n == name) {
+                                    return Ok(Some(child));
+                                }
+                                queue.push_back(child);
+                            }
+                        }
+                        Ok(None)
+                    })?;
+
+            ecs.set("findDescendant", find_descendant)?;
+
             // create root entity
             let root_table = create_entity_table(&self.lua, "root", 0.0, 0.0, None)?;
             root_table.set("id", 0)?;
@@ -1734,6 +2732,7 @@ impl Runtime {
                 parent: None,
                 id: 0,
                 luau_key: root_key,
+                started: false,
             };
             self.entities.borrow_mut().insert(0, root_entity);
             ecs.set("root", root_table)?;
@@ -1744,6 +2743,7 @@ impl Runtime {
             crate::core::add_core_components(
                 &self.lua,
                 self.platform.clone(),
+                self.camera.clone(),
                 self.render_state.clone(),
                 self.environment.clone(),
             )?; // a lot of heavy lifting
@@ -1826,19 +2826,7 @@ impl Runtime {
                         }
 
                         let component: Table = components.get(index)?;
-                        if let Ok(destroy) = component.get::<Function>("destroy") {
-                            let component_name = describe_component_name(&component, Some(&entity));
-                            protect_lua_call(
-                                &format!("running component destroy callback ({component_name})"),
-                                || destroy.call::<()>((&entity, &component)),
-                            )?;
-                        } else if let Ok(on_destroy) = component.get::<Function>("onDestroy") {
-                            let component_name = describe_component_name(&component, Some(&entity));
-                            protect_lua_call(
-                                &format!("running component onDestroy callback ({component_name})"),
-                                || on_destroy.call::<()>((&entity, &component)),
-                            )?;
-                        }
+                        run_component_destroy(&entity, &component)?;
                         component.set("entity", Value::Nil)?;
 
                         table_remove_component.call::<()>((&components, index))?;
@@ -1846,6 +2834,105 @@ impl Runtime {
                     })?;
 
             ecs.set("removeComponent", remove_component)?;
+
+            let get_component =
+                self.lua
+                    .create_function(move |lua, (entity, name): (Table, String)| {
+                        let components: Table = entity.get("components")?;
+                        for component in components.sequence_values::<Table>() {
+                            let component = component?;
+                            if component_matches_name(lua, &component, &name) {
+                                return Ok(Some(component));
+                            }
+                        }
+                        Ok(None)
+                    })?;
+
+            ecs.set("getComponent", get_component)?;
+
+            let entities = self.entities.clone();
+            let query = self.lua.create_function(move |lua, name: String| {
+                // collect first so component lookups can't overlap a borrow of the entity map
+                let mut candidates: Vec<(hierarchy::EntityId, Table)> = Vec::new();
+                for (id, entity) in entities.borrow().iter() {
+                    candidates.push((*id, lua.registry_value::<Table>(&entity.luau_key)?));
+                }
+                // ids grow with creation order, which keeps results stable between frames
+                candidates.sort_by_key(|(id, _)| *id);
+
+                let results = lua.create_table()?;
+                for (_, entity) in candidates {
+                    let Ok(components) = entity.get::<Table>("components") else {
+                        continue;
+                    };
+                    for component in components.sequence_values::<Table>() {
+                        if component_matches_name(lua, &component?, &name) {
+                            results.raw_push(entity)?;
+                            break;
+                        }
+                    }
+                }
+                Ok(results)
+            })?;
+
+            ecs.set("query", query)?;
+        }
+
+        // Scenes
+        {
+            let scene_root = env_root.clone();
+            let entities = self.entities.clone();
+            let systems = self.systems.clone();
+            let event_handlers = self.event_handlers.clone();
+            let scene = self.scene.clone();
+            let load_scene = self.lua.create_function(move |lua, path: String| {
+                // resolve before tearing anything down, so a typo keeps the current scene
+                let script = resolve_existing_softrequire_path(&scene_root, &path)
+                    .map_err(mlua::Error::external)?
+                    .ok_or_else(|| {
+                        mlua::Error::external(format!("ecs.loadScene: scene '{path}' not found"))
+                    })?;
+                let name = script
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.clone());
+
+                // the tops of every tree except the root: its children plus entities created
+                // without a parent; deleting those takes the rest of the scene with them
+                let mut tops: Vec<(usize, Table)> = Vec::new();
+                for entity in entities.borrow().values() {
+                    if entity.id != 0 && matches!(entity.parent, None | Some(0)) {
+                        tops.push((entity.id, lua.registry_value(&entity.luau_key)?));
+                    }
+                }
+                tops.sort_by_key(|(id, _)| *id);
+                let delete: Function = lua.globals().get::<Table>("ecs")?.get("deleteEntity")?;
+                for (_, entity) in tops {
+                    delete.call::<()>(entity)?;
+                }
+
+                systems.borrow_mut().clear();
+                // handlers from the old scene would otherwise keep acting on deleted entities
+                event_handlers.borrow_mut().clear();
+                {
+                    let mut scene = scene.borrow_mut();
+                    scene.name = name;
+                    scene.loads += 1;
+                }
+
+                lua.load(script.as_path())
+                    .set_name(format!("@{}", script.with_extension("").display()))
+                    .exec()
+            })?;
+
+            ecs.set("loadScene", load_scene)?;
+
+            let scene = self.scene.clone();
+            let current_scene = self
+                .lua
+                .create_function(move |_lua, ()| Ok(scene.borrow().name.clone()))?;
+
+            ecs.set("currentScene", current_scene)?;
         }
 
         self.lua.globals().set("ecs", ecs)?;
@@ -1861,6 +2948,79 @@ impl Runtime {
         Ok(())
     }
 
+    // the first frame only records the window state; later frames report what changed
+    fn dispatch_window_events(&mut self) {
+        let Ok(platform) = self.platform.lock() else {
+            return;
+        };
+        let window = platform.window();
+        let focused = platform.is_focused();
+        drop(platform);
+
+        let size = (window.width, window.height);
+        if self.last_window_size.is_some_and(|last| last != size) {
+            self.call_app_callback(&self.resize_callback, "app.onResize", size);
+        }
+        self.last_window_size = Some(size);
+
+        if focused != self.last_focused {
+            self.last_focused = focused;
+            self.call_app_callback(&self.focus_callback, "app.onFocusChanged", focused);
+        }
+    }
+
+    // failed screenshots without a callback are printed instead
+    fn dispatch_screenshot_results(&self) {
+        let results = match self.platform.lock() {
+            Ok(mut platform) => platform.take_screenshot_results(),
+            Err(_) => return,
+        };
+        for (id, result) in results {
+            let Some(key) = self.screenshot_callbacks.borrow_mut().remove(&id) else {
+                if let Err(error) = result {
+                    eprintln!("screenshot failed: {error}");
+                }
+                continue;
+            };
+            let (saved, error) = match result {
+                Ok(()) => (true, None),
+                Err(error) => (false, Some(error)),
+            };
+            let outcome = self
+                .lua
+                .registry_value::<Function>(&key)
+                .and_then(|callback| {
+                    protect_lua_call("running app.screenshot callback", || {
+                        callback.call::<()>((saved, error))
+                    })
+                });
+            let _ = self.lua.remove_registry_value(key);
+            if let Err(e) = outcome {
+                report_lua_error(&self.lua, "app.screenshot", &e);
+            }
+        }
+    }
+
+    fn call_app_callback(
+        &self,
+        slot: &Rc<RefCell<Option<RegistryKey>>>,
+        name: &str,
+        args: impl IntoLuaMulti,
+    ) {
+        let callback = match slot.borrow().as_ref() {
+            Some(key) => self.lua.registry_value::<Function>(key),
+            None => return,
+        };
+        let result = callback.and_then(|callback| {
+            protect_lua_call(&format!("running {name} callback"), || {
+                callback.call::<()>(args)
+            })
+        });
+        if let Err(e) = result {
+            report_lua_error(&self.lua, name, &e);
+        }
+    }
+
     fn poll_http_callbacks(&self) {
         let globals = self.lua.globals();
         let http = match globals.get::<Table>("http") {
@@ -1872,10 +3032,7 @@ impl Runtime {
             Err(_) => return,
         };
         if let Err(e) = protect_lua_call("polling HTTP callbacks", || poll.call::<()>(())) {
-            eprintln!(
-                "\x1b[31mLua Error:\x1b[0m Failed to poll HTTP callbacks\n{}",
-                describe_lua_error(&e)
-            );
+            report_lua_error(&self.lua, "HTTP callback polling", &e);
         }
     }
 
@@ -1890,10 +3047,7 @@ impl Runtime {
             Err(_) => return,
         };
         if let Err(e) = protect_lua_call("polling server callbacks", || poll.call::<()>(())) {
-            eprintln!(
-                "\x1b[31mLua Error:\x1b[0m Failed to poll server callbacks\n{}",
-                describe_lua_error(&e)
-            );
+            report_lua_error(&self.lua, "server callback polling", &e);
         }
     }
 
@@ -1908,14 +3062,18 @@ impl Runtime {
             }
         };
 
+        // presses a system already consumed don't reach entities
+        let available = |button: &str| {
+            input.mouse_pressed.contains(button) && !input.mouse_consumed.contains(button)
+        };
         let mut triggered_events = HashSet::<EntityListenEvent>::new();
-        if input.mouse_pressed.contains("left") {
+        if available("left") {
             triggered_events.insert(EntityListenEvent::LeftClick);
         }
-        if input.mouse_pressed.contains("right") {
+        if available("right") {
             triggered_events.insert(EntityListenEvent::RightClick);
         }
-        if input.mouse_pressed.contains("middle") {
+        if available("middle") {
             triggered_events.insert(EntityListenEvent::MiddleClick);
         }
         if input.wheel_y > 0.0 {
@@ -1928,6 +3086,15 @@ impl Runtime {
             return;
         }
 
+        let window = match self.platform.lock() {
+            Ok(platform) => platform.window(),
+            Err(_) => return,
+        };
+        let mouse = self
+            .camera
+            .borrow()
+            .screen_to_world(window, mouse.x, mouse.y);
+
         let mut hovered_entities = Vec::<(Table, f64, usize)>::new();
         {
             let entities = self.entities.borrow();
@@ -1944,10 +3111,7 @@ impl Runtime {
                     }
                     Ok(false) => {}
                     Err(error) => {
-                        eprintln!(
-                            "\x1b[31mLua Error:\x1b[0m Failed to hit-test entity listener target: {}",
-                            error
-                        );
+                        report_lua_error(&self.lua, "entity listener hit-test", &error);
                     }
                 }
             }
@@ -1970,10 +3134,7 @@ impl Runtime {
                     let callback = match self.lua.registry_value::<Function>(&listener.callback) {
                         Ok(callback) => callback,
                         Err(error) => {
-                            eprintln!(
-                                "\x1b[31mLua Error:\x1b[0m Failed to resolve entity listener callback: {}",
-                                error
-                            );
+                            report_lua_error(&self.lua, "entity listener lookup", &error);
                             continue;
                         }
                     };
@@ -1987,10 +3148,7 @@ impl Runtime {
                     ) {
                         Ok(payload) => payload,
                         Err(error) => {
-                            eprintln!(
-                                "\x1b[31mLua Error:\x1b[0m Failed to build entity listener event: {}",
-                                error
-                            );
+                            report_lua_error(&self.lua, "entity listener event", &error);
                             continue;
                         }
                     };
@@ -2003,10 +3161,7 @@ impl Runtime {
             if let Err(error) = protect_lua_call("running entity listener callback", || {
                 callback.call::<()>((entity.clone(), payload.clone()))
             }) {
-                eprintln!(
-                    "\x1b[31mLua Error in entity listener callback:\x1b[0m\n{}",
-                    describe_lua_error(&error)
-                );
+                report_lua_error(&self.lua, "entity listener callback", &error);
             }
         }
     }
@@ -2809,60 +3964,85 @@ impl Runtime {
 
         Ok(())
     }
-    pub fn update(&mut self, dt: f32) -> Result<(), String> {
-        crate::core::begin_ui_frame();
-
-        self.set_mouse_table()
-            .map_err(|error| format!("failed to sync mouse state into Lua: {error}"))?;
-        self.set_window_table()
-            .map_err(|error| format!("failed to sync window state into Lua: {error}"))?;
-        self.poll_http_callbacks();
-        self.poll_server_callbacks();
-        self.dispatch_entity_listeners();
+    // one simulation step: systems, then non-rendering components, then physics
+    fn step_simulation(&mut self, dt: f32) -> Vec<(Table, Vec<(Table, Function)>)> {
+        self.spatial_hash.borrow_mut().mark_stale();
+        self.run_systems(dt);
+        let rendering_entities = self.update_components(Some(dt));
+        if let Err(e) = self.simulate_rapier_physics(dt) {
+            report_lua_error(&self.lua, "Rapier2D physics", &e);
+        }
+        rendering_entities
+    }
 
-        let clear = (|| -> mlua::Result<PlatformColor> {
-            let app: Table = self.lua.globals().get("app")?;
-            let bg: Table = app.get("bg")?;
-            let r: u8 = bg.get("r")?;
-            let g: u8 = bg.get("g")?;
-            let b: u8 = bg.get("b")?;
-            let a: u8 = bg.get("a")?;
-            Ok(PlatformColor::rgba(r, g, b, a))
-        })()
-        .map_err(|error| {
-            format!(
-                "failed to resolve app background color:\n{}",
-                describe_lua_error(&error)
-            )
-        })?;
-        self.platform
-            .lock()
-            .map_err(|_| "platform lock poisoned while updating clear color".to_string())?
-            .set_clear_color(clear);
+    fn run_systems(&self, dt: f32) {
+        let context: Option<Table> = match &self.system_context {
+            Some(key) => self.lua.registry_value(key).ok(),
+            None => None,
+        };
+        // resolved up front so systems can add systems or load a scene while the loop runs
+        let systems: Vec<Table> = self
+            .systems
+            .borrow()
+            .iter()
+            .filter_map(|key| match self.lua.registry_value(key) {
+                Ok(system) => Some(system),
+                Err(e) => {
+                    report_lua_error(&self.lua, "system lookup", &e);
+                    None
+                }
+            })
+            .collect();
+        let loads = self.scene.borrow().loads;
+        for system in systems {
+            // the rest belong to a scene that was just unloaded
+            if self.scene.borrow().loads != loads {
+                break;
+            }
+            if let Ok(Value::Function(update)) = system.get::<Value>("update") {
+                if let Err(e) = protect_lua_call("running system update callback", || {
+                    update.call::<()>((system.clone(), dt, context.clone()))
+                }) {
+                    report_lua_error(&self.lua, "system update", &e);
+                }
+            }
+        }
+    }
 
-        {
-            let keys = self.systems.borrow();
-            for key in keys.iter() {
-                let system: Table = match self.lua.registry_value(key) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        eprintln!("\x1b[31mLua Error:\x1b[0m Failed to get system: {}", e);
-                        continue;
-                    }
-                };
-                if let Ok(Value::Function(update)) = system.get::<Value>("update") {
-                    if let Err(e) = protect_lua_call("running system update callback", || {
-                        update.call::<()>((system.clone(), dt))
-                    }) {
-                        eprintln!(
-                            "\x1b[31mLua Error in system update:\x1b[0m\n{}",
-                            describe_lua_error(&e)
-                        );
-                    }
+    // the optional `start(self)` / `update(self, dt)` functions set directly on an entity;
+    // `start` runs once, in the first simulation step that finds the entity enabled
+    fn run_entity_hooks(&self, ent: &Table, id: hierarchy::EntityId, dt: f32) {
+        let first_step = self
+            .entities
+            .borrow_mut()
+            .get_mut(&id)
+            .is_some_and(|entity| !std::mem::replace(&mut entity.started, true));
+        let entity_name = ent.get::<String>("name").unwrap_or_default();
+
+        if first_step {
+            if let Ok(Value::Function(start)) = ent.get::<Value>("start") {
+                if let Err(e) = protect_lua_call(
+                    &format!("running entity start callback ('{entity_name}')"),
+                    || start.call::<()>(ent),
+                ) {
+                    report_lua_error(&self.lua, "entity start", &e);
                 }
             }
         }
 
+        if let Ok(Value::Function(update)) = ent.get::<Value>("update") {
+            if let Err(e) = protect_lua_call(
+                &format!("running entity update callback ('{entity_name}')"),
+                || update.call::<()>((ent, dt)),
+            ) {
+                report_lua_error(&self.lua, "entity update", &e);
+            }
+        }
+    }
+
+    // runs the non-rendering component updates when `dt` is given, and returns the rendering
+    // components grouped by entity either way
+    fn update_components(&self, dt: Option<f32>) -> Vec<(Table, Vec<(Table, Function)>)> {
         let mut ordered_entities: Vec<(Table, f64, usize)> = Vec::new();
 
         {
@@ -2870,6 +4050,10 @@ impl Runtime {
             ordered_entities.reserve(entities.len());
             for entity in entities.values() {
                 if let Ok(table) = self.lua.registry_value::<Table>(&entity.luau_key) {
+                    // disabled entities stay in the hierarchy but none of their components run
+                    if !is_entity_enabled(&table) {
+                        continue;
+                    }
                     let z = table.get::<f64>("z").unwrap_or(0.0);
                     let id = table.get::<usize>("id").unwrap_or(0);
                     ordered_entities.push((table, z, id));
@@ -2879,92 +4063,372 @@ impl Runtime {
 
         ordered_entities.sort_by(|a, b| compare_entity_order(a.1, a.2, b.1, b.2));
 
-        let mut rendering_components: Vec<(Table, Table, Function)> = Vec::new();
-        rendering_components.reserve(ordered_entities.len());
+        // rendering components grouped by entity, so the draw-order sort below reads each
+        // entity's z/id once instead of once per component
+        let mut rendering_entities: Vec<(Table, Vec<(Table, Function)>)> = Vec::new();
+        rendering_entities.reserve(ordered_entities.len());
+
+        for (ent, _, id) in ordered_entities {
+            if let Some(dt) = dt {
+                self.run_entity_hooks(&ent, id, dt);
+            }
 
-        for (ent, _, _) in ordered_entities {
+            let mut rendering_components: Vec<(Table, Function)> = Vec::new();
             // run through all the components
 
             let components: Table = match ent.get("components") {
                 Ok(c) => c,
                 Err(e) => {
-                    eprintln!(
-                        "\x1b[31mLua Error:\x1b[0m Entity missing components table: {}",
-                        e
-                    );
+                    report_lua_error(&self.lua, "entity components table", &e);
                     continue;
                 }
             };
 
+            let mut ordered_components = Vec::new();
             for component in components.sequence_values::<Table>() {
-                let component = match component {
-                    Ok(v) => v,
-                    Err(e) => {
-                        eprintln!(
-                            "\x1b[31mLua Error:\x1b[0m Failed to iterate components: {}",
-                            e
-                        );
-                        continue;
+                match component {
+                    Ok(component) => {
+                        let order = component.get::<f64>("order").unwrap_or(0.0);
+                        ordered_components.push((order, component));
                     }
-                };
+                    Err(e) => report_lua_error(&self.lua, "component iteration", &e),
+                }
+            }
+            // stable, so components with equal `order` keep the order they were added in
+            ordered_components
+                .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+            for (_, component) in ordered_components {
                 let update: Function = match component.get("update") {
                     Ok(u) => u,
                     Err(e) => {
-                        eprintln!("\x1b[31mLua Error:\x1b[0m Component missing update: {}", e);
+                        report_lua_error(&self.lua, "component update lookup", &e);
                         continue;
                     }
                 };
 
                 let is_rendering = component.get::<bool>("NEOLOVE_RENDERING").unwrap_or(false);
-                if !is_rendering {
+                if is_rendering {
+                    rendering_components.push((component, update));
+                } else if let Some(dt) = dt {
                     let component_name = describe_component_name(&component, Some(&ent));
                     if let Err(e) = protect_lua_call(
                         &format!("running component update callback ({component_name})"),
                         || update.call::<()>((&ent, component, dt)),
                     ) {
-                        eprintln!(
-                            "\x1b[31mLua Error in component update:\x1b[0m\n{}",
-                            describe_lua_error(&e)
-                        );
+                        report_lua_error(&self.lua, "component update", &e);
                     }
-                } else {
-                    rendering_components.push((ent.clone(), component, update));
                 }
             }
+            if !rendering_components.is_empty() {
+                rendering_entities.push((ent, rendering_components));
+            }
         }
 
-        if let Err(e) = self.simulate_rapier_physics(dt) {
-            eprintln!(
-                "\x1b[31mLua Error in Rapier2D physics:\x1b[0m\n{}",
-                describe_lua_error(&e)
-            );
-        }
+        rendering_entities
+    }
 
-        for trio in rendering_components {
-            let component_name = describe_component_name(&trio.1, Some(&trio.0));
-            if let Err(e) = protect_lua_call(
-                &format!("running rendering component update callback ({component_name})"),
-                || trio.2.call::<()>((trio.0, trio.1, dt)),
-            ) {
-                eprintln!(
-                    "\x1b[31mLua Error in rendering component update:\x1b[0m\n{}",
-                    describe_lua_error(&e)
-                );
+    pub fn update(&mut self, dt: f32) -> Result<(), String> {
+        crate::core::begin_ui_frame(&self.lua);
+        crate::lua_error::flush_lua_error_reports(&self.error_debouncer, Instant::now());
+        self.track_frame_rate(dt);
+        self.spatial_hash.borrow_mut().mark_stale();
+        let (frame_start, overlay_start) = {
+            let render_state = self
+                .render_state
+                .lock()
+                .map_err(|_| "render state lock poisoned before the frame".to_string())?;
+            (
+                render_state.command_count(),
+                render_state.overlay_command_count(),
+            )
+        };
+
+        self.set_mouse_table()
+            .map_err(|error| format!("failed to sync mouse state into Lua: {error}"))?;
+        self.set_window_table()
+            .map_err(|error| format!("failed to sync window state into Lua: {error}"))?;
+        self.dispatch_window_events();
+        self.dispatch_screenshot_results();
+        self.poll_http_callbacks();
+        self.poll_server_callbacks();
+        self.dispatch_entity_listeners();
+        if let Some(manager) = &self.asset_manager {
+            if let Ok(mut manager) = manager.lock() {
+                manager.tick_auto_gc(dt);
             }
         }
 
-        Ok(())
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::time::{SystemTime, UNIX_EPOCH};
+        // gameplay time from here on; the fps readout and asset GC above use real time. A stall
+        // (window drag, breakpoint) pauses gameplay instead of fast-forwarding it
+        let dt = dt.clamp(0.0, self.max_delta()) * *self.time_scale.borrow();
+        *self.frame_dt.borrow_mut() = dt;
 
-    fn assert_close(actual: f32, expected: f32) {
-        let diff = (actual - expected).abs();
-        assert!(
+        let clear = (|| -> mlua::Result<PlatformColor> {
+            let app: Table = self.lua.globals().get("app")?;
+            let bg: Table = app.get("bg")?;
+            let r: u8 = bg.get("r")?;
+            let g: u8 = bg.get("g")?;
+            let b: u8 = bg.get("b")?;
+            let a: u8 = bg.get("a")?;
+            Ok(PlatformColor::rgba(r, g, b, a))
+        })()
+        .map_err(|error| {
+            format!(
+                "failed to resolve app background color:\n{}",
+                describe_lua_error(&error)
+            )
+        })?;
+        self.platform
+            .lock()
+            .map_err(|_| "platform lock poisoned while updating clear color".to_string())?
+            .set_clear_color(clear);
+
+        let paused = *self.paused.borrow();
+        let fixed_step = self.fixed_timestep.borrow().step;
+        let rendering_entities = match fixed_step {
+            // the scene stays on screen but nothing advances, fixed-step time included
+            _ if paused => self.update_components(None),
+            Some(step) => {
+                self.fixed_timestep.borrow_mut().accumulator += dt;
+                let mut steps = 0;
+                let mut last_step = None;
+                while self.fixed_timestep.borrow().accumulator >= step {
+                    if steps == MAX_FIXED_STEPS_PER_FRAME {
+                        // too far behind to catch up: drop the backlog instead of stalling
+                        self.fixed_timestep.borrow_mut().accumulator %= step;
+                        break;
+                    }
+                    last_step = Some(self.step_simulation(step));
+                    self.fixed_timestep.borrow_mut().accumulator -= step;
+                    steps += 1;
+                }
+                match last_step {
+                    Some(rendering_entities) => rendering_entities,
+                    None => self.update_components(None),
+                }
+            }
+            None => self.step_simulation(dt),
+        };
+
+        // everything the rendering components queue is in world space; the camera maps it to
+        // the screen and leaves the overlay (drawn afterwards) alone
+        let camera_start = self
+            .render_state
+            .lock()
+            .map_err(|_| "render state lock poisoned before rendering".to_string())?
+            .command_count();
+
+        // non-rendering updates may have moved entities between layers, so draw by the z values
+        // they hold now; each entity's components keep their order
+        let mut rendering_entities: Vec<(f64, usize, Table, Vec<(Table, Function)>)> =
+            rendering_entities
+                .into_iter()
+                .map(|(ent, components)| {
+                    let z = ent.get::<f64>("z").unwrap_or(0.0);
+                    let id = ent.get::<usize>("id").unwrap_or(0);
+                    (z, id, ent, components)
+                })
+                .collect();
+        rendering_entities.sort_by(|a, b| compare_entity_order(a.0, a.1, b.0, b.1));
+
+        // paused rendering components still draw (and UI still takes input), but animate nothing
+        let render_dt = if paused { 0.0 } else { dt };
+
+        for (_, _, ent, components) in rendering_entities {
+            for (component, update) in components {
+                let component_name = describe_component_name(&component, Some(&ent));
+                let blend = component_blend_mode(&component).unwrap_or_else(|e| {
+                    report_lua_error(&self.lua, &format!("blend on {component_name}"), &e);
+                    BlendMode::Alpha
+                });
+                if blend != BlendMode::Alpha {
+                    self.queue_blend(blend)?;
+                }
+                if let Err(e) = protect_lua_call(
+                    &format!("running rendering component update callback ({component_name})"),
+                    || update.call::<()>((&ent, component, render_dt)),
+                ) {
+                    report_lua_error(&self.lua, "rendering component update", &e);
+                }
+                if blend != BlendMode::Alpha {
+                    self.queue_blend(BlendMode::Alpha)?;
+                }
+            }
+        }
+
+        // a target still active here would swallow the overlay and the next frame, so finish it
+        let unfinished_target = self
+            .render_state
+            .lock()
+            .map_err(|_| "render state lock poisoned after rendering".to_string())?
+            .take_target();
+        if let Some(target) = unfinished_target {
+            if let Err(error) = crate::renderer::draw_render_target(target) {
+                eprintln!("\x1b[31mRender target error:\x1b[0m {error}");
+            }
+        }
+
+        {
+            let window = self
+                .platform
+                .lock()
+                .map_err(|_| "platform lock poisoned while applying camera".to_string())?
+                .window();
+            let camera = *self.camera.borrow();
+            camera.apply(
+                window,
+                self.render_state
+                    .lock()
+                    .map_err(|_| "render state lock poisoned while applying camera".to_string())?
+                    .commands_since_mut(camera_start),
+            );
+        }
+
+        // the debug panel already shows fps, so it replaces the plain counter
+        if *self.debug_overlay.borrow() {
+            self.queue_debug_overlay()?;
+        } else if self.show_fps() {
+            self.queue_fps_overlay()?;
+        }
+
+        // everything so far is in game units; with `[window] high_dpi` the framebuffer has more
+        // pixels than that
+        let content_scale = self
+            .platform
+            .lock()
+            .map_err(|_| "platform lock poisoned while scaling the frame".to_string())?
+            .display_scale()
+            .content;
+        {
+            let mut render_state = self
+                .render_state
+                .lock()
+                .map_err(|_| "render state lock poisoned while scaling the frame".to_string())?;
+            crate::camera::scale_commands(
+                render_state.commands_since_mut(frame_start),
+                content_scale,
+            );
+            crate::camera::scale_commands(
+                render_state.overlay_commands_since_mut(overlay_start),
+                content_scale,
+            );
+        }
+
+        Ok(())
+    }
+
+    fn track_frame_rate(&mut self, dt: f32) {
+        if dt <= 0.0 || !dt.is_finite() {
+            return;
+        }
+        // a rolling average so the readout doesn't flicker between frames
+        if self.recent_dts.len() == FRAME_RATE_SAMPLES {
+            self.recent_dts.pop_front();
+        }
+        self.recent_dts.push_back(dt);
+        let total: f32 = self.recent_dts.iter().sum();
+        *self.frame_rate.borrow_mut() = self.recent_dts.len() as f32 / total;
+    }
+
+    fn overlay_text_request(text: String) -> TextRenderRequest {
+        TextRenderRequest {
+            text,
+            bounds: Rect {
+                x: 10.0,
+                y: 10.0,
+                w: 0.0,
+                h: 0.0,
+            },
+            rotation: 0.0,
+            pivot: Vec2 { x: 10.0, y: 10.0 },
+            color: PlatformColor::rgba(0, 255, 0, 255),
+            font: FontHandle::Default,
+            scale: 24.0,
+            min_scale: 1.0,
+            text_scale: TextScaleMode::None,
+            align_x: TextAlignX::Left,
+            align_y: TextAlignY::Top,
+            wrap: TextWrapMode::None,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            line_spacing: 1.0,
+            letter_spacing: 0.0,
+            stretch_width: 0.0,
+            stretch_height: 0.0,
+        }
+    }
+
+    fn queue_blend(&self, mode: BlendMode) -> Result<(), String> {
+        self.render_state
+            .lock()
+            .map_err(|_| "render state lock poisoned while setting blend mode".to_string())?
+            .queue(DrawCommand::SetBlend(mode));
+        Ok(())
+    }
+
+    fn queue_fps_overlay(&self) -> Result<(), String> {
+        let fps = self.frame_rate.borrow().round() as i64;
+        let request = Self::overlay_text_request(format!("FPS: {fps}"));
+        // overlay commands are drawn after every entity, so the counter is never covered
+        self.render_state
+            .lock()
+            .map_err(|_| "render state lock poisoned while drawing fps".to_string())?
+            .extend_overlay(vec![DrawCommand::Text(request)]);
+        Ok(())
+    }
+
+    fn queue_debug_overlay(&self) -> Result<(), String> {
+        let fps = self.frame_rate.borrow().round() as i64;
+        let entity_count = self.entities.borrow().len();
+        let system_count = self.systems.borrow().len();
+        let request = Self::overlay_text_request(format!(
+            "FPS: {fps}\nEntities: {entity_count}\nSystems: {system_count}"
+        ));
+
+        let mut commands = Vec::with_capacity(2);
+        if let Some(metrics) = crate::renderer::measure_text(&request) {
+            let padding = 6.0;
+            commands.push(DrawCommand::Rect {
+                x: request.bounds.x - padding,
+                y: request.bounds.y - padding,
+                w: metrics.width + padding * 2.0,
+                h: metrics.height + padding * 2.0,
+                rotation: 0.0,
+                offset: Vec2 { x: 0.0, y: 0.0 },
+                color: PlatformColor::rgba(0, 0, 0, 180),
+            });
+        }
+        commands.push(DrawCommand::Text(request));
+
+        self.render_state
+            .lock()
+            .map_err(|_| "render state lock poisoned while drawing debug overlay".to_string())?
+            .extend_overlay(commands);
+        Ok(())
+    }
+
+    // runs one frame without a window: updates the game, discards the queued draw
+    // commands and clears per-frame input, the same way the desktop loop ends a frame.
+    pub fn step(&mut self, dt: f32) -> Result<(), String> {
+        self.update(dt)?;
+        crate::renderer::drain_commands(&self.render_state)?;
+        self.platform
+            .lock()
+            .map_err(|_| "platform lock poisoned while finishing frame".to_string())?
+            .begin_frame();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn assert_close(actual: f32, expected: f32) {
+        let diff = (actual - expected).abs();
+        assert!(
             diff <= 0.001,
             "expected {expected}, got {actual}, diff {diff}"
         );
@@ -3198,6 +4662,100 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn systems_only_lose_presses_a_click_listener_consumes() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("listener_consumes_press")?;
+        runtime
+            .lua
+            .load(
+                r#"
+                log = {}
+                local button = ecs.newEntity("button", nil)
+                ecs.addComponent(button, core.Rect2D)
+                button:listen("leftClick", function()
+                    table.insert(log, "button")
+                    input.consumeMousePress("left")
+                end)
+                local label = ecs.newEntity("label", nil, 100, 0)
+                ecs.addComponent(label, core.Rect2D)
+                label:listen("leftClick", function()
+                    table.insert(log, "label")
+                end)
+                ecs.addSystem({
+                    update = function()
+                        table.insert(log, tostring(input.isMousePressed("left")))
+                    end,
+                })
+                "#,
+            )
+            .exec()?;
+
+        // on the button, on the label, then on empty space
+        for (x, y) in [(16.0, 16.0), (116.0, 16.0), (200.0, 200.0)] {
+            {
+                let mut platform = runtime.platform.lock().unwrap();
+                platform.begin_frame();
+                platform.set_mouse_position(x, y);
+                platform
+                    .input_mut()
+                    .mouse_pressed
+                    .insert("left".to_string());
+            }
+            runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        }
+
+        let log: String = runtime.lua.load("return table.concat(log, ',')").eval()?;
+        assert_eq!(log, "button,false,label,true,true");
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn consumed_mouse_press_is_hidden_for_the_rest_of_the_frame() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("consume_mouse_press")?;
+        runtime
+            .lua
+            .load(
+                r#"
+                log = {}
+                ecs.addSystem({
+                    update = function()
+                        table.insert(log, tostring(input.consumeMousePress("left")))
+                    end,
+                })
+                ecs.addSystem({
+                    update = function()
+                        table.insert(log, tostring(input.isMousePressed("left")))
+                        table.insert(log, tostring(input.consumeMousePress()))
+                    end,
+                })
+                "#,
+            )
+            .exec()?;
+
+        // the claim ends with the frame, so the next press can be taken again
+        for _ in 0..2 {
+            {
+                let mut platform = runtime.platform.lock().unwrap();
+                platform.begin_frame();
+                platform
+                    .input_mut()
+                    .mouse_pressed
+                    .insert("left".to_string());
+            }
+            runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        }
+        runtime.platform.lock().unwrap().begin_frame();
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+
+        let log: String = runtime.lua.load("return table.concat(log, ',')").eval()?;
+        assert_eq!(log, "true,false,false,true,false,false,false,false,false");
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
     #[test]
     fn entity_and_component_tables_expose_instance_methods() -> mlua::Result<()> {
         let (runtime, root) = start_test_runtime("entity_methods")?;
@@ -3260,4 +4818,2301 @@ mod tests {
         std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
         Ok(())
     }
+
+    #[test]
+    fn system_update_receives_shared_context() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("system_context")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                ecs.addSystem({
+                    update = function(self, dt, context)
+                        context.frames = (context.frames or 0) + 1
+                    end,
+                })
+                ecs.addSystem({
+                    update = function(self, dt, context)
+                        context.seen = context.frames
+                    end,
+                })
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+
+        let ecs: Table = runtime.lua.globals().get("ecs")?;
+        let context: Table = ecs.get("context")?;
+        assert_eq!(context.get::<i64>("frames")?, 2);
+        assert_eq!(context.get::<i64>("seen")?, 2);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn step_runs_headless_frames_with_injected_input() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("headless_step")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                player = ecs.newEntity("player", nil, 0, 0)
+                ecs.addSystem({
+                    update = function(self, dt)
+                        if input.isKeyDown("d") then
+                            player.x += 10
+                        end
+                        if input.isKeyPressed("space") then
+                            player.y += 1
+                        end
+                    end,
+                })
+                "#,
+            )
+            .exec()?;
+
+        runtime.press_key("D");
+        runtime.press_key("space");
+        for _ in 0..3 {
+            runtime.step(1.0 / 60.0).map_err(mlua::Error::external)?;
+        }
+        runtime.release_key("d");
+        runtime.step(1.0 / 60.0).map_err(mlua::Error::external)?;
+
+        let player: Table = runtime.lua.globals().get("player")?;
+        assert_close(player.get::<f32>("x")?, 30.0);
+        assert_close(player.get::<f32>("y")?, 1.0);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn audio_module_plays_loaded_sounds() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("audio_module")?;
+
+        let result: String = runtime
+            .lua
+            .load(
+                r#"
+                assets.newSound(8000, 1, 800, 0.25):save("assets/blip.wav")
+                local sound = assets.loadSound("blip.wav")
+                local ok, err = pcall(audio.play, sound)
+                if ok then
+                    audio.stop(sound)
+                    return "played"
+                end
+                -- machines without an output device still reach the backend
+                return tostring(err)
+                "#,
+            )
+            .eval()?;
+        assert!(
+            result == "played" || result.contains("audio output"),
+            "unexpected audio.play result: {result}"
+        );
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn input_module_is_registered_on_start() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("input_module")?;
+
+        let globals = runtime.lua.globals();
+        let input: Table = globals.get("input")?;
+        let user_input: Table = globals.get("userInput")?;
+        assert_eq!(input.to_pointer(), user_input.to_pointer());
+        assert!(input.get::<Function>("isKeyDown").is_ok());
+
+        runtime.press_key("w");
+        let is_key_down: Function = input.get("isKeyDown")?;
+        assert!(is_key_down.call::<bool>("W".to_string())?);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn fps_counter_follows_show_fps() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("fps_counter")?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        match commands.last() {
+            Some(DrawCommand::Text(request)) => {
+                assert_eq!(request.text, "FPS: 60");
+                assert_ne!(request.color, PlatformColor::rgba(0, 0, 0, 255));
+            }
+            other => panic!("expected fps text overlay, got {other:?}"),
+        }
+
+        runtime.lua.load("app.setShowFps(false)").exec()?;
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        assert!(commands.is_empty());
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn debug_overlay_lists_entity_and_system_counts() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("debug_overlay")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                ecs.newEntity("a", nil, 0, 0)
+                ecs.newEntity("b", nil, 0, 0)
+                ecs.addSystem({ update = function() end })
+                app.setDebugOverlay(true)
+                "#,
+            )
+            .exec()?;
+        assert!(runtime
+            .lua
+            .load("return app.getDebugOverlay()")
+            .eval::<bool>()?);
+
+        let entity_count = runtime.entities.borrow().len();
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        match commands.last() {
+            Some(DrawCommand::Text(request)) => {
+                assert_eq!(
+                    request.text,
+                    format!("FPS: 60\nEntities: {entity_count}\nSystems: 1")
+                );
+            }
+            other => panic!("expected debug overlay text, got {other:?}"),
+        }
+        assert!(!commands.iter().any(
+            |command| matches!(command, DrawCommand::Text(request) if request.text == "FPS: 60")
+        ));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn query_returns_entities_with_component() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("ecs_query")?;
+
+        let found: (usize, String, String, usize, usize) = runtime
+            .lua
+            .load(
+                r#"
+                local first = ecs.newEntity("first", nil, 0, 0)
+                ecs.addComponent(first, core.Rect2D)
+                local second = ecs.newEntity("second", nil, 0, 0)
+                ecs.addComponent(second, { name = "Health", awake = function() end })
+                ecs.addComponent(second, core.Rect2D)
+                ecs.newEntity("plain", nil, 0, 0)
+
+                local rects = ecs.query("Rect2D")
+                return #rects, rects[1].name, rects[2].name,
+                    #ecs.query("Health"), #ecs.query("Missing")
+                "#,
+            )
+            .eval()?;
+        assert_eq!(found, (2, "first".to_string(), "second".to_string(), 1, 0));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn disabled_entities_skip_updates_with_their_children() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("entity_enabled")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                updates = { parent = 0, child = 0 }
+                local function counter(key)
+                    return {
+                        name = "Counter",
+                        awake = function() end,
+                        update = function() updates[key] += 1 end,
+                    }
+                end
+                parent = ecs.newEntity("parent", nil, 0, 0)
+                ecs.addComponent(parent, counter("parent"))
+                local child = ecs.newEntity("child", parent, 0, 0)
+                ecs.addComponent(child, counter("child"))
+                "#,
+            )
+            .exec()?;
+
+        let counts = |runtime: &Runtime| -> mlua::Result<(i64, i64)> {
+            runtime
+                .lua
+                .load("return updates.parent, updates.child")
+                .eval()
+        };
+
+        assert!(runtime.lua.load("return parent.enabled").eval::<bool>()?);
+        runtime.step(1.0 / 60.0).map_err(mlua::Error::external)?;
+        assert_eq!(counts(&runtime)?, (1, 1));
+
+        runtime.lua.load("parent.enabled = false").exec()?;
+        runtime.step(1.0 / 60.0).map_err(mlua::Error::external)?;
+        assert_eq!(counts(&runtime)?, (1, 1));
+
+        runtime.lua.load("parent.enabled = true").exec()?;
+        runtime.step(1.0 / 60.0).map_err(mlua::Error::external)?;
+        assert_eq!(counts(&runtime)?, (2, 2));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn rendering_follows_z_changed_during_update() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("render_z_order")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                local function rect(name, z, r, g, b)
+                    local entity = ecs.newEntity(name, nil, 10, 10)
+                    entity.z = z
+                    local component = ecs.addComponent(entity, core.Rect2D)
+                    component.color = Color4(r, g, b)
+                    return entity
+                end
+                rect("red", 0, 255, 0, 0)
+                rect("green", 1, 0, 255, 0)
+                local blue = rect("blue", 2, 0, 0, 255)
+                ecs.addComponent(blue, {
+                    name = "SinkBelow",
+                    awake = function() end,
+                    update = function(entity) entity.z = -1 end,
+                })
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        let colors: Vec<PlatformColor> = commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Rect { color, .. } => Some(*color),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            colors,
+            vec![
+                PlatformColor::rgba(0, 0, 255, 255),
+                PlatformColor::rgba(255, 0, 0, 255),
+                PlatformColor::rgba(0, 255, 0, 255),
+            ]
+        );
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn screenshot_queues_png_path_inside_project() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("screenshot_request")?;
+
+        runtime
+            .lua
+            .load(r#"app.screenshot("shots/title")"#)
+            .exec()?;
+        let escape = runtime
+            .lua
+            .load(r#"app.screenshot("../outside.png")"#)
+            .exec();
+        assert!(escape.is_err());
+
+        let requests = runtime
+            .platform
+            .lock()
+            .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+            .take_screenshot_requests();
+        let project = root.canonicalize().map_err(mlua::Error::external)?;
+        let paths: Vec<PathBuf> = requests.into_iter().map(|request| request.path).collect();
+        assert_eq!(paths, vec![project.join("shots").join("title.png")]);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn screenshot_callbacks_receive_write_errors() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("screenshot_errors")?;
+        // a file where the directory should be makes the png write fail
+        std::fs::write(root.join("blocked"), "").map_err(mlua::Error::external)?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                results = {}
+                local function record(ok, err)
+                    table.insert(results, tostring(ok) .. "," .. tostring(err ~= nil))
+                end
+                app.screenshot("shots/saved", record)
+                app.screenshot("blocked/shot", function(ok, err)
+                    record(ok, err)
+                    blockedError = err
+                end)
+                "#,
+            )
+            .exec()?;
+        let requests = runtime
+            .platform
+            .lock()
+            .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+            .take_screenshot_requests();
+        let frame = image::RgbaImage::new(2, 2);
+        save_screenshots(&runtime.platform, requests, Ok(&frame));
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+
+        let results: String = runtime
+            .lua
+            .load("return table.concat(results, ';')")
+            .eval()?;
+        assert_eq!(results, "true,false;false,true");
+        assert!(root.join("shots").join("saved.png").is_file());
+        let error: String = runtime.lua.globals().get("blockedError")?;
+        assert!(error.contains("blocked"), "{error}");
+
+        // a failed readback fails every pending screenshot with its message
+        runtime
+            .lua
+            .load(r#"app.screenshot("shots/again", function(ok, err) readbackError = err end)"#)
+            .exec()?;
+        let requests = runtime
+            .platform
+            .lock()
+            .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+            .take_screenshot_requests();
+        save_screenshots(
+            &runtime.platform,
+            requests,
+            Err("readback failed".to_string()),
+        );
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let error: String = runtime.lua.globals().get("readbackError")?;
+        assert_eq!(error, "readback failed");
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn require_resolves_declared_dependencies() -> mlua::Result<()> {
+        let root = temp_project_root("dependencies");
+        std::fs::create_dir_all(root.join("libs").join("mathx")).map_err(mlua::Error::external)?;
+        std::fs::write(root.join("main.luau"), "-- test runtime\n")
+            .map_err(mlua::Error::external)?;
+        std::fs::write(
+            root.join("libs").join("mathx").join("init.luau"),
+            "return { double = function(value) return value * 2 end }\n",
+        )
+        .map_err(mlua::Error::external)?;
+        std::fs::write(
+            root.join("neolove.toml"),
+            "[package]\nname = \"deps\"\n\n[dependencies]\nmathx = \"libs/mathx\"\n",
+        )
+        .map_err(mlua::Error::external)?;
+
+        let mut runtime = Runtime::new(root.clone());
+        runtime.start()?;
+        let (doubled, same): (f64, bool) = runtime
+            .lua
+            .load(r#"return require("mathx").double(21), require("mathx") == require("mathx")"#)
+            .eval()?;
+        assert_eq!(doubled, 42.0);
+        assert!(same);
+
+        std::fs::write(
+            root.join("neolove.toml"),
+            "[dependencies]\nmissing = \"libs/missing\"\n",
+        )
+        .map_err(mlua::Error::external)?;
+        let error = Runtime::new(root.clone())
+            .start()
+            .expect_err("missing dependency should fail startup")
+            .to_string();
+        assert!(error.contains("'missing'"), "{error}");
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn require_resolves_relative_to_requiring_script() -> mlua::Result<()> {
+        let root = temp_project_root("relative_require");
+        std::fs::create_dir_all(root.join("lib")).map_err(mlua::Error::external)?;
+        std::fs::write(
+            root.join("main.luau"),
+            "local util = require(\"lib/util\")\nresult = util\n",
+        )
+        .map_err(mlua::Error::external)?;
+        std::fs::write(
+            root.join("lib").join("util.luau"),
+            r#"
+            local helper = require("helper")
+            return {
+                value = helper.value + 1,
+                same = require("./helper") == helper,
+                config = require("config").name,
+            }
+            "#,
+        )
+        .map_err(mlua::Error::external)?;
+        std::fs::write(
+            root.join("lib").join("helper.luau"),
+            "return { value = 41 }\n",
+        )
+        .map_err(mlua::Error::external)?;
+        // same name at the root: the sibling of the requiring file has to win
+        std::fs::write(root.join("helper.luau"), "return { value = 0 }\n")
+            .map_err(mlua::Error::external)?;
+        std::fs::write(root.join("config.luau"), "return { name = \"root\" }\n")
+            .map_err(mlua::Error::external)?;
+
+        let mut runtime = Runtime::new(root.clone());
+        runtime.start()?;
+        let result: Table = runtime.lua.globals().get("result")?;
+        assert_eq!(result.get::<f64>("value")?, 42.0);
+        assert!(result.get::<bool>("same")?);
+        assert_eq!(result.get::<String>("config")?, "root");
+
+        let missing = runtime
+            .lua
+            .load(
+                r#"
+                local ok, err = pcall(require, "./nowhere")
+                return ok, tostring(err)
+                "#,
+            )
+            .eval::<(bool, String)>()?;
+        assert!(!missing.0);
+        assert!(missing.1.contains("'./nowhere' not found"), "{}", missing.1);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn key_list_queries_check_any_and_all_keys() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("key_lists")?;
+
+        {
+            let mut platform = runtime
+                .platform
+                .lock()
+                .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+            platform.press_key("leftcontrol".to_string());
+            platform.press_key("s".to_string());
+        }
+
+        let results: (bool, bool, bool, bool, bool) = runtime
+            .lua
+            .load(
+                r#"
+                return input.isAnyKeyDown({ "w", "a", "s", "d" }),
+                    input.isAnyKeyDown({ "up", "down" }),
+                    input.areKeysDown({ "LeftControl", "S" }),
+                    input.areKeysDown({ "leftcontrol", "z" }),
+                    input.areKeysDown({})
+                "#,
+            )
+            .eval()?;
+        assert_eq!(results, (true, false, true, false, false));
+
+        let modifiers: (bool, bool, bool, bool) = runtime
+            .lua
+            .load(
+                r#"
+                return input.isCtrlDown(), input.isShiftDown(),
+                    input.isAltDown(), input.isSuperDown()
+                "#,
+            )
+            .eval()?;
+        assert_eq!(modifiers, (true, false, false, false));
+        runtime.press_key("RightShift");
+        assert!(runtime
+            .lua
+            .load("return input.isShiftDown()")
+            .eval::<bool>()?);
+
+        assert!(runtime
+            .lua
+            .load(r#"return input.isAnyKeyDown({ "w", 5 })"#)
+            .exec()
+            .is_err());
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn touches_report_their_phase_for_one_frame() -> mlua::Result<()> {
+        use crate::platform::TouchPhase;
+
+        let (runtime, root) = start_test_runtime("touches")?;
+        let touch = |id: u64, x: f32, phase: TouchPhase| -> mlua::Result<()> {
+            runtime
+                .platform
+                .lock()
+                .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+                .update_touch(id, x, 20.0, phase);
+            Ok(())
+        };
+        let begin_frame = || -> mlua::Result<()> {
+            runtime
+                .platform
+                .lock()
+                .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+                .begin_frame();
+            Ok(())
+        };
+        let summary = r#"
+            local parts = {}
+            for _, touch in ipairs(input.getTouches()) do
+                table.insert(parts, touch.id .. ":" .. touch.x .. ":" .. touch.phase)
+            end
+            return table.concat(parts, ","), input.touchCount()
+        "#;
+
+        touch(1, 10.0, TouchPhase::Started)?;
+        touch(1, 15.0, TouchPhase::Moved)?;
+        touch(2, 40.0, TouchPhase::Started)?;
+        let first: (String, usize) = runtime.lua.load(summary).eval()?;
+        assert_eq!(first, ("1:15:started,2:40:started".to_string(), 2));
+
+        begin_frame()?;
+        touch(2, 50.0, TouchPhase::Moved)?;
+        touch(1, 15.0, TouchPhase::Ended)?;
+        let second: (String, usize) = runtime.lua.load(summary).eval()?;
+        assert_eq!(second, ("1:15:ended,2:50:moved".to_string(), 1));
+
+        begin_frame()?;
+        let third: (String, usize) = runtime.lua.load(summary).eval()?;
+        assert_eq!(third, ("2:50:stationary".to_string(), 1));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn mouse_delta_sums_motion_within_a_frame() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("mouse_delta")?;
+        let delta = |runtime: &Runtime| -> mlua::Result<(f32, f32)> {
+            runtime.lua.load("return input.getMouseDelta()").eval()
+        };
+
+        runtime.set_platform_mouse_state(300.0, 200.0);
+        assert_eq!(delta(&runtime)?, (0.0, 0.0));
+        runtime.step(1.0 / 60.0).map_err(mlua::Error::external)?;
+
+        runtime.set_platform_mouse_state(310.0, 195.0);
+        runtime.set_platform_mouse_state(315.0, 190.0);
+        assert_eq!(delta(&runtime)?, (15.0, -10.0));
+
+        runtime.step(1.0 / 60.0).map_err(mlua::Error::external)?;
+        assert_eq!(delta(&runtime)?, (0.0, 0.0));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn text_input_collects_every_character_typed_in_a_frame() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("text_input")?;
+
+        {
+            let mut platform = runtime
+                .platform
+                .lock()
+                .map_err(|_| mlua::Error::external("platform lock poisoned"))?;
+            platform.push_text_input("h");
+            platform.push_text_input("é");
+            platform.push_text_input("y");
+        }
+        let (text, last): (String, Option<String>) = runtime
+            .lua
+            .load("return input.getTextInput(), input.getCharPressed()")
+            .eval()?;
+        assert_eq!(text, "héy");
+        assert_eq!(last.as_deref(), Some("y"));
+
+        runtime.step(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let text: String = runtime.lua.load("return input.getTextInput()").eval()?;
+        assert_eq!(text, "");
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn get_overlapping_lists_intersecting_candidates() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("get_overlapping")?;
+
+        let names: Vec<String> = runtime
+            .lua
+            .load(
+                r#"
+                local player = ecs.newEntity("player", nil, 0, 0)
+                local wall = ecs.newEntity("wall", nil, 20, 20)
+                local coin = ecs.newEntity("coin", nil, -16, 8)
+                local far = ecs.newEntity("far", nil, 200, 200)
+                local hits = transform.getOverlapping(player, { wall, player, far, coin })
+                local names = {}
+                for _, hit in hits do
+                    table.insert(names, hit.name)
+                end
+                return names
+                "#,
+            )
+            .eval()?;
+        assert_eq!(names, vec!["wall".to_string(), "coin".to_string()]);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn query_region_finds_entities_by_their_world_aabb() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("query_region")?;
+
+        let names = |runtime: &Runtime, code: &str| -> mlua::Result<String> {
+            runtime
+                .lua
+                .load(format!(
+                    r#"
+                    local names = {{}}
+                    for _, hit in {code} do
+                        table.insert(names, hit.name)
+                    end
+                    return table.concat(names, ",")
+                    "#
+                ))
+                .eval()
+        };
+
+        runtime
+            .lua
+            .load(
+                r#"
+                near = ecs.newEntity("near", ecs.root, 10, 10)
+                ecs.newEntity("child", near, 100, 0)
+                ecs.newEntity("far", nil, 5000, 5000)
+                hidden = ecs.newEntity("hidden", nil, 0, 0)
+                hidden.enabled = false
+                "#,
+            )
+            .exec()?;
+        assert_eq!(
+            names(&runtime, "transform.queryRegion(0, 0, 200, 50)")?,
+            "near,child"
+        );
+        assert_eq!(
+            names(&runtime, "transform.queryRegion(4990, 4990, 20, 20)")?,
+            "far"
+        );
+
+        // positions are picked up again once the frame moves on
+        runtime.lua.load("near.x = 4000").exec()?;
+        assert_eq!(
+            names(&runtime, "transform.queryRegion(0, 0, 200, 50)")?,
+            "near,child"
+        );
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        assert_eq!(names(&runtime, "transform.queryRegion(0, 0, 200, 50)")?, "");
+        assert!(runtime
+            .lua
+            .load("transform.queryRegion(0, 0, -1, 10)")
+            .exec()
+            .is_err());
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn circle_overlap_and_point_in_entity() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("circle_point_tests")?;
+
+        let results: (bool, bool, bool, bool, bool) = runtime
+            .lua
+            .load(
+                r#"
+                local a = ecs.newEntity("a", nil, 0, 0)
+                local b = ecs.newEntity("b", nil, 30, 40)
+                local box = ecs.newEntity("box", nil, 100, 100)
+                return transform.circleOverlap(a, b, 20, 30),
+                    transform.circleOverlap(a, b, 20, 29),
+                    transform.pointInEntity(116, 131, box),
+                    transform.pointInEntity(132, 110, box),
+                    transform.pointInEntity(99, 110, box)
+                "#,
+            )
+            .eval()?;
+        assert_eq!(results, (true, false, true, false, false));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn pick_returns_the_topmost_entity_under_a_point() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("transform_pick")?;
+
+        let picked: (String, String, String, bool) = runtime
+            .lua
+            .load(
+                r#"
+                local back = ecs.newEntity("back", nil, 0, 0)
+                local front = ecs.newEntity("front", nil, 10, 10)
+                front.z = 5
+                local later = ecs.newEntity("later", nil, 0, 0)
+                local hidden = ecs.newEntity("hidden", nil, 0, 0)
+                hidden.z = 10
+                hidden.enabled = false
+                local all = { back, front, later, hidden }
+                return transform.pick(15, 15, all).name,
+                    transform.pick(5, 5, all).name,
+                    transform.pick(5, 5, { back }).name,
+                    transform.pick(500, 500, all) == nil
+                "#,
+            )
+            .eval()?;
+        assert_eq!(
+            picked,
+            (
+                "front".to_string(),
+                "later".to_string(),
+                "back".to_string(),
+                true
+            )
+        );
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn find_all_children_and_find_descendant_search_the_tree() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("find_descendant")?;
+
+        let found: (usize, f32, f32, bool, f32) = runtime
+            .lua
+            .load(
+                r#"
+                local level = ecs.newEntity("level", nil, 0, 0)
+                ecs.newEntity("coin", level, 1, 0)
+                local room = ecs.newEntity("room", level, 0, 0)
+                ecs.newEntity("coin", level, 2, 0)
+                local chest = ecs.newEntity("chest", room, 0, 0)
+                ecs.newEntity("key", chest, 7, 0)
+                ecs.newEntity("door", room, 3, 0)
+
+                local coins = ecs.findAllChildren(level, "coin")
+                return #coins, coins[2].x,
+                    ecs.findDescendant(level, "key").x,
+                    ecs.findDescendant(level, "missing") == nil,
+                    level:FindDescendant("door").x
+                "#,
+            )
+            .eval()?;
+        assert_eq!(found, (2, 2.0, 7.0, true, 3.0));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn set_parent_moves_entity_between_parents() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("set_parent")?;
+
+        let (left_count, right_count, parent_name, x): (usize, usize, String, f32) = runtime
+            .lua
+            .load(
+                r#"
+                left = ecs.newEntity("left", nil, 0, 0)
+                right = ecs.newEntity("right", nil, 100, 0)
+                item = ecs.newEntity("item", left, 5, 0)
+                ecs.setParent(item, right)
+                return #left.children, #right.children, item.parent.name,
+                    transform.getWorldPosition(item)
+                "#,
+            )
+            .eval()?;
+        assert_eq!(
+            (left_count, right_count, parent_name.as_str()),
+            (0, 1, "right")
+        );
+        assert_close(x, 105.0);
+
+        let item_id: usize = runtime.lua.load("return item.id").eval()?;
+        let right_id: usize = runtime.lua.load("return right.id").eval()?;
+        {
+            let entities = runtime.entities.borrow();
+            assert_eq!(entities[&item_id].parent, Some(right_id));
+            assert_eq!(entities[&right_id].children, vec![item_id]);
+        }
+
+        let cycle = runtime.lua.load("ecs.setParent(right, item)").exec();
+        assert!(cycle.is_err());
+        let detached: bool = runtime
+            .lua
+            .load("ecs.setParent(item, nil) return item.parent == nil and #right.children == 0")
+            .eval()?;
+        assert!(detached);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn new_entity_records_parent_in_hierarchy() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("new_entity_hierarchy")?;
+
+        let (parent_id, child_id, orphan_id): (usize, usize, usize) = runtime
+            .lua
+            .load(
+                r#"
+                local parent = ecs.newEntity("parent", nil, 0, 0)
+                local child = ecs.newEntity("child", parent, 0, 0)
+                local orphan = ecs.newEntity("orphan", nil, 0, 0)
+                return parent.id, child.id, orphan.id
+                "#,
+            )
+            .eval()?;
+
+        let entities = runtime.entities.borrow();
+        assert_eq!(entities[&child_id].parent, Some(parent_id));
+        assert_eq!(entities[&parent_id].children, vec![child_id]);
+        assert_eq!(entities[&orphan_id].parent, None);
+        drop(entities);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn app_quit_requests_exit_with_code() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("app_quit")?;
+        assert_eq!(runtime.exit_code(), None);
+
+        runtime.lua.load("app.quit(3)").exec()?;
+        assert!(runtime.exit_requested());
+        assert_eq!(runtime.exit_code(), Some(3));
+
+        runtime.lua.load("die()").exec()?;
+        assert_eq!(runtime.exit_code(), Some(3));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn time_global_reports_clock_and_frame_delta() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("time_global")?;
+
+        runtime.lua.load("app.setMaxDelta(1)").exec()?;
+        runtime.step(0.25).map_err(mlua::Error::external)?;
+        let (frame, ordered, since): (f32, bool, f64) = runtime
+            .lua
+            .load(
+                r#"
+                local first = time.now()
+                local second = time.now()
+                return time.frame(), first >= 0 and second >= first, time.since(first)
+                "#,
+            )
+            .eval()?;
+        assert_close(frame, 0.25);
+        assert!(ordered);
+        assert!(since >= 0.0);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn render_target_captures_drawing_until_frame_end() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("render_target")?;
+
+        let target: mlua::AnyUserData = runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                local target = assets.newRenderTarget(4, 4)
+                local entity = ecs.newEntity("minimap", nil, 0, 0)
+                entity.size_x = 2
+                entity.size_y = 2
+                local rect = ecs.addComponent(entity, core.Rect2D)
+                rect.color = Color4(255, 0, 0)
+                ecs.addComponent(entity, {
+                    name = "UseTarget",
+                    awake = function() end,
+                    update = function() app.setRenderTarget(target) end,
+                })
+                return target
+                "#,
+            )
+            .eval()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        assert!(commands.is_empty());
+
+        let image = target.borrow::<crate::assets::ImageHandle>()?.clone();
+        assert_eq!(image.sample_rgba(1, 1)?, [255, 0, 0, 255]);
+        assert_eq!(image.sample_rgba(3, 3)?, [0, 0, 0, 0]);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn high_dpi_content_scale_maps_the_frame_to_framebuffer_pixels() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("content_scale")?;
+        runtime.set_platform_display_scale(2.0, 2.0);
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                ecs.addSystem({
+                    update = function()
+                        draw.rect(1, 2, 3, 4)
+                    end,
+                })
+                "#,
+            )
+            .exec()?;
+        assert_close(runtime.lua.load("return app.getDpiScale()").eval()?, 2.0);
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        match commands.as_slice() {
+            [DrawCommand::Rect { x, y, w, h, .. }] => {
+                assert_eq!((*x, *y, *w, *h), (2.0, 4.0, 6.0, 8.0))
+            }
+            other => panic!("expected one rect, got {other:?}"),
+        }
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn draw_global_queues_overlay_commands_from_systems() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("draw_global")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                camera.setPosition(1000, 1000)
+                local image = assets.newImage(2, 2)
+                ecs.addSystem({
+                    update = function()
+                        draw.rect(1, 2, 3, 4, Color4(255, 0, 0))
+                        draw.line(0, 0, 10, 0, 2)
+                        draw.circle(5, 5, 3)
+                        draw.text("hud", 4, 4, 16)
+                        draw.texture(image, 8, 8)
+                    end,
+                })
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        assert_eq!(commands.len(), 5);
+        match &commands[0] {
+            DrawCommand::Rect {
+                x, y, w, h, color, ..
+            } => {
+                // screen space: the camera doesn't move immediate-mode drawing
+                assert_eq!((*x, *y, *w, *h), (1.0, 2.0, 3.0, 4.0));
+                assert_eq!(*color, PlatformColor::rgba(255, 0, 0, 255));
+            }
+            other => panic!("expected rect, got {other:?}"),
+        }
+        match &commands[1] {
+            DrawCommand::Rect { w, h, .. } => assert_eq!((*w, *h), (10.0, 2.0)),
+            other => panic!("expected line rect, got {other:?}"),
+        }
+        assert!(matches!(commands[2], DrawCommand::Circle { .. }));
+        assert!(matches!(commands[3], DrawCommand::Text(_)));
+        match &commands[4] {
+            DrawCommand::Image { dest, .. } => assert_eq!((dest.w, dest.h), (2.0, 2.0)),
+            other => panic!("expected image, got {other:?}"),
+        }
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn image2d_tile_repeats_and_crops_the_last_tile() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("image2d_tile")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                local entity = ecs.newEntity("backdrop", nil, 0, 0)
+                entity.size_x = 10
+                entity.size_y = 2
+                local image = ecs.addComponent(entity, core.Image2D)
+                image.image = assets.newImage(4, 2)
+                image.tile = true
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let tiles: Vec<(f32, f32, f32)> = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?
+            .into_iter()
+            .filter_map(|command| match command {
+                DrawCommand::Image { dest, source, .. } => {
+                    Some((dest.x, dest.w, source.map_or(0.0, |source| source.w)))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            tiles,
+            vec![(0.0, 4.0, 4.0), (4.0, 4.0, 4.0), (8.0, 2.0, 2.0)]
+        );
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn image2d_source_rect_is_clamped_to_the_image() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("image2d_source")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                local sheet = assets.newImage(8, 4)
+                local function frame(x, src_x, src_y, src_w, src_h, flip_x)
+                    local entity = ecs.newEntity("frame", nil, x, 0)
+                    local image = ecs.addComponent(entity, core.Image2D)
+                    image.image = sheet
+                    image.src_x = src_x
+                    image.src_y = src_y
+                    image.src_w = src_w
+                    image.src_h = src_h
+                    image.flip_x = flip_x or false
+                end
+                frame(0, 2, 1, 4, 2)
+                -- hangs off the bottom right corner
+                frame(100, 6, 2, 4, 4)
+                -- entirely outside the sheet
+                frame(200, 20, 0, 4, 4)
+                -- mirrored, so the part cut off the right lands on the left of the entity
+                frame(300, 6, 2, 4, 4, true)
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        type Draw = ((f32, f32, f32, f32), Option<(f32, f32, f32, f32)>);
+        let mut draws: Vec<Draw> = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?
+            .into_iter()
+            .filter_map(|command| match command {
+                DrawCommand::Image { dest, source, .. } => Some((
+                    (dest.x, dest.y, dest.w, dest.h),
+                    source.map(|source| (source.x, source.y, source.w, source.h)),
+                )),
+                _ => None,
+            })
+            .collect();
+        draws.sort_by(|((a, ..), _), ((b, ..), _)| a.total_cmp(b));
+        // the clamped frames keep their 8px-per-texel scale instead of stretching over 32x32
+        assert_eq!(
+            draws,
+            vec![
+                ((0.0, 0.0, 32.0, 32.0), Some((2.0, 1.0, 4.0, 2.0))),
+                ((100.0, 0.0, 16.0, 16.0), Some((6.0, 2.0, 2.0, 2.0))),
+                ((316.0, 0.0, 16.0, 16.0), Some((8.0, 2.0, -2.0, 2.0))),
+            ]
+        );
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tilemap2d_draws_non_empty_cells_from_the_tileset() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("tilemap2d")?;
+
+        let (tile, outside_ok): (i64, bool) = runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                local entity = ecs.newEntity("level", nil, 10, 0)
+                local map = ecs.addComponent(entity, core.Tilemap2D)
+                map.tileset = assets.newImage(4, 2)
+                map.tile_width = 2
+                map.tile_height = 2
+                map:setSize(3, 1)
+                map:setTile(0, 0, 1)
+                map:setTile(2, 0, 2)
+                local outside_ok = pcall(map.setTile, map, 3, 0, 1)
+                return map:getTile(2, 0), outside_ok
+                "#,
+            )
+            .eval()?;
+        assert_eq!(tile, 2);
+        assert!(!outside_ok);
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let cells: Vec<(f32, f32)> = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?
+            .into_iter()
+            .filter_map(|command| match command {
+                DrawCommand::Image { dest, source, .. } => {
+                    Some((dest.x, source.map_or(-1.0, |source| source.x)))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(cells, vec![(10.0, 0.0), (14.0, 2.0)]);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tilemap2d_set_size_rejects_oversized_grids() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("tilemap2d_size")?;
+
+        let summary: String = runtime
+            .lua
+            .load(
+                r#"
+                local entity = ecs.newEntity("level", nil)
+                local map = ecs.addComponent(entity, core.Tilemap2D)
+                map:setSize(2, 2)
+                local overflow_ok, overflow_err = pcall(map.setSize, map, 2 ^ 32, 2 ^ 32)
+                local huge_ok = pcall(map.setSize, map, 5000, 5000)
+                return table.concat({
+                    tostring(overflow_ok),
+                    tostring(string.find(tostring(overflow_err), "cell limit", 1, true) ~= nil),
+                    tostring(huge_ok),
+                    map.columns .. "x" .. map.rows,
+                }, ",")
+                "#,
+            )
+            .eval()?;
+        assert_eq!(summary, "false,true,false,2x2");
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn entity_clone_copies_the_whole_subtree() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("entity_clone")?;
+
+        let summary: String = runtime
+            .lua
+            .load(
+                r#"
+                awakes = 0
+                local Counter = {
+                    awake = function(_, component) awakes += 1; component.count = 0 end,
+                    update = function() end,
+                }
+                local ship = ecs.newEntity("ship", nil, 10, 20)
+                local turret = ecs.newEntity("turret", ship, 5, 0)
+                local barrel = ecs.newEntity("barrel", turret)
+                ecs.addComponent(turret, Counter).count = 7
+                ecs.addComponent(barrel, core.Rect2D).visible = false
+
+                local copy = ship:clone()
+                local copyTurret = copy:FindFirstChild("turret")
+                local copyBarrel = copyTurret:FindFirstChild("barrel")
+                local counter = copyTurret.components[1]
+                return table.concat({
+                    tostring(copy.parent == ecs.root),
+                    tostring(copyTurret.parent == copy),
+                    tostring(copyBarrel.parent == copyTurret),
+                    tostring(copyTurret.id ~= turret.id and copyBarrel.id ~= barrel.id),
+                    tostring(counter.entity == copyTurret),
+                    tostring(counter.count),
+                    tostring(copyBarrel.components[1].visible),
+                    tostring(awakes),
+                }, ",")
+                "#,
+            )
+            .eval()?;
+        assert_eq!(summary, "true,true,true,true,true,7,false,2");
+        // root plus the original three entities and their three copies
+        assert_eq!(runtime.entities.borrow().len(), 7);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn entity_start_runs_once_before_its_updates() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("entity_hooks")?;
+        runtime
+            .lua
+            .load(
+                r#"
+                log = {}
+                app.setMaxDelta(1)
+                player = ecs.newEntity("player", ecs.root)
+                function player:start()
+                    table.insert(log, "start " .. self.name)
+                end
+                function player:update(dt)
+                    table.insert(log, "update " .. dt)
+                end
+                sleeper = ecs.newEntity("sleeper", ecs.root)
+                sleeper.enabled = false
+                sleeper.start = function() table.insert(log, "sleeper") end
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(0.5).map_err(mlua::Error::external)?;
+        runtime.lua.load("app.setPaused(true)").exec()?;
+        runtime.update(0.5).map_err(mlua::Error::external)?;
+        runtime
+            .lua
+            .load("app.setPaused(false); sleeper.enabled = true")
+            .exec()?;
+        runtime.update(0.25).map_err(mlua::Error::external)?;
+
+        let log: String = runtime.lua.load("return table.concat(log, ',')").eval()?;
+        assert_eq!(log, "start player,update 0.5,update 0.25,sleeper");
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn load_scene_drops_the_old_scenes_event_handlers() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("load_scene_events")?;
+        std::fs::write(
+            root.join("arena.luau"),
+            r#"
+            events.on("hit", function() table.insert(log, "arena") end)
+            "#,
+        )
+        .map_err(mlua::Error::external)?;
+
+        let (before, after, log): (usize, usize, String) = runtime
+            .lua
+            .load(
+                r#"
+                log = {}
+                events.on("hit", function() table.insert(log, "menu") end)
+                events.on("quit", function() table.insert(log, "quit") end)
+                local before = events.emit("hit")
+                ecs.loadScene("arena")
+                local after = events.emit("hit") + events.emit("quit")
+                return before, after, table.concat(log, ",")
+                "#,
+            )
+            .eval()?;
+        assert_eq!((before, after), (1, 1));
+        assert_eq!(log, "menu,arena");
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn load_scene_replaces_entities_and_systems() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("load_scene")?;
+        std::fs::create_dir_all(root.join("levels")).map_err(mlua::Error::external)?;
+        std::fs::write(
+            root.join("levels/forest.luau"),
+            r#"
+            ecs.newEntity("tree", ecs.root)
+            ecs.addSystem({ update = function() forestTicks += 1 end })
+            "#,
+        )
+        .map_err(mlua::Error::external)?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                destroyed, menuTicks, forestTicks = 0, 0, 0
+                local menu = ecs.newEntity("menu", ecs.root)
+                ecs.newEntity("button", menu)
+                ecs.newEntity("detached")
+                ecs.addComponent(menu, {
+                    awake = function() end,
+                    update = function() end,
+                    destroy = function() destroyed += 1 end,
+                })
+                ecs.addSystem({
+                    update = function()
+                        menuTicks += 1
+                        ecs.loadScene("levels/forest")
+                    end,
+                })
+                -- never runs: the first system swaps the scene out from under it
+                ecs.addSystem({ update = function() menuTicks += 100 end })
+                "#,
+            )
+            .exec()?;
+        assert!(runtime.lua.load("ecs.loadScene('missing')").exec().is_err());
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let (scene, names, destroyed, menu_ticks, forest_ticks, has_core): (
+            String,
+            String,
+            i64,
+            i64,
+            i64,
+            bool,
+        ) = runtime
+            .lua
+            .load(
+                r#"
+                local names = {}
+                for _, child in ecs.root.children do
+                    table.insert(names, child.name)
+                end
+                return ecs.currentScene(), table.concat(names, ","), destroyed, menuTicks,
+                    forestTicks, core.Rect2D ~= nil
+                "#,
+            )
+            .eval()?;
+        assert_eq!((scene.as_str(), names.as_str()), ("forest", "tree"));
+        assert_eq!((destroyed, menu_ticks, forest_ticks), (1, 1, 1));
+        assert!(has_core);
+        assert_eq!(runtime.entities.borrow().len(), 2);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn nine_slice2d_keeps_corners_and_stretches_the_rest() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("nine_slice2d")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                local panel = ecs.newEntity("panel", nil, 100, 50)
+                panel.size_x = 40
+                panel.size_y = 20
+                local slice = ecs.addComponent(panel, core.NineSlice2D)
+                slice.image = assets.newImage(12, 12)
+                slice.left, slice.right, slice.top, slice.bottom = 4, 4, 4, 4
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let pieces: Vec<((f32, f32, f32, f32), (f32, f32))> =
+            crate::renderer::drain_commands(&runtime.render_state)
+                .map_err(mlua::Error::external)?
+                .into_iter()
+                .filter_map(|command| match command {
+                    DrawCommand::Image { dest, source, .. } => source
+                        .map(|source| ((dest.x, dest.y, dest.w, dest.h), (source.x, source.y))),
+                    _ => None,
+                })
+                .collect();
+        assert_eq!(pieces.len(), 9);
+        // top-left corner, top edge, center and bottom-right corner
+        assert_eq!(pieces[0], ((100.0, 50.0, 4.0, 4.0), (0.0, 0.0)));
+        assert_eq!(pieces[1], ((104.0, 50.0, 32.0, 4.0), (4.0, 0.0)));
+        assert_eq!(pieces[4], ((104.0, 54.0, 32.0, 12.0), (4.0, 4.0)));
+        assert_eq!(pieces[8], ((136.0, 66.0, 4.0, 4.0), (8.0, 8.0)));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn nine_slice2d_source_rect_is_clamped_to_the_image() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("nine_slice2d_source")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                local atlas = assets.newImage(12, 12)
+                local function panel(x, src_x)
+                    local entity = ecs.newEntity("panel", nil, x, 0)
+                    local slice = ecs.addComponent(entity, core.NineSlice2D)
+                    slice.image = atlas
+                    slice.src_x, slice.src_y, slice.src_w, slice.src_h = src_x, 4, 12, 12
+                end
+                -- hangs off the right and bottom edges
+                panel(0, 4)
+                -- entirely outside the atlas
+                panel(100, 20)
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let sources: Vec<(f32, f32, f32, f32)> =
+            crate::renderer::drain_commands(&runtime.render_state)
+                .map_err(mlua::Error::external)?
+                .into_iter()
+                .filter_map(|command| match command {
+                    DrawCommand::Image { source, .. } => {
+                        source.map(|source| (source.x, source.y, source.w, source.h))
+                    }
+                    _ => None,
+                })
+                .collect();
+        // no insets, so the clamped 8x8 panel is drawn as a single center piece
+        assert_eq!(sources, vec![(4.0, 4.0, 8.0, 8.0)]);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn blend_field_wraps_the_component_draw_in_set_blend() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("blend_field")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                local glow = ecs.newEntity("glow", nil, 0, 0)
+                ecs.addComponent(glow, core.Rect2D).blend = "additive"
+                local plain = ecs.newEntity("plain", nil, 0, 0)
+                plain.z = 1
+                ecs.addComponent(plain, core.Rect2D)
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        assert!(matches!(
+            commands.as_slice(),
+            [
+                DrawCommand::SetBlend(BlendMode::Additive),
+                DrawCommand::Rect { .. },
+                DrawCommand::SetBlend(BlendMode::Alpha),
+                DrawCommand::Rect { .. },
+            ]
+        ));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn particle_emitter_spawns_moves_and_expires_particles() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("particle_emitter")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                app.setMaxDelta(1)
+                local entity = ecs.newEntity("sparks", nil, 100, 50)
+                emitter = ecs.addComponent(entity, core.ParticleEmitter2D)
+                emitter.rate = 10
+                emitter.lifetime = 0.5
+                emitter.velocity_min_x = 20
+                emitter.velocity_max_x = 20
+                emitter.velocity_min_y = 0
+                emitter.velocity_max_y = 0
+                emitter.start_color = Color4(255, 0, 0)
+                emitter.end_color = Color4(0, 0, 255)
+                "#,
+            )
+            .exec()?;
+
+        let mut frames = Vec::new();
+        for _ in 0..3 {
+            runtime.update(0.25).map_err(mlua::Error::external)?;
+            let rects: Vec<(f32, PlatformColor)> =
+                crate::renderer::drain_commands(&runtime.render_state)
+                    .map_err(mlua::Error::external)?
+                    .into_iter()
+                    .filter_map(|command| match command {
+                        DrawCommand::Rect { x, color, .. } => Some((x, color)),
+                        _ => None,
+                    })
+                    .collect();
+            frames.push(rects);
+        }
+
+        let red = PlatformColor::rgba(255, 0, 0, 255);
+        let purple = PlatformColor::rgba(128, 0, 128, 255);
+        assert_eq!(frames[0], vec![(100.0, red); 2]);
+        assert_eq!(
+            frames[1],
+            vec![
+                (105.0, purple),
+                (105.0, purple),
+                (100.0, red),
+                (100.0, red),
+                (100.0, red)
+            ]
+        );
+        // the first two reached their lifetime and were dropped
+        assert_eq!(frames[2].len(), 5);
+        assert_eq!(
+            runtime
+                .lua
+                .load("return #emitter.particles")
+                .eval::<usize>()?,
+            5
+        );
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn resize_and_focus_callbacks_fire_on_changes() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("window_callbacks")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                resizes, focus = {}, nil
+                app.onResize(function(w, h) table.insert(resizes, w .. "x" .. h) end)
+                app.onFocusChanged(function(focused) focus = focused end)
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        runtime.set_platform_window_state(800.0, 600.0);
+        runtime.set_platform_focused(false);
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+
+        let (resizes, focus): (String, Option<bool>) = runtime
+            .lua
+            .load("return table.concat(resizes, \",\"), focus")
+            .eval()?;
+        assert_eq!(resizes, "800x600");
+        assert_eq!(focus, Some(false));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn fixed_timestep_runs_whole_steps_and_still_renders_every_frame() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("fixed_timestep")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                steps, step_dt = 0, 0
+                ecs.addSystem({
+                    update = function(_, dt)
+                        steps += 1
+                        step_dt = dt
+                    end,
+                })
+                ecs.addComponent(ecs.newEntity("box", nil, 0, 0), core.Rect2D)
+                app.setFixedTimestep(0.25)
+                app.setMaxDelta(10)
+                "#,
+            )
+            .exec()?;
+
+        let frame = |runtime: &mut Runtime, dt: f32| -> mlua::Result<(u32, f32, f32, usize)> {
+            runtime.update(dt).map_err(mlua::Error::external)?;
+            let drawn = crate::renderer::drain_commands(&runtime.render_state)
+                .map_err(mlua::Error::external)?
+                .len();
+            let (steps, step_dt, alpha): (u32, f32, f32) = runtime
+                .lua
+                .load(
+                    r#"
+                    local count = steps
+                    steps = 0
+                    return count, step_dt, app.getInterpolationAlpha()
+                    "#,
+                )
+                .eval()?;
+            Ok((steps, step_dt, alpha, drawn))
+        };
+
+        assert_eq!(frame(&mut runtime, 0.625)?, (2, 0.25, 0.5, 1));
+        assert_eq!(frame(&mut runtime, 0.125)?, (1, 0.25, 0.0, 1));
+        assert_eq!(frame(&mut runtime, 0.125)?, (0, 0.25, 0.5, 1));
+        // a long stall runs at most MAX_FIXED_STEPS_PER_FRAME steps
+        assert_eq!(frame(&mut runtime, 10.0)?.0, MAX_FIXED_STEPS_PER_FRAME);
+
+        runtime.lua.load("app.setFixedTimestep(nil)").exec()?;
+        assert_eq!(frame(&mut runtime, 0.1)?, (1, 0.1, 1.0, 1));
+        assert!(runtime.lua.load("app.setFixedTimestep(-1)").exec().is_err());
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn components_update_by_their_order_field() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("component_order")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                log = {}
+                local function logger(name, order)
+                    return {
+                        name = name,
+                        order = order,
+                        awake = function() end,
+                        update = function() table.insert(log, name) end,
+                    }
+                end
+                local entity = ecs.newEntity("player", nil, 0, 0)
+                ecs.addComponent(entity, logger("collision", 2))
+                ecs.addComponent(entity, logger("movement", 1))
+                ecs.addComponent(entity, logger("default"))
+                ecs.addComponent(entity, logger("input", 1))
+                ecs.addComponent(entity, logger("early", -5))
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let log: String = runtime.lua.load("return table.concat(log, \",\")").eval()?;
+        assert_eq!(log, "early,default,movement,input,collision");
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn paused_runtime_keeps_drawing_without_updating() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("paused")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                app.setShowFps(false)
+                ticks, render_dt = 0, nil
+                ecs.addSystem({ update = function() ticks += 1 end })
+                local entity = ecs.newEntity("player", nil, 0, 0)
+                ecs.addComponent(entity, {
+                    awake = function() end,
+                    update = function() ticks += 1 end,
+                })
+                ecs.addComponent(entity, {
+                    NEOLOVE_RENDERING = true,
+                    awake = function() end,
+                    update = function(_, _, dt) render_dt = dt end,
+                })
+                ecs.addComponent(entity, core.Rect2D)
+                app.setPaused(true)
+                app.setMaxDelta(1)
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(0.5).map_err(mlua::Error::external)?;
+        let drawn = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?
+            .len();
+        assert_eq!(drawn, 1);
+        let paused: (u32, f32, bool) = runtime
+            .lua
+            .load("return ticks, render_dt, app.isPaused()")
+            .eval()?;
+        assert_eq!(paused, (0, 0.0, true));
+
+        runtime.lua.load("app.setPaused(false)").exec()?;
+        runtime.update(0.5).map_err(mlua::Error::external)?;
+        let resumed: (u32, f32, bool) = runtime
+            .lua
+            .load("return ticks, render_dt, app.isPaused()")
+            .eval()?;
+        assert_eq!(resumed, (2, 0.5, false));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn time_scale_scales_gameplay_dt_only() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("time_scale")?;
+
+        runtime
+            .lua
+            .load(
+                r#"
+                system_dt = nil
+                ecs.addSystem({ update = function(_, dt) system_dt = dt end })
+                app.setTimeScale(0.5)
+                app.setMaxDelta(1)
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(0.5).map_err(mlua::Error::external)?;
+        let scaled: (f32, f32, f32) = runtime
+            .lua
+            .load("return system_dt, time.frame(), app.getTimeScale()")
+            .eval()?;
+        assert_eq!(scaled, (0.25, 0.25, 0.5));
+        // the fps readout follows the real frame time
+        assert_close(*runtime.frame_rate.borrow(), 2.0);
+
+        runtime.lua.load("app.setTimeScale(-3)").exec()?;
+        runtime.update(0.5).map_err(mlua::Error::external)?;
+        let frozen: f32 = runtime.lua.load("return system_dt").eval()?;
+        assert_eq!(frozen, 0.0);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn max_delta_defaults_to_a_tenth_of_a_second() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("max_delta")?;
+        assert_close(runtime.max_delta(), 0.1);
+
+        runtime.lua.load("app.setMaxDelta(0.25)").exec()?;
+        assert_close(runtime.max_delta(), 0.25);
+        assert_close(runtime.lua.load("return app.getMaxDelta()").eval()?, 0.25);
+        assert!(runtime.lua.load("app.setMaxDelta(0)").exec().is_err());
+        assert!(runtime
+            .lua
+            .load("app.setMaxDelta(math.huge)")
+            .exec()
+            .is_err());
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn long_frames_reach_systems_clamped_to_max_delta() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("max_delta_clamp")?;
+        runtime
+            .lua
+            .load(
+                r#"
+                system_dt = nil
+                ecs.addSystem({ update = function(_, dt) system_dt = dt end })
+                "#,
+            )
+            .exec()?;
+
+        runtime.update(2.0).map_err(mlua::Error::external)?;
+        let clamped: (f32, f32) = runtime.lua.load("return system_dt, time.frame()").eval()?;
+        assert_eq!(clamped, (0.1, 0.1));
+        // the fps average still sees the real stall
+        assert_close(*runtime.frame_rate.borrow(), 0.5);
+
+        runtime.lua.load("app.setMaxDelta(0.25)").exec()?;
+        runtime.update(2.0).map_err(mlua::Error::external)?;
+        let raised: f32 = runtime.lua.load("return system_dt").eval()?;
+        assert_eq!(raised, 0.25);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn disabling_clear_keeps_the_previous_frame() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("clear_enabled")?;
+        let mut renderer = crate::renderer::SoftwareRenderer::new(2, 2);
+        assert!(runtime
+            .lua
+            .load("return app.isClearEnabled()")
+            .eval::<bool>()?);
+
+        runtime
+            .lua
+            .load("app.bg = { r = 255, g = 0, b = 0, a = 255 }")
+            .exec()?;
+        runtime.update(0.016).map_err(mlua::Error::external)?;
+        renderer
+            .render(&runtime.platform, &runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        assert_eq!(&renderer.pixels()[0..4], &[255, 0, 0, 255]);
+
+        runtime
+            .lua
+            .load("app.setClearEnabled(false); app.bg = { r = 0, g = 0, b = 255, a = 255 }")
+            .exec()?;
+        runtime.update(0.016).map_err(mlua::Error::external)?;
+        renderer
+            .render(&runtime.platform, &runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        assert_eq!(&renderer.pixels()[0..4], &[255, 0, 0, 255]);
+        assert!(!runtime
+            .lua
+            .load("return app.isClearEnabled()")
+            .eval::<bool>()?);
+
+        runtime.lua.load("app.setClearEnabled(true)").exec()?;
+        runtime.update(0.016).map_err(mlua::Error::external)?;
+        renderer
+            .render(&runtime.platform, &runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        assert_eq!(&renderer.pixels()[0..4], &[0, 0, 255, 255]);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn fps_is_averaged_over_recent_frames() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("fps_average")?;
+
+        runtime.update(0.01).map_err(mlua::Error::external)?;
+        runtime.update(0.03).map_err(mlua::Error::external)?;
+        let (fps, frame_time): (f32, f32) = runtime
+            .lua
+            .load("return app.getFps(), app.getFrameTime()")
+            .eval()?;
+        assert_close(fps, 50.0);
+        assert_close(frame_time, 20.0);
+
+        // older frames fall out of the window
+        for _ in 0..FRAME_RATE_SAMPLES {
+            runtime.update(0.025).map_err(mlua::Error::external)?;
+        }
+        let fps: f32 = runtime.lua.load("return app.getFps()").eval()?;
+        assert_close(fps, 40.0);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn get_component_finds_core_and_named_components() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("get_component")?;
+
+        let found: (bool, bool, bool, bool, bool) = runtime
+            .lua
+            .load(
+                r#"
+                local entity = ecs.newEntity("thing", nil, 0, 0)
+                local rect = ecs.addComponent(entity, core.Rect2D)
+                local label = ecs.addComponent(entity, core.TextLabel)
+                local custom = ecs.addComponent(entity, {
+                    name = "Health",
+                    awake = function() end,
+                    update = function() end,
+                })
+                return ecs.getComponent(entity, "Rect2D") == rect,
+                    ecs.getComponent(entity, "TextLabel") == label,
+                    ecs.getComponent(entity, "TextBox") == label,
+                    entity:GetComponent("Health") == custom,
+                    ecs.getComponent(entity, "Image2D") == nil
+                "#,
+            )
+            .eval()?;
+        assert_eq!(found, (true, true, true, true, true));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn delete_entity_runs_component_destroy_hooks() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("delete_destroy")?;
+
+        let destroyed: i64 = runtime
+            .lua
+            .load(
+                r#"
+                local destroyed = 0
+                local function tracked()
+                    return {
+                        awake = function() end,
+                        update = function() end,
+                        onDestroy = function(entity, component)
+                            destroyed += 1
+                        end,
+                    }
+                end
+                local parent = ecs.newEntity("parent", nil, 0, 0)
+                local child = ecs.newEntity("child", parent, 0, 0)
+                ecs.addComponent(parent, tracked())
+                ecs.addComponent(child, tracked())
+                ecs.addComponent(child, tracked())
+                ecs.deleteEntity(parent)
+                return destroyed
+                "#,
+            )
+            .eval()?;
+        assert_eq!(destroyed, 3);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn clicks_hit_entities_through_a_moved_camera() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("camera_click")?;
+        runtime
+            .lua
+            .load(
+                r#"
+                clicks = 0
+                local button = ecs.newEntity("button", nil)
+                button.x = 1000
+                button.y = 1000
+                ecs.addComponent(button, core.Rect2D)
+                button:listen("leftClick", function()
+                    clicks += 1
+                end)
+                -- the button's center lands in the middle of the window at twice its size
+                camera.setPosition(1016, 1016)
+                camera.setZoom(2)
+                "#,
+            )
+            .exec()?;
+
+        let window = runtime
+            .platform
+            .lock()
+            .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+            .window();
+        let mut click_at = |x: f32, y: f32| -> mlua::Result<u32> {
+            {
+                let mut platform = runtime.platform.lock().unwrap();
+                platform.begin_frame();
+                platform.set_mouse_position(x, y);
+                platform
+                    .input_mut()
+                    .mouse_pressed
+                    .insert("left".to_string());
+            }
+            runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+            runtime.lua.load("return clicks").eval()
+        };
+
+        // inside the zoomed button but outside its unmoved world bounds
+        assert_eq!(
+            click_at(window.width * 0.5 + 20.0, window.height * 0.5 + 20.0)?,
+            1
+        );
+        // where the button would be without the camera
+        assert_eq!(click_at(1010.0, 1010.0)?, 1);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn mouse_world_position_follows_camera() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("mouse_world_position")?;
+        runtime
+            .platform
+            .lock()
+            .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+            .set_mouse_position(100.0, 50.0);
+
+        let (screen_x, screen_y, world_x, world_y): (f32, f32, f32, f32) = runtime
+            .lua
+            .load(
+                r#"
+                camera.setPosition(1000, 0)
+                camera.setZoom(2)
+                local sx, sy = input.getMousePosition()
+                local wx, wy = input.getMouseWorldPosition()
+                return sx, sy, wx, wy
+                "#,
+            )
+            .eval()?;
+        assert_close(screen_x, 100.0);
+        assert_close(screen_y, 50.0);
+        assert_close(world_x, 890.0);
+        assert_close(world_y, -95.0);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn gamepad_queries_read_platform_snapshot() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("gamepad_input")?;
+        {
+            let mut gamepad = crate::platform::GamepadState::default();
+            gamepad.buttons_down.insert("a".to_string());
+            gamepad.axes.insert("leftx".to_string(), -0.5);
+            runtime
+                .platform
+                .lock()
+                .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+                .input_mut()
+                .gamepads = vec![gamepad];
+        }
+
+        let result: (i64, bool, bool, bool, f32, f32, bool) = runtime
+            .lua
+            .load(
+                r#"
+                local ok = pcall(input.isGamepadButtonDown, 1, "not a button")
+                return input.gamepadCount(),
+                    input.isGamepadButtonDown(1, "Cross"),
+                    input.isGamepadButtonDown(1, "b"),
+                    input.isGamepadButtonDown(2, "a"),
+                    input.getGamepadAxis(1, "leftX"),
+                    input.getGamepadAxis(1, "rt"),
+                    ok
+                "#,
+            )
+            .eval()?;
+        assert_eq!(result.0, 1);
+        assert!(result.1);
+        assert!(!result.2);
+        assert!(!result.3);
+        assert_close(result.4, -0.5);
+        assert_close(result.5, 0.0);
+        assert!(!result.6);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn transform_distance_and_angle_use_world_positions() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("transform_distance")?;
+
+        let (distance, angle): (f32, f32) = runtime
+            .lua
+            .load(
+                r#"
+                local parent = ecs.newEntity("parent", nil, 10, 10)
+                local a = ecs.newEntity("a", parent, 0, 0)
+                local b = ecs.newEntity("b", nil, 10, 13)
+                return transform.distance(a, b), transform.angleBetween(a, b)
+                "#,
+            )
+            .eval()?;
+        assert_close(distance, 3.0);
+        assert_close(angle, std::f32::consts::FRAC_PI_2);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn do_they_overlap_checks_every_pair_once() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("do_they_overlap")?;
+
+        let result: (bool, bool, bool) = runtime
+            .lua
+            .load(
+                r#"
+                local function boxAt(name, x, y)
+                    local entity = ecs.newEntity(name, nil, x, y)
+                    entity.size_x = 10
+                    entity.size_y = 10
+                    return entity
+                end
+                local a = boxAt("a", 0, 0)
+                local b = boxAt("b", 50, 0)
+                local c = boxAt("c", 55, 5)
+                return transform.doTheyOverlap({ a, b }),
+                    transform.doTheyOverlap({ a, b, c }),
+                    transform.doTheyOverlap({ a, a })
+                "#,
+            )
+            .eval()?;
+        assert_eq!(result, (false, true, false));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn world_transform_rotates_children_around_parent() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("world_transform")?;
+
+        let (x, y, rotation): (f32, f32, f32) = runtime
+            .lua
+            .load(
+                r#"
+                local parent = ecs.newEntity("parent", nil, 100, 100)
+                parent.rotation = math.pi / 2
+                local child = ecs.newEntity("child", parent, 10, 0)
+                return transform.getWorldTransform(child)
+                "#,
+            )
+            .eval()?;
+        assert_close(x, 100.0);
+        assert_close(y, 110.0);
+        assert_close(rotation, std::f32::consts::FRAC_PI_2);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn app_window_setters_queue_platform_requests() -> mlua::Result<()> {
+        let (runtime, root) = start_test_runtime("app_window_setters")?;
+
+        let (accepted, rejected): (bool, bool) = runtime
+            .lua
+            .load(
+                r#"
+                app.setTitle("Level 2")
+                app.setFullscreen(true)
+                app.setVsync(true)
+                local accepted = app.setWindowSize(800, 600)
+                local rejected = app.setWindowSize(0, 600)
+                return accepted, rejected
+                "#,
+            )
+            .eval()?;
+        assert!(accepted);
+        assert!(!rejected);
+
+        let requests = runtime
+            .platform
+            .lock()
+            .map_err(|_| mlua::Error::external("platform lock poisoned"))?
+            .take_window_requests();
+        assert_eq!(requests.title.as_deref(), Some("Level 2"));
+        assert_eq!(requests.size, Some((800.0, 600.0)));
+        assert_eq!(requests.fullscreen, Some(true));
+        assert_eq!(requests.vsync, Some(true));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn content_text_wraps_at_max_width() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("text_max_width")?;
+        runtime.lua.load("app.setShowFps(false)").exec()?;
+
+        let label: Table = runtime
+            .lua
+            .load(
+                r#"
+                local entity = ecs.newEntity("dialogue", nil, 300, 50)
+                local label = ecs.addComponent(entity, core.TextBox)
+                label.text = "a line of dialogue long enough to need several rows"
+                label.scale = 16
+                label.max_width = 120
+                label.align_x = "right"
+                return label
+                "#,
+            )
+            .eval()?;
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        let Some(DrawCommand::Text(request)) = commands
+            .iter()
+            .find(|command| matches!(command, DrawCommand::Text(_)))
+        else {
+            panic!("expected a text command, got {commands:?}");
+        };
+
+        let (width, height): (f32, f32) = (label.get("dx")?, label.get("dy")?);
+        assert!(label.get::<usize>("line_count")? > 1);
+        assert!(width > 0.0 && width <= 120.0, "{width}");
+        assert!(height > 16.0, "{height}");
+        // the max_width box, not the measured block, ends on the origin
+        assert_close(request.bounds.x, 180.0);
+        assert_close(request.bounds.w, 120.0);
+        assert!(matches!(request.wrap, TextWrapMode::Word));
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
+
+    #[test]
+    fn content_text_align_anchors_on_entity_origin() -> mlua::Result<()> {
+        let (mut runtime, root) = start_test_runtime("text_align_anchor")?;
+        runtime.lua.load("app.setShowFps(false)").exec()?;
+
+        let label: Table = runtime
+            .lua
+            .load(
+                r#"
+                local entity = ecs.newEntity("label", nil, 100, 50)
+                local label = ecs.addComponent(entity, core.TextBox)
+                label.text = "Centered"
+                label.align_x = "center"
+                label.baseline = true
+                return label
+                "#,
+            )
+            .eval()?;
+        runtime.update(1.0 / 60.0).map_err(mlua::Error::external)?;
+        let commands = crate::renderer::drain_commands(&runtime.render_state)
+            .map_err(mlua::Error::external)?;
+        let Some(DrawCommand::Text(request)) = commands
+            .iter()
+            .find(|command| matches!(command, DrawCommand::Text(_)))
+        else {
+            panic!("expected a text command, got {commands:?}");
+        };
+
+        let width: f32 = label.get("dx")?;
+        assert!(width > 0.0);
+        assert_close(request.bounds.x, 100.0 - width * 0.5);
+        assert!(request.bounds.y < 50.0);
+        assert_close(request.pivot.x, 100.0);
+
+        std::fs::remove_dir_all(root).map_err(mlua::Error::external)?;
+        Ok(())
+    }
 }